@@ -0,0 +1,75 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use relative_path::{PathExt, RelativePathBuf};
+use thiserror::Error;
+
+use crate::{
+    config::RemoteConfig,
+    repo::{url_encode_path, Package, Version},
+};
+
+#[derive(Error, Debug)]
+#[error("archive destination already exists: `{0}`")]
+pub(crate) struct ArchiveDestinationExists(std::path::PathBuf);
+
+/// Files that stay in the version folder when a version is archived but kept in the index,
+/// since they are needed to generate the version's entry.
+const KEPT_FILES: &[&str] = &["version.toml", "CHANGELOG.txt"];
+
+/// Move a version folder to `{destination}/{identifier}/{version}`.
+///
+/// If `url` is given, the version is kept in the index: its config and changelog stay in the repository, and its
+/// sources are downloaded from `{url}/{identifier}/{version}/...` instead. Otherwise the whole folder is removed
+/// from the repository.
+pub(crate) fn archive_version(
+    pkg: &Package,
+    ver: &mut Version,
+    destination: &Path,
+    url: Option<&str>,
+) -> Result<()> {
+    let relpath = RelativePathBuf::from(pkg.identifier().as_ref()).join(ver.name().as_ref());
+    let target = relpath.to_logical_path(destination);
+    if target.exists() {
+        return Err(ArchiveDestinationExists(target).into());
+    }
+
+    // find the sources before moving anything, so that the list can be stored in the version config
+    let files: Vec<RelativePathBuf> = ver
+        .sources(pkg)?
+        .iter()
+        // external sources keep their own URL, they stay in the `external` list
+        .filter(|src| !src.is_external())
+        .map(|src| src.path().relative_to(ver.path()))
+        .collect::<Result<_, _>>()?;
+
+    // rename fails across filesystems, so copy instead
+    crate::copy_dir_all(ver.path(), &target)?;
+
+    let Some(url) = url else {
+        fs::remove_dir_all(ver.path())?;
+        return Ok(());
+    };
+
+    for file in files.iter() {
+        if !KEPT_FILES.contains(&file.as_str()) {
+            fs::remove_file(file.to_logical_path(ver.path()))?;
+        }
+    }
+    // remove the subfolders left empty by the removed files
+    for entry in walkdir::WalkDir::new(ver.path()).contents_first(true) {
+        let entry = entry?;
+        if entry.file_type().is_dir() && entry.path() != ver.path() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+
+    ver.set_remote(RemoteConfig {
+        url: format!(
+            "{}/{}",
+            url.trim_end_matches('/'),
+            url_encode_path(&relpath)
+        ),
+        files,
+    })
+}
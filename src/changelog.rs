@@ -0,0 +1,196 @@
+//! Combine the changelogs of many versions into a single Markdown document, e.g. for release announcements.
+//! Also generate the changelog of a new version from the git history of its source folder.
+
+use std::{ffi::OsStr, path::Path, process::Command};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::repo::{GitCommitError, Package, Repository, Version};
+
+/// The versions of a package to include, newest first. Yanked versions are skipped.
+/// With `since`, only versions newer than the given version are included.
+fn versions_since(pkg: &Package, since: Option<&str>) -> Result<Vec<Version>> {
+    let mut versions: Vec<Version> = pkg
+        .versions()?
+        .into_iter()
+        .filter(|ver| !ver.yanked())
+        .filter(|ver| {
+            since.is_none_or(|since| {
+                Version::compare_version_names(&ver.name(), since) == std::cmp::Ordering::Greater
+            })
+        })
+        .collect();
+    versions.sort_by(|a, b| Version::compare_version_names(&b.name(), &a.name()));
+    Ok(versions)
+}
+
+/// The changelog of each version of the package as Markdown, using headings of the given level.
+/// Returns None if there are no versions to include.
+fn package_changelog(
+    repo: &Repository,
+    pkg: &Package,
+    since: Option<&str>,
+    level: usize,
+) -> Result<Option<String>> {
+    let versions = versions_since(pkg, since)?;
+    if versions.is_empty() {
+        return Ok(None);
+    }
+    let heading = "#".repeat(level);
+    let mut result = vec![];
+    for ver in versions {
+        let text = ver
+            .changelog_text(repo, pkg)?
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| "_No changelog._".into());
+        result.push(format!(
+            "{heading} {} ({})\n\n{}",
+            ver.name(),
+            ver.time().format("%Y-%m-%d"),
+            text
+        ));
+    }
+    Ok(Some(result.join("\n\n")))
+}
+
+/// The changelogs of a single package, or of every package in the repository, as a Markdown document.
+pub(crate) fn generate(
+    repo: &Repository,
+    pkg: Option<&Package>,
+    since: Option<&str>,
+) -> Result<String> {
+    if let Some(pkg) = pkg {
+        let body =
+            package_changelog(repo, pkg, since, 2)?.unwrap_or_else(|| "_No versions._".into());
+        return Ok(format!("# {}\n\n{}\n", pkg.name(), body));
+    }
+
+    let mut packages = repo.packages()?;
+    packages.sort_by_key(|pkg| pkg.identifier().to_string());
+    let mut sections = vec![format!("# {}", repo.identifier())];
+    for pkg in packages.iter() {
+        if let Some(body) = package_changelog(repo, pkg, since, 3)? {
+            sections.push(format!("## {}\n\n{}", pkg.name(), body));
+        }
+    }
+    Ok(sections.join("\n\n") + "\n")
+}
+
+/// Where to start collecting commits for a changelog generated from git.
+pub(crate) enum GitSince<'a> {
+    /// Commits made after the given time, e.g. the time of the previous version
+    Time(DateTime<Utc>),
+    /// Commits that aren't reachable from the given tag
+    Tag(&'a str),
+    /// Every commit
+    Beginning,
+}
+
+/// Format the output of `git log --format=%s` as a list, skipping empty and repeated subjects.
+/// Returns None if there are no subjects.
+fn format_commit_subjects(log: &str) -> Option<String> {
+    let mut subjects: Vec<&str> = vec![];
+    for subject in log.lines().map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        if !subjects.contains(&subject) {
+            subjects.push(subject);
+        }
+    }
+    if subjects.is_empty() {
+        return None;
+    }
+    Some(subjects.iter().map(|x| format!("- {x}\n")).collect())
+}
+
+/// A changelog listing the subjects of the commits that changed the given path, oldest first.
+/// Returns None if no commits were found.
+pub(crate) fn from_git(path: &Path, since: GitSince) -> Result<Option<String>> {
+    // run git in the folder of the path, so the pathspec must be relative to that folder rather than to the
+    // current directory
+    let (dir, pathspec) = if path.is_dir() {
+        (path, OsStr::new("."))
+    } else {
+        let dir = path
+            .parent()
+            .filter(|x| !x.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        (dir, path.file_name().unwrap_or(path.as_os_str()))
+    };
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .args(["log", "--no-merges", "--reverse", "--format=%s"]);
+    match since {
+        GitSince::Time(time) => command.arg(format!("--since={}", time.to_rfc3339())),
+        GitSince::Tag(tag) => command.arg(format!("{tag}..HEAD")),
+        GitSince::Beginning => &mut command,
+    };
+    let output = command
+        .arg("--")
+        .arg(pathspec)
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+
+    if !output.status.success() {
+        return Err(GitCommitError::FailedToGetGitLog(dir.into()).into());
+    }
+
+    Ok(format_commit_subjects(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_commit_subjects_01() {
+        assert_eq!(
+            format_commit_subjects("Fix a bug\n\nAdd a feature\nFix a bug\n"),
+            Some("- Fix a bug\n- Add a feature\n".into())
+        );
+        assert_eq!(format_commit_subjects(" \n"), None);
+    }
+
+    #[test]
+    fn from_git_01() {
+        let temp = tempfile::tempdir_in(".").unwrap();
+        // a relative path, like the ones given on the command line
+        let root = temp
+            .path()
+            .strip_prefix(std::env::current_dir().unwrap())
+            .unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(root)
+                .args(["-c", "user.name=Me", "-c", "user.email=me@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        std::fs::create_dir_all(root.join("src/foo")).unwrap();
+        std::fs::write(root.join("src/foo/a.lua"), "1").unwrap();
+        std::fs::write(root.join("b.lua"), "1").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "Add foo"]);
+        std::fs::write(root.join("b.lua"), "2").unwrap();
+        git(&["commit", "--quiet", "-am", "Change b"]);
+
+        assert_eq!(
+            from_git(&root.join("src/foo"), GitSince::Beginning)
+                .unwrap()
+                .as_deref(),
+            Some("- Add foo\n")
+        );
+        assert_eq!(
+            from_git(&root.join("b.lua"), GitSince::Beginning)
+                .unwrap()
+                .as_deref(),
+            Some("- Add foo\n- Change b\n")
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Find features of a generated index that older ReaPack clients don't support.
+
+use crate::{index::IndexModel, repo::Version};
+
+/// Package types, and the ReaPack version that added them. Types that aren't listed are supported by every version.
+const PACKAGE_TYPES: &[(&str, &str)] = &[
+    ("langpack", "1.1"),
+    ("webinterface", "1.1"),
+    ("projecttpl", "1.2"),
+    ("tracktpl", "1.2"),
+    ("midinotenames", "1.2"),
+    ("autoitem", "1.2"),
+];
+
+/// Source platforms, and the ReaPack version that added them. Platforms that aren't listed are supported by every
+/// version.
+const PLATFORMS: &[(&str, &str)] = &[
+    ("linux", "1.2"),
+    ("linux32", "1.2"),
+    ("linux64", "1.2"),
+    ("darwin-arm64", "1.2.3"),
+    ("linux-armv7l", "1.2.3"),
+    ("linux-aarch64", "1.2.3"),
+    ("windows-arm64ec", "1.2.5"),
+];
+
+/// The ReaPack version that added section lists in the `main` attribute of sources, e.g. `main="midi_editor"`.
+const SECTION_LIST_SINCE: &str = "1.1";
+
+/// The ReaPack version that added the `type` attribute of sources.
+const SOURCE_TYPE_SINCE: &str = "1.1";
+
+/// The ReaPack version that added the `hash` attribute of sources.
+const HASH_SINCE: &str = "1.2.4";
+
+/// A feature used by the index that needs a newer ReaPack than the minimum version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Incompatibility {
+    /// Identifier of the package that uses the feature
+    pub(crate) package: String,
+    pub(crate) feature: String,
+    /// The ReaPack version that added the feature
+    pub(crate) since: &'static str,
+}
+
+fn is_newer(since: &str, min_version: &str) -> bool {
+    Version::compare_version_names(since, min_version).is_gt()
+}
+
+/// Find the features of the index that ReaPack clients older than `min_version` don't support.
+pub(crate) fn check(index: &IndexModel, min_version: &str) -> Vec<Incompatibility> {
+    let mut result = vec![];
+    let mut add = |package: &str, feature: String, since: &'static str| {
+        let incompatibility = Incompatibility {
+            package: package.into(),
+            feature,
+            since,
+        };
+        if is_newer(since, min_version) && !result.contains(&incompatibility) {
+            result.push(incompatibility);
+        }
+    };
+    for pkg in index.packages.values() {
+        if let Some(typ) = pkg.attributes.get("type") {
+            if let Some((_, since)) = PACKAGE_TYPES.iter().find(|(x, _)| x == typ) {
+                add(&pkg.name, format!("package type `{typ}`"), since);
+            }
+        }
+        for src in pkg.versions.values().flat_map(|ver| ver.sources.values()) {
+            if src.attributes.get("main").is_some_and(|x| x != "true") {
+                add(
+                    &pkg.name,
+                    "section lists in `main`".into(),
+                    SECTION_LIST_SINCE,
+                );
+            }
+            if src.attributes.contains_key("type") {
+                add(&pkg.name, "source `type`".into(), SOURCE_TYPE_SINCE);
+            }
+            if src.attributes.contains_key("hash") {
+                add(&pkg.name, "source `hash`".into(), HASH_SINCE);
+            }
+            if let Some(platform) = src.attributes.get("platform") {
+                if let Some((_, since)) = PLATFORMS.iter().find(|(x, _)| x == platform) {
+                    add(&pkg.name, format!("platform `{platform}`"), since);
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_01() {
+        let index = IndexModel::parse(
+            r#"<index version="1" name="repo">
+	<category name="Category">
+		<reapack desc="A" type="autoitem" name="pkg-a">
+			<version name="1.0" author="Me" time="2024-01-01T00:00:00+00:00">
+				<source file="../pkg-a/a.lua" main="main" hash="1220ab">https://example.com/a.lua</source>
+				<source file="../pkg-a/b.lua" platform="darwin-arm64">https://example.com/b.lua</source>
+			</version>
+		</reapack>
+	</category>
+</index>"#,
+        )
+        .unwrap();
+        let features = |min_version| {
+            check(&index, min_version)
+                .into_iter()
+                .map(|x| x.feature)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            features("1.0"),
+            vec![
+                "package type `autoitem`",
+                "section lists in `main`",
+                "source `hash`",
+                "platform `darwin-arm64`"
+            ]
+        );
+        assert_eq!(features("1.2.3"), vec!["source `hash`"]);
+        assert!(features("1.2.4").is_empty());
+    }
+}
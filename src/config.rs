@@ -1,6 +1,11 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    str::FromStr,
+};
 
 use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
@@ -147,26 +152,255 @@ impl<'de> Deserialize<'de> for ActionListSection {
     }
 }
 
+/// As defined in:
+/// https://github.com/cfillion/reapack/wiki/Index-Format#source-element
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum Platform {
+    All,            // all
+    Darwin,         // darwin
+    Darwin32,       // darwin32
+    Darwin64,       // darwin64
+    DarwinArm64,    // darwin-arm64
+    Linux,          // linux
+    Linux32,        // linux32
+    Linux64,        // linux64
+    LinuxArmv7l,    // linux-armv7l
+    LinuxAarch64,   // linux-aarch64
+    Windows,        // windows
+    Win32,          // win32
+    Win64,          // win64
+    WindowsArm64ec, // windows-arm64ec
+}
+
+#[derive(Error, Debug)]
+#[error("invalid platform: {0}")]
+pub(crate) struct InvalidPlatform(String);
+
+impl FromStr for Platform {
+    type Err = InvalidPlatform;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "darwin" => Ok(Self::Darwin),
+            "darwin32" => Ok(Self::Darwin32),
+            "darwin64" => Ok(Self::Darwin64),
+            "darwin-arm64" => Ok(Self::DarwinArm64),
+            "linux" => Ok(Self::Linux),
+            "linux32" => Ok(Self::Linux32),
+            "linux64" => Ok(Self::Linux64),
+            "linux-armv7l" => Ok(Self::LinuxArmv7l),
+            "linux-aarch64" => Ok(Self::LinuxAarch64),
+            "windows" => Ok(Self::Windows),
+            "win32" => Ok(Self::Win32),
+            "win64" => Ok(Self::Win64),
+            "windows-arm64ec" => Ok(Self::WindowsArm64ec),
+            _ => Err(InvalidPlatform(s.into())),
+        }
+    }
+}
+
+impl From<&Platform> for &str {
+    fn from(value: &Platform) -> Self {
+        match value {
+            Platform::All => "all",
+            Platform::Darwin => "darwin",
+            Platform::Darwin32 => "darwin32",
+            Platform::Darwin64 => "darwin64",
+            Platform::DarwinArm64 => "darwin-arm64",
+            Platform::Linux => "linux",
+            Platform::Linux32 => "linux32",
+            Platform::Linux64 => "linux64",
+            Platform::LinuxArmv7l => "linux-armv7l",
+            Platform::LinuxAarch64 => "linux-aarch64",
+            Platform::Windows => "windows",
+            Platform::Win32 => "win32",
+            Platform::Win64 => "win64",
+            Platform::WindowsArm64ec => "windows-arm64ec",
+        }
+    }
+}
+
+impl Serialize for Platform {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.into())
+    }
+}
+
+impl<'de> Deserialize<'de> for Platform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        text.parse::<Platform>().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The version in the XML declaration of the index.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum XmlVersion {
+    /// XML 1.0, as written by ReaPack's own indexer
+    #[default]
+    #[serde(rename = "1.0")]
+    #[value(name = "1.0")]
+    V1_0,
+    #[serde(rename = "1.1")]
+    #[value(name = "1.1")]
+    V1_1,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct RepositoryConfig {
     pub(crate) identifier: Option<String>,
     pub(crate) author: String,
     pub(crate) url_pattern: String,
+    /// Program and arguments that print the URL of a source, replacing `url_pattern`
+    pub(crate) url_command: Option<Vec<String>>,
+    pub(crate) category_order: Option<Vec<RelativePathBuf>>,
+    pub(crate) pin_git_commits: Option<bool>,
+    /// Length of the `git_commit_short` URL variable
+    pub(crate) git_commit_short_length: Option<usize>,
+    pub(crate) changelog_url_pattern: Option<String>,
+    pub(crate) authors: Option<Vec<String>>,
+    pub(crate) output_path: Option<PathBuf>,
+    /// The pandoc executable used to convert READMEs. Relative paths are resolved from the repository folder
+    pub(crate) pandoc_path: Option<PathBuf>,
+    pub(crate) lint_changelogs: Option<bool>,
+    pub(crate) exclude_deprecated: Option<bool>,
+    pub(crate) legacy_main_attribute: Option<bool>,
+    pub(crate) source_hashes: Option<bool>,
+    /// The oldest ReaPack version the index should work with, features newer than this are warned about on export
+    pub(crate) min_reapack_version: Option<String>,
+    /// Default `max_versions_in_index` of packages
+    pub(crate) max_versions_in_index: Option<usize>,
+    pub(crate) xml_version: Option<XmlVersion>,
+    /// Whether the index is indented
+    pub(crate) xml_indent: Option<bool>,
+    /// Whether the attributes of each element are sorted by name, instead of in the order they are added
+    pub(crate) xml_sort_attributes: Option<bool>,
+    /// Links shown in ReaPack's About dialog of the repository
+    pub(crate) website: Option<String>,
+    pub(crate) donation: Option<String>,
+    pub(crate) screenshot: Option<String>,
+    pub(crate) storage: Option<StorageConfig>,
+}
+
+/// An S3-compatible bucket that sources are uploaded to with `export --upload`, e.g. on AWS S3, Cloudflare R2 or
+/// Backblaze B2. The credentials are read from the `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY` environment
+/// variables, so that they aren't committed with the config.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct StorageConfig {
+    /// URL of the S3 API, e.g. `https://s3.us-east-1.amazonaws.com`
+    pub(crate) endpoint: String,
+    pub(crate) bucket: String,
+    /// Defaults to `auto`, which is accepted by R2. Other providers need the region of the bucket
+    pub(crate) region: Option<String>,
+    /// Public URL of the bucket that ReaPack downloads from
+    pub(crate) public_url: String,
+    /// Folder in the bucket that sources are uploaded to. Defaults to the root of the bucket
+    pub(crate) prefix: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct PackageConfig {
     pub(crate) name: Option<String>,
+    /// Short description shown in ReaPack's package list. Defaults to the name
+    pub(crate) description: Option<String>,
     pub(crate) category: RelativePathBuf,
     #[serde(rename = "type")]
     pub(crate) pkg_type: PackageType,
     pub(crate) identifier: Option<String>,
     pub(crate) author: Option<String>,
+    /// Overrides `url_pattern` of the repository
+    pub(crate) url_pattern: Option<String>,
     pub(crate) entrypoints: Option<HashMap<ActionListSection, Vec<String>>>,
+    /// Sections of individual files, by their path in the version folder. Takes priority over `entrypoints`
+    pub(crate) entrypoint_files: Option<BTreeMap<RelativePathBuf, Vec<ActionListSection>>>,
+    pub(crate) max_versions_in_index: Option<usize>,
+    pub(crate) deprecated: Option<Deprecation>,
+    /// Platform of the files matching each glob pattern, for packages that ship different files per OS
+    pub(crate) platform: Option<BTreeMap<String, Platform>>,
+    /// Type of the files matching each glob pattern, for packages that ship files of other types
+    pub(crate) types: Option<BTreeMap<String, PackageType>>,
+    pub(crate) links: Option<PackageLinks>,
+    /// Glob patterns of files in version folders that aren't sources. Replaces the default patterns
+    pub(crate) exclude: Option<Vec<String>>,
+    /// Glob patterns of files in version folders that are sources. Other files are ignored
+    pub(crate) include: Option<Vec<String>>,
+}
+
+/// Links shown in ReaPack's About dialog of a package.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct PackageLinks {
+    pub(crate) website: Option<String>,
+    pub(crate) donation: Option<String>,
+    pub(crate) screenshot: Option<Vec<String>>,
+}
+
+/// Either `deprecated = true`, or a message explaining the deprecation, e.g. `deprecated = "Use X instead"`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum Deprecation {
+    Flag(bool),
+    Message(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct VersionConfig {
     pub(crate) time: DateTime<Utc>,
     pub(crate) entrypoints: Option<HashMap<ActionListSection, Vec<String>>>,
+    /// Overrides `entrypoint_files` of the package
+    pub(crate) entrypoint_files: Option<BTreeMap<RelativePathBuf, Vec<ActionListSection>>>,
+    pub(crate) author: Option<String>,
+    /// Overrides `url_pattern` of the package
+    pub(crate) url_pattern: Option<String>,
+    pub(crate) yanked: Option<bool>,
+    pub(crate) remote: Option<RemoteConfig>,
+    /// Overrides `platform` of the package
+    pub(crate) platform: Option<BTreeMap<String, Platform>>,
+    /// Overrides `types` of the package
+    pub(crate) types: Option<BTreeMap<String, PackageType>>,
+    pub(crate) external: Option<Vec<ExternalSource>>,
+    /// Path each file is installed to relative to the package's install folder, by its path in the version folder
+    pub(crate) targets: Option<BTreeMap<RelativePathBuf, RelativePathBuf>>,
+    /// Overrides `exclude` of the package
+    pub(crate) exclude: Option<Vec<String>>,
+    /// Overrides `include` of the package
+    pub(crate) include: Option<Vec<String>>,
+    /// Platforms of the files matching each glob pattern, for files that are listed once for each platform,
+    /// e.g. a Windows DLL that works on both win32 and win64. Takes priority over `platform`
+    pub(crate) arch: Option<BTreeMap<String, Vec<Platform>>>,
+}
+
+/// A source file that is downloaded from an absolute URL instead of the version folder, e.g. a release asset.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ExternalSource {
+    pub(crate) url: String,
+    /// Path the file is installed to, as if it were in the version folder
+    pub(crate) file: RelativePathBuf,
+    /// Overrides `platform` patterns
+    pub(crate) platform: Option<Platform>,
+    /// Overrides `types` patterns
+    #[serde(rename = "type")]
+    pub(crate) src_type: Option<PackageType>,
+}
+
+/// Source files that are hosted outside of the repository, e.g. archived versions or release assets.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct RemoteConfig {
+    /// Base URL that the file paths are relative to
+    pub(crate) url: String,
+    /// Paths of the source files, relative to the version folder
+    pub(crate) files: Vec<RelativePathBuf>,
+}
+
+/// Folders that packages are developed in, for the `sync` command.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct WorkspaceConfig {
+    /// Source folder of each package, by package identifier. Relative paths are relative to the workspace file
+    pub(crate) packages: BTreeMap<String, PathBuf>,
 }
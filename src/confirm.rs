@@ -0,0 +1,53 @@
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Set how confirmation prompts are answered for the rest of the program.
+///
+/// - `assume_yes`: answer yes to every prompt without asking
+/// - `no_input`: never ask, and use each prompt's default answer instead
+pub(crate) fn configure(assume_yes: bool, no_input: bool) {
+    ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+    NO_INPUT.store(no_input, Ordering::Relaxed);
+}
+
+/// Ask the user a yes/no question before a destructive operation.
+///
+/// If `--yes` was given, the answer is always yes. If `--no-input` was given, or the program is not attached to
+/// a terminal, the question isn't asked and the default answer is used instead.
+pub(crate) fn confirm(message: &str, default: bool) -> Result<bool> {
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    if NO_INPUT.load(Ordering::Relaxed) || !std::io::stdin().is_terminal() {
+        log::info!(
+            "{message} {} (no input available, pass --yes to confirm)",
+            if default { "yes" } else { "no" }
+        );
+        return Ok(default);
+    }
+    Ok(inquire::Confirm::new(message)
+        .with_default(default)
+        .prompt()?)
+}
+
+/// Ask the user to choose one of the options.
+///
+/// If the question can't be asked, e.g. when `--no-input` was given or the program is not attached to a terminal,
+/// the default option is used instead. `--yes` has no effect, since there is no "yes" option.
+pub(crate) fn choose<'a>(message: &str, options: &[&'a str], default: &'a str) -> Result<&'a str> {
+    if NO_INPUT.load(Ordering::Relaxed) || !std::io::stdin().is_terminal() {
+        log::info!("{message} {default} (no input available)");
+        return Ok(default);
+    }
+    let starting_cursor = options.iter().position(|x| *x == default).unwrap_or(0);
+    Ok(inquire::Select::new(message, options.to_vec())
+        .with_starting_cursor(starting_cursor)
+        .prompt()?)
+}
@@ -0,0 +1,159 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::repo::{pandoc_executable, Package, Repository};
+
+/// The result of a single environment check.
+pub(crate) struct Diagnostic {
+    pub(crate) name: &'static str,
+    pub(crate) outcome: Outcome,
+}
+
+pub(crate) enum Outcome {
+    /// The check passed, with some details to show the user
+    Ok(String),
+    /// The check is not relevant for this repository
+    Skipped(String),
+    /// The check failed, with a description of the problem and how to fix it
+    Failed { problem: String, remedy: String },
+}
+
+/// Run a command and return the first line of its output, or None if the command failed.
+fn command_output(dir: &Path, program: impl AsRef<OsStr>, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+/// Check whether any README file in the repository needs to be converted by pandoc.
+fn find_markdown_readme(repo: &Repository) -> Option<String> {
+    let needs_pandoc =
+        |dir: &Path| dir.join("README.md").exists() && !dir.join("README.rtf").exists();
+    if needs_pandoc(repo.path()) {
+        return Some(repo.path().join("README.md").display().to_string());
+    }
+    let package_paths = Package::discover_package_paths(repo.path()).unwrap_or_default();
+    package_paths
+        .iter()
+        .find(|path| needs_pandoc(path))
+        .map(|path| path.join("README.md").display().to_string())
+}
+
+pub(crate) fn diagnose(repo_path: &Path) -> Vec<Diagnostic> {
+    let mut result = vec![];
+
+    let repo = match Repository::read(repo_path) {
+        Ok(repo) => {
+            result.push(Diagnostic {
+                name: "repository",
+                outcome: Outcome::Ok(format!("found {}", repo.path().display())),
+            });
+            Some(repo)
+        }
+        Err(err) => {
+            result.push(Diagnostic {
+                name: "repository",
+                outcome: Outcome::Failed {
+                    problem: err.to_string(),
+                    remedy:
+                        "run `init` to create a repository, or fix the errors in the repository config"
+                            .into(),
+                },
+            });
+            None
+        }
+    };
+
+    // git
+    let uses_git = repo
+        .as_ref()
+        .map(|repo| repo.url_pattern().contains("{git_"))
+        .unwrap_or(true);
+    match command_output(repo_path, "git", &["--version"]) {
+        Some(version) => {
+            result.push(Diagnostic {
+                name: "git",
+                outcome: Outcome::Ok(version),
+            });
+            if !uses_git {
+                result.push(Diagnostic {
+                    name: "git work tree",
+                    outcome: Outcome::Skipped("url_pattern does not use git variables".into()),
+                });
+            } else if command_output(repo_path, "git", &["rev-parse", "--is-inside-work-tree"])
+                .is_some_and(|x| x == "true")
+            {
+                let commit = command_output(repo_path, "git", &["rev-parse", "HEAD"]);
+                result.push(Diagnostic {
+                    name: "git work tree",
+                    outcome: match commit {
+                        Some(commit) => Outcome::Ok(format!("current commit is {commit}")),
+                        None => Outcome::Failed {
+                            problem: "the git repository has no commits".into(),
+                            remedy: "commit your files with `git commit`, so that `{git_commit}` can be resolved".into(),
+                        },
+                    },
+                });
+            } else {
+                result.push(Diagnostic {
+                    name: "git work tree",
+                    outcome: Outcome::Failed {
+                        problem: "the repository is not inside a git work tree".into(),
+                        remedy: "run `git init` in the repository, or remove git variables from url_pattern".into(),
+                    },
+                });
+            }
+        }
+        None if uses_git => result.push(Diagnostic {
+            name: "git",
+            outcome: Outcome::Failed {
+                problem: "git is not installed or not in PATH".into(),
+                remedy: "install git from https://git-scm.com/ and make sure it is in your PATH"
+                    .into(),
+            },
+        }),
+        None => result.push(Diagnostic {
+            name: "git",
+            outcome: Outcome::Skipped("url_pattern does not use git variables".into()),
+        }),
+    }
+
+    // pandoc
+    let markdown_readme = repo.as_ref().and_then(find_markdown_readme);
+    let pandoc = match repo.as_ref().and_then(|repo| repo.pandoc_path()) {
+        Some(path) => pandoc_executable(&path),
+        None => PathBuf::from("pandoc"),
+    };
+    match (
+        command_output(repo_path, &pandoc, &["--version"]),
+        markdown_readme,
+    ) {
+        (Some(version), _) => result.push(Diagnostic {
+            name: "pandoc",
+            outcome: Outcome::Ok(version),
+        }),
+        (None, Some(readme)) => result.push(Diagnostic {
+            name: "pandoc",
+            outcome: Outcome::Failed {
+                problem: format!("pandoc is not installed, but is needed to convert {readme}"),
+                remedy: "install pandoc from https://pandoc.org/installing.html, set `pandoc_path` in repository.toml, or provide README.rtf files instead".into(),
+            },
+        }),
+        (None, None) => result.push(Diagnostic {
+            name: "pandoc",
+            outcome: Outcome::Skipped("no Markdown files need to be converted".into()),
+        }),
+    }
+
+    result
+}
@@ -0,0 +1,138 @@
+//! An Atom feed of the most recent versions across all packages, for release announcements.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use xml_builder::{XMLBuilder, XMLElement, XMLVersion};
+
+use crate::repo::{cdata, Package, Repository, Version};
+
+/// How many of the most recent versions are included in the feed.
+const FEED_ENTRIES: usize = 50;
+
+/// A unique and permanent ID for a version, as required by Atom.
+fn entry_id(repo: &str, pkg: &str, ver: &str) -> String {
+    let encode = |x: &str| utf8_percent_encode(x, NON_ALPHANUMERIC).to_string();
+    format!(
+        "urn:reapack-indexer:{}:{}:{}",
+        encode(repo),
+        encode(pkg),
+        encode(ver)
+    )
+}
+
+/// An element containing text.
+fn text_element(name: &str, text: &str) -> XMLElement {
+    let mut element = XMLElement::new(name);
+    element.add_text(cdata(text)).unwrap();
+    element
+}
+
+fn entry_element(repo: &Repository, pkg: &Package, ver: &Version) -> Result<XMLElement> {
+    let mut entry = XMLElement::new("entry");
+    entry
+        .add_child(text_element(
+            "title",
+            &format!("{} {}", pkg.name(), ver.name()),
+        ))
+        .unwrap();
+    entry
+        .add_child(text_element(
+            "id",
+            &entry_id(&repo.identifier(), &pkg.identifier(), &ver.name()),
+        ))
+        .unwrap();
+    entry
+        .add_child(text_element("updated", &ver.time().to_rfc3339()))
+        .unwrap();
+
+    let mut author = XMLElement::new("author");
+    author
+        .add_child(text_element("name", ver.resolved_author(repo, pkg)))
+        .unwrap();
+    entry.add_child(author).unwrap();
+
+    if let Some(url) = repo.changelog_url(pkg, ver)? {
+        let mut link = XMLElement::new("link");
+        link.add_attribute("rel", "alternate");
+        link.add_attribute("href", &url);
+        entry.add_child(link).unwrap();
+    }
+
+    let mut category = XMLElement::new("category");
+    category.add_attribute("term", pkg.category().as_str());
+    entry.add_child(category).unwrap();
+
+    if let Some(changelog) = ver.changelog()? {
+        let mut content = text_element("content", changelog.trim());
+        content.add_attribute("type", "text");
+        entry.add_child(content).unwrap();
+    }
+    Ok(entry)
+}
+
+/// Generate an Atom feed of the newest versions in the index, newest first.
+pub(crate) fn generate_feed(repo: &Repository) -> Result<String> {
+    let mut versions: Vec<(DateTime<Utc>, Package, Version)> = vec![];
+    for pkg in repo.indexed_packages()? {
+        for ver in pkg.indexed_versions(repo)? {
+            versions.push((ver.time(), pkg.clone(), ver));
+        }
+    }
+    versions.sort_by_key(|(time, _, _)| std::cmp::Reverse(*time));
+    versions.truncate(FEED_ENTRIES);
+
+    let mut feed = XMLElement::new("feed");
+    feed.add_attribute("xmlns", "http://www.w3.org/2005/Atom");
+    feed.add_child(text_element(
+        "title",
+        &format!("{} releases", repo.identifier()),
+    ))
+    .unwrap();
+    feed.add_child(text_element(
+        "id",
+        &format!(
+            "urn:reapack-indexer:{}",
+            utf8_percent_encode(&repo.identifier(), NON_ALPHANUMERIC)
+        ),
+    ))
+    .unwrap();
+    // feeds without entries still need a date, so use the time of generation
+    let updated = versions
+        .first()
+        .map(|(time, _, _)| *time)
+        .unwrap_or_else(Utc::now);
+    feed.add_child(text_element("updated", &updated.to_rfc3339()))
+        .unwrap();
+    let mut author = XMLElement::new("author");
+    author
+        .add_child(text_element("name", repo.author()))
+        .unwrap();
+    feed.add_child(author).unwrap();
+
+    for (_, pkg, ver) in versions.iter() {
+        feed.add_child(entry_element(repo, pkg, ver)?).unwrap();
+    }
+
+    let mut xml = XMLBuilder::new()
+        .version(XMLVersion::XML1_0)
+        .encoding("UTF-8".into())
+        .build();
+    xml.set_root_element(feed);
+    let mut buf: Vec<u8> = Vec::new();
+    xml.generate(&mut buf)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_id_01() {
+        assert_eq!(
+            entry_id("My Repo", "my-package", "1.0"),
+            "urn:reapack-indexer:My%20Repo:my%2Dpackage:1%2E0"
+        );
+    }
+}
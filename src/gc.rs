@@ -0,0 +1,244 @@
+//! Find files and folders in the repository that aren't part of any package or version, and clean them up.
+//!
+//! These are skipped when generating the index, so they are easy to miss.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+
+use crate::{
+    manifest::{checksums_path, manifest_path},
+    repo::{Package, Repository, Version},
+    sign::SignTool,
+    state::ExportState,
+    templates::{self, PackageTemplateParams, VersionTemplateParams},
+};
+
+/// Folders in the repository root that are used by other commands.
+const KNOWN_FOLDERS: &[&str] = &["archive", Repository::CACHE_DIRNAME];
+
+/// Files in the repository root that are written by other commands, or are commonly found in repositories.
+/// The outputs of `export` are found with [export_outputs] instead.
+const KNOWN_FILES: &[&str] = &[ExportState::FILENAME, "workspace.toml"];
+
+/// Prefixes of file names in the repository root that are commonly found in repositories, e.g. `LICENSE.txt`.
+const KNOWN_FILE_PREFIXES: &[&str] = &["readme", "license", "licence", "changelog"];
+
+pub(crate) enum Problem {
+    /// A folder in a package without `version.toml`
+    VersionWithoutConfig(PathBuf),
+    /// A folder in the repository root without `package.toml`
+    PackageWithoutConfig(PathBuf),
+    /// An empty file in a version folder
+    EmptySource(PathBuf),
+    /// A file in the repository root that doesn't belong to any package
+    UnknownFile(PathBuf),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::VersionWithoutConfig(path) => {
+                write!(f, "version folder without version.toml: {}", path.display())
+            }
+            Problem::PackageWithoutConfig(path) => {
+                write!(f, "package folder without package.toml: {}", path.display())
+            }
+            Problem::EmptySource(path) => write!(f, "empty source file: {}", path.display()),
+            Problem::UnknownFile(path) => {
+                write!(f, "file not belonging to any package: {}", path.display())
+            }
+        }
+    }
+}
+
+impl Problem {
+    pub(crate) fn path(&self) -> &Path {
+        match self {
+            Problem::VersionWithoutConfig(path)
+            | Problem::PackageWithoutConfig(path)
+            | Problem::EmptySource(path)
+            | Problem::UnknownFile(path) => path,
+        }
+    }
+
+    /// Whether the problem can be fixed by creating the missing config.
+    pub(crate) fn can_adopt(&self) -> bool {
+        matches!(
+            self,
+            Problem::VersionWithoutConfig(_) | Problem::PackageWithoutConfig(_)
+        )
+    }
+
+    pub(crate) fn delete(&self) -> Result<()> {
+        let path = self.path();
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Create the missing config from a template. Returns the path of the created config.
+    /// Versions use the time the folder was last modified as their publication date.
+    pub(crate) fn adopt(&self) -> Result<Option<PathBuf>> {
+        match self {
+            Problem::VersionWithoutConfig(path) => {
+                let time: DateTime<Utc> = path
+                    .metadata()
+                    .and_then(|x| x.modified())
+                    .map(Into::into)
+                    .unwrap_or_else(|_| Utc::now());
+                let config_path = path.join(Version::CONFIG_FILENAME);
+                fs::write(
+                    &config_path,
+                    templates::generate_version_config(
+                        &VersionTemplateParams::default().time(&time.to_rfc3339()),
+                    ),
+                )?;
+                Ok(Some(config_path))
+            }
+            Problem::PackageWithoutConfig(path) => {
+                let name = path
+                    .file_name()
+                    .map(|x| x.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let config_path = path.join(Package::CONFIG_FILENAME);
+                fs::write(
+                    &config_path,
+                    templates::generate_package_config(
+                        &PackageTemplateParams::default().name(&name),
+                    ),
+                )?;
+                Ok(Some(config_path))
+            }
+            Problem::EmptySource(_) | Problem::UnknownFile(_) => Ok(None),
+        }
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|x| x.to_string_lossy().starts_with('.'))
+}
+
+/// Files and folders written by `export` and `sign`: the configured index with its manifest, checksums and
+/// signatures, and everything written by the last export, e.g. the HTML pages and the feed.
+fn export_outputs(repo: &Repository) -> Vec<PathBuf> {
+    let mut outputs = repo.export_outputs();
+    outputs.extend(std::path::absolute(repo.index_path()));
+    let mut result = vec![];
+    for output in outputs {
+        result.push(manifest_path(&output));
+        result.push(checksums_path(&output));
+        for tool in SignTool::value_variants() {
+            result.push(tool.signature_path(&output));
+        }
+        result.push(output);
+    }
+    result
+}
+
+/// Whether a file in the repository root is expected to be there.
+fn is_known_root_file(repo: &Repository, outputs: &[PathBuf], path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|x| x.to_string_lossy().to_lowercase()) else {
+        return false;
+    };
+    name.starts_with('.')
+        || KNOWN_FILES.iter().any(|x| x.to_lowercase() == name)
+        || KNOWN_FILE_PREFIXES.iter().any(|x| name.starts_with(x))
+        || path == repo.config_path()
+        || outputs.iter().any(|x| x == path)
+}
+
+/// Whether a folder in the repository root is an export output, or contains one, e.g. `docs/` when the index is
+/// exported to `docs/index.xml`.
+fn contains_export_output(outputs: &[PathBuf], dir: &Path) -> bool {
+    outputs.iter().any(|x| x.starts_with(dir))
+}
+
+/// Subfolders and files of a folder, sorted by name.
+fn read_dir_sorted(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut dirs = vec![];
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        } else {
+            files.push(path);
+        }
+    }
+    dirs.sort();
+    files.sort();
+    Ok((dirs, files))
+}
+
+/// Find every problem in the repository. Only the folder layout is checked, configs aren't read.
+pub(crate) fn find_problems(repo: &Repository) -> Result<Vec<Problem>> {
+    let mut problems = vec![];
+    let outputs = export_outputs(repo);
+    let (dirs, files) = read_dir_sorted(repo.path())?;
+    for file in files {
+        if !is_known_root_file(repo, &outputs, &file) {
+            problems.push(Problem::UnknownFile(file));
+        }
+    }
+    for dir in dirs {
+        if is_hidden(&dir)
+            || dir
+                .file_name()
+                .is_some_and(|x| KNOWN_FOLDERS.contains(&x.to_string_lossy().as_ref()))
+            || contains_export_output(&outputs, &dir)
+        {
+            continue;
+        }
+        if !dir.join(Package::CONFIG_FILENAME).exists() {
+            problems.push(Problem::PackageWithoutConfig(dir));
+            continue;
+        }
+        let (ver_dirs, _) = read_dir_sorted(&dir)?;
+        for ver_dir in ver_dirs {
+            if is_hidden(&ver_dir) {
+                continue;
+            }
+            if !ver_dir.join(Version::CONFIG_FILENAME).exists() {
+                problems.push(Problem::VersionWithoutConfig(ver_dir));
+                continue;
+            }
+            for entry in walkdir::WalkDir::new(&ver_dir).sort_by_file_name() {
+                let entry = entry?;
+                if entry.file_type().is_file() && entry.metadata()?.len() == 0 {
+                    problems.push(Problem::EmptySource(entry.into_path()));
+                }
+            }
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_export_output_01() {
+        let outputs = vec![
+            PathBuf::from("/repo/docs/index.xml"),
+            PathBuf::from("/repo/site"),
+        ];
+        assert!(contains_export_output(&outputs, Path::new("/repo/docs")));
+        assert!(contains_export_output(&outputs, Path::new("/repo/site")));
+        assert!(!contains_export_output(&outputs, Path::new("/repo/doc")));
+        assert!(!contains_export_output(
+            &outputs,
+            Path::new("/repo/my-package")
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+//! SHA-256 hashes of files and text, written as lowercase hex like `sha256sum`.
+
+use std::{fs, io, path::Path};
+
+use sha2::{Digest, Sha256};
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// Hash a file with SHA-256, returning the hex digest and the size of the file.
+pub(crate) fn hash_file(path: &Path) -> io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok((hex(&hasher.finalize()), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_hex_01() {
+        assert_eq!(
+            sha256_hex(b"test"),
+            "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Parser for the metadata headers used by reapack-index, e.g.:
+//!
+//! ```lua
+//! -- @description My script
+//! -- @author Me
+//! -- @version 1.0
+//! -- @changelog
+//! --   Fixed a bug
+//! -- @provides
+//! --   [main] other script.lua
+//! --   data/*.txt
+//! ```
+
+use std::collections::BTreeMap;
+
+/// Tags found in the header of a file, with lowercase names.
+/// Multiline values have their lines joined with newlines, and their common indentation removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Headers(BTreeMap<String, String>);
+
+/// A file listed in the `@provides` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Provided {
+    /// Path or glob pattern, relative to the file containing the header
+    pub(crate) pattern: String,
+    /// Action list sections to add the file to, e.g. `main`
+    pub(crate) sections: Vec<String>,
+    /// The path to install the file to, if different from its pattern
+    pub(crate) target: Option<String>,
+}
+
+/// Remove comment markers from a line, returning None if the line is not a comment.
+fn strip_comment(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return Some("");
+    }
+    ["--", "//", "#"]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+}
+
+/// Remove the common indentation from a list of lines, and trim empty lines at the start and end.
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.len() - x.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|x| x.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+impl Headers {
+    /// Parse the header comments at the start of a file.
+    /// Parsing stops at the first line that isn't a comment.
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut result = BTreeMap::new();
+        let mut current: Option<(String, Vec<&str>)> = None;
+
+        let mut finish = |current: &mut Option<(String, Vec<&str>)>| {
+            if let Some((tag, lines)) = current.take() {
+                result.insert(tag, dedent(&lines));
+            }
+        };
+
+        let mut in_block = false;
+        for line in text.lines() {
+            let content = if in_block {
+                match line.find("]]").or_else(|| line.find("*/")) {
+                    Some(end) => {
+                        in_block = false;
+                        &line[..end]
+                    }
+                    None => line,
+                }
+            } else if let Some(rest) = ["--[[", "/*"]
+                .iter()
+                .find_map(|marker| line.trim_start().strip_prefix(marker))
+            {
+                match rest.find("]]").or_else(|| rest.find("*/")) {
+                    Some(end) => &rest[..end],
+                    None => {
+                        in_block = true;
+                        rest
+                    }
+                }
+            } else {
+                match strip_comment(line) {
+                    Some(content) => content,
+                    None => break,
+                }
+            };
+
+            let trimmed = content.trim_start();
+            if let Some(tag_line) = trimmed.strip_prefix('@') {
+                finish(&mut current);
+                let (tag, value) = tag_line
+                    .split_once(|c: char| c.is_whitespace() || c == ':')
+                    .unwrap_or((tag_line, ""));
+                let value = value.trim();
+                current = Some((
+                    tag.to_lowercase(),
+                    if value.is_empty() {
+                        vec![]
+                    } else {
+                        vec![value]
+                    },
+                ));
+            } else if let Some((_, lines)) = current.as_mut() {
+                lines.push(content);
+            }
+        }
+        finish(&mut current);
+
+        Self(result)
+    }
+
+    pub(crate) fn get(&self, tag: &str) -> Option<&str> {
+        self.0.get(tag).map(|x| x.as_str())
+    }
+
+    /// Whether the file should be skipped by the indexer.
+    pub(crate) fn noindex(&self) -> bool {
+        self.0.contains_key("noindex")
+    }
+
+    /// The files listed in the `@provides` tag.
+    pub(crate) fn provides(&self) -> Vec<Provided> {
+        let Some(provides) = self.get("provides") else {
+            return vec![];
+        };
+        provides
+            .lines()
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(|line| {
+                let (options, rest) = match line.strip_prefix('[') {
+                    Some(rest) => rest.split_once(']').unwrap_or(("", rest)),
+                    None => ("", line),
+                };
+                let (pattern, target) = match rest.split_once(" > ") {
+                    Some((pattern, target)) => (pattern, Some(target.trim().to_string())),
+                    None => (rest, None),
+                };
+                let mut sections = vec![];
+                for option in options.split_whitespace() {
+                    match option.strip_prefix("main=") {
+                        Some(values) => sections.extend(values.split(',').map(String::from)),
+                        None if option == "main" => sections.push("main".into()),
+                        // platform and type options are not supported
+                        None => (),
+                    }
+                }
+                Provided {
+                    pattern: pattern.trim().to_string(),
+                    sections,
+                    target,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_01() {
+        let text = "-- @description My script
+-- @author Me
+-- @version 1.0.2
+-- @changelog
+--   Fixed a bug
+--     with indentation
+-- @about:
+--   # My script
+--
+--   Does things
+-- @noindex
+
+print('hello')
+-- @version 2.0
+";
+        let headers = Headers::parse(text);
+        assert_eq!(headers.get("description"), Some("My script"));
+        assert_eq!(headers.get("author"), Some("Me"));
+        assert_eq!(headers.get("version"), Some("1.0.2"));
+        assert_eq!(
+            headers.get("changelog"),
+            Some("Fixed a bug\n  with indentation")
+        );
+        assert_eq!(headers.get("about"), Some("# My script\n\nDoes things"));
+        assert!(headers.noindex());
+    }
+
+    #[test]
+    fn parse_02() {
+        let text = "--[[
+  @version 1.0
+  @provides
+    [main] other.lua
+    [main=main,midi_editor] editor.lua
+    data/*.txt
+    [win64] lib.dll > lib/lib.dll
+]]";
+        let headers = Headers::parse(text);
+        assert_eq!(headers.get("version"), Some("1.0"));
+        assert_eq!(
+            headers.provides(),
+            vec![
+                Provided {
+                    pattern: "other.lua".into(),
+                    sections: vec!["main".into()],
+                    target: None,
+                },
+                Provided {
+                    pattern: "editor.lua".into(),
+                    sections: vec!["main".into(), "midi_editor".into()],
+                    target: None,
+                },
+                Provided {
+                    pattern: "data/*.txt".into(),
+                    sections: vec![],
+                    target: None,
+                },
+                Provided {
+                    pattern: "lib.dll".into(),
+                    sections: vec![],
+                    target: Some("lib/lib.dll".into()),
+                },
+            ]
+        );
+    }
+}
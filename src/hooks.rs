@@ -0,0 +1,118 @@
+//! Git hooks that check the index is up to date before committing or pushing.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use thiserror::Error;
+
+use crate::repo::GitCommitError;
+
+/// Written into every installed hook, so that they can be recognised and replaced later.
+const MARKER: &str = "# Installed by reapack-indexer-4";
+
+#[derive(Error, Debug)]
+#[error("failed to find the git hooks folder, is the repository in a git repository? {0}")]
+pub(crate) struct HooksDirNotFound(PathBuf);
+
+#[derive(Error, Debug)]
+#[error(
+    "a hook that wasn't installed by this program already exists, use --force to replace it: {0}"
+)]
+pub(crate) struct HookAlreadyExists(PathBuf);
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookType {
+    PreCommit,
+    PrePush,
+}
+
+impl HookType {
+    fn filename(&self) -> &'static str {
+        match self {
+            HookType::PreCommit => "pre-commit",
+            HookType::PrePush => "pre-push",
+        }
+    }
+}
+
+/// Quote a string for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// The contents of a hook that checks the index, and with `check`, also validates the repository.
+fn hook_script(exe: &Path, repo: &Path, index: &Path, check: bool) -> String {
+    let exe = shell_quote(&exe.to_string_lossy());
+    let repo = shell_quote(&repo.to_string_lossy());
+    let index = shell_quote(&index.to_string_lossy());
+    let mut script = format!("#!/bin/sh\n{MARKER}\n\n");
+    if check {
+        script.push_str(&format!("{exe} check --repo {repo} || exit 1\n"));
+    }
+    script.push_str(&format!(
+        "{exe} export --repo {repo} --check {index} || {{\n    echo \"The index is out of date, run \\`export\\` and commit the index again\" >&2\n    exit 1\n}}\n"
+    ));
+    script
+}
+
+/// The hooks folder of the git repository containing `dir`. This respects `core.hooksPath`.
+fn hooks_dir(dir: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+    if !output.status.success() {
+        return Err(HooksDirNotFound(dir.into()).into());
+    }
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(dir.join(stdout.trim()))
+}
+
+/// Install a hook into the git repository containing the repository. Returns the path of the hook.
+/// Existing hooks are only replaced if they were installed by this program, or with `force`.
+pub(crate) fn install(
+    repo: &Path,
+    index: &Path,
+    hook: HookType,
+    check: bool,
+    force: bool,
+) -> Result<PathBuf> {
+    let repo = std::path::absolute(repo)?;
+    let index = std::path::absolute(index)?;
+    let dir = hooks_dir(&repo)?;
+    let path = dir.join(hook.filename());
+    if !force {
+        if let Ok(existing) = fs::read_to_string(&path) {
+            if !existing.contains(MARKER) {
+                return Err(HookAlreadyExists(path).into());
+            }
+        }
+    }
+
+    let exe = std::env::current_exe()?;
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, hook_script(&exe, &repo, &index, check))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_quote_01() {
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,530 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use relative_path::{Component, RelativePath, RelativePathBuf};
+use thiserror::Error;
+
+use crate::{
+    config::{ActionListSection, PackageType},
+    headers::Headers,
+    index::{IndexModel, PackageEntry, VersionEntry},
+    repo::{update_toml_file, Repository, Version},
+    templates::{self, PackageTemplateParams, VersionTemplateParams},
+};
+
+#[derive(Error, Debug)]
+#[error("failed to download `{0}`: {1}")]
+pub(crate) struct DownloadFailed(pub(crate) String, pub(crate) String);
+
+/// Entrypoints of a version, mapping action list sections to glob patterns.
+type Entrypoints = BTreeMap<String, Vec<String>>;
+
+/// Read the contents of a file or URL.
+pub(crate) fn fetch(location: &str) -> Result<Vec<u8>> {
+    if !(location.starts_with("http://") || location.starts_with("https://")) {
+        return Ok(fs::read(location)?);
+    }
+    let response = ureq::get(location)
+        .call()
+        .map_err(|err| DownloadFailed(location.into(), err.to_string()))?;
+    let mut bytes = vec![];
+    response.into_reader().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Escape glob metacharacters, so that the file name only matches itself when used as an entrypoint.
+fn escape_glob(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '{' | '}' => {
+                result.push('[');
+                result.push(c);
+                result.push(']');
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Find the path of a source file within its version folder.
+///
+/// The `file` attribute is relative to the category folder. Leading `..` components are dropped, as well as
+/// the package's own folder if present (as written by this program's `export`).
+fn source_relpath(file: &str, identifier: &str) -> RelativePathBuf {
+    let path = RelativePath::new(file).normalize();
+    let mut components: Vec<&str> = path
+        .components()
+        .skip_while(|x| *x == Component::ParentDir)
+        .map(|x| x.as_str())
+        .collect();
+    if components.len() > 1 && components[0] == identifier {
+        components.remove(0);
+    }
+    components.join("/").into()
+}
+
+/// Parse the `main` attribute of a source into the action list sections it belongs to.
+fn source_sections(main: &str) -> Vec<&str> {
+    main.split_whitespace()
+        .filter(|section| {
+            let valid = section.parse::<ActionListSection>().is_ok();
+            if !valid {
+                log::warn!("ignoring unknown action list section `{section}`");
+            }
+            valid
+        })
+        .collect()
+}
+
+fn entrypoints_item(entrypoints: &Entrypoints) -> toml_edit::Item {
+    let mut table = toml_edit::Table::new();
+    for (section, patterns) in entrypoints {
+        table[section] = toml_edit::value(patterns.iter().collect::<toml_edit::Array>());
+    }
+    toml_edit::Item::Table(table)
+}
+
+/// Write `version.toml` in the given version folder.
+/// The author and entrypoints are only written if given, otherwise they are inherited from the package.
+pub(crate) fn write_version_config(
+    ver_path: &Path,
+    time: &str,
+    author: Option<&str>,
+    entrypoints: Option<&Entrypoints>,
+) -> Result<()> {
+    let config_path = ver_path.join("version.toml");
+    fs::write(
+        &config_path,
+        templates::generate_version_config(&VersionTemplateParams::default().time(time)),
+    )?;
+    update_toml_file(&config_path, |doc| {
+        if let Some(author) = author {
+            doc["author"] = toml_edit::value(author);
+        }
+        if let Some(entrypoints) = entrypoints.filter(|x| !x.is_empty()) {
+            doc["entrypoints"] = entrypoints_item(entrypoints);
+        }
+    })
+}
+
+/// Write `package.toml` in the given package folder.
+fn write_package_config(
+    pkg_path: &Path,
+    identifier: &str,
+    name: &str,
+    category: &str,
+    pkg_type: &str,
+    author: Option<&str>,
+    entrypoints: Option<&Entrypoints>,
+) -> Result<()> {
+    let config_path = pkg_path.join("package.toml");
+    fs::write(
+        &config_path,
+        templates::generate_package_config(
+            &PackageTemplateParams::default().identifier(identifier),
+        ),
+    )?;
+    // set the values here rather than in the template, so that they are escaped properly
+    update_toml_file(&config_path, |doc| {
+        doc["name"] = toml_edit::value(name);
+        doc["category"] = toml_edit::value(category);
+        doc["type"] = toml_edit::value(pkg_type);
+        if let Some(author) = author {
+            doc["author"] = toml_edit::value(author);
+        }
+        match entrypoints {
+            Some(entrypoints) if !entrypoints.is_empty() => {
+                doc["entrypoints"] = entrypoints_item(entrypoints);
+            }
+            _ => {
+                doc.remove("entrypoints");
+            }
+        }
+    })
+}
+
+/// Download the sources of a version into the given folder, returning the entrypoints of the version.
+fn import_version(
+    repo: &Repository,
+    pkg: &PackageEntry,
+    identifier: &str,
+    ver: &VersionEntry,
+    ver_path: &Path,
+    package_entrypoints: Option<&Entrypoints>,
+) -> Result<Entrypoints> {
+    fs::create_dir_all(ver_path)?;
+
+    let mut entrypoints = Entrypoints::new();
+    let mut written = vec![];
+    for src in ver.sources.values() {
+        let file = src.attributes.get("file").unwrap_or(&pkg.name);
+        let relpath = source_relpath(file, identifier);
+        if written.contains(&relpath) {
+            log::warn!(
+                "skipping duplicate source `{}` in {} {}, only one platform can be imported",
+                relpath,
+                identifier,
+                ver_path.display()
+            );
+            continue;
+        }
+
+        let bytes = fetch(&src.url)?;
+        let path = relpath.to_path(ver_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, bytes)?;
+
+        if let Some(main) = src.attributes.get("main") {
+            for section in source_sections(main) {
+                entrypoints
+                    .entry(section.into())
+                    .or_default()
+                    .push(escape_glob(relpath.as_str()));
+            }
+        }
+        written.push(relpath);
+    }
+
+    // version config
+    let time = ver.attributes.get("time").cloned().unwrap_or_default();
+    let time = match chrono::DateTime::parse_from_rfc3339(&time) {
+        Ok(time) => time.to_rfc3339(),
+        Err(_) => chrono::Utc::now().to_rfc3339(),
+    };
+    let author = ver
+        .attributes
+        .get("author")
+        .filter(|author| *author != repo.author());
+    let differs = package_entrypoints.is_some_and(|x| x != &entrypoints);
+    write_version_config(
+        ver_path,
+        &time,
+        author.map(|x| x.as_str()),
+        Some(&entrypoints).filter(|_| differs),
+    )?;
+
+    if let Some(changelog) = &ver.changelog {
+        if !changelog.trim().is_empty() {
+            fs::write(ver_path.join("CHANGELOG.txt"), changelog)?;
+        }
+    }
+
+    Ok(entrypoints)
+}
+
+/// Create a package folder in the repository for the given index entry, returning its path.
+/// Returns None if the package already exists in the repository.
+pub(crate) fn import_package(repo: &Repository, pkg: &PackageEntry) -> Result<Option<PathBuf>> {
+    let identifier = sanitize_filename::sanitize(&pkg.name);
+    let pkg_path = repo.path().join(&identifier);
+    if repo.package(&identifier)?.is_some() || pkg_path.exists() {
+        log::warn!("package `{identifier}` already exists in the repository, skipping");
+        return Ok(None);
+    }
+
+    let pkg_type = pkg
+        .attributes
+        .get("type")
+        .map(|x| x.as_str())
+        .unwrap_or("script");
+    pkg_type.parse::<PackageType>()?;
+
+    // import versions, newest first.
+    // the package's entrypoints are taken from the latest version,
+    // older versions only store their entrypoints if they differ
+    let mut versions: Vec<(&String, &VersionEntry)> = pkg.versions.iter().collect();
+    versions.sort_by(|a, b| Version::compare_version_names(b.0, a.0));
+    fs::create_dir(&pkg_path)?;
+    let mut package_entrypoints = None;
+    for (name, ver) in versions {
+        let ver_path = pkg_path.join(sanitize_filename::sanitize(name));
+        let entrypoints = import_version(
+            repo,
+            pkg,
+            &identifier,
+            ver,
+            &ver_path,
+            package_entrypoints.as_ref(),
+        )?;
+        if package_entrypoints.is_none() {
+            package_entrypoints = Some(entrypoints);
+        }
+    }
+
+    let name = pkg.attributes.get("desc").unwrap_or(&pkg.name);
+    write_package_config(
+        &pkg_path,
+        &identifier,
+        name,
+        &pkg.category,
+        pkg_type,
+        None,
+        package_entrypoints.as_ref(),
+    )?;
+
+    if let Some(description) = &pkg.description {
+        if !description.trim().is_empty() {
+            fs::write(pkg_path.join("README.rtf"), description)?;
+        }
+    }
+
+    Ok(Some(pkg_path))
+}
+
+/// Import all packages in the index into the repository, returning the number of packages imported.
+pub(crate) fn import(repo: &Repository, index: &IndexModel) -> Result<usize> {
+    let mut count = 0;
+    for pkg in index.packages.values() {
+        if let Some(path) = import_package(repo, pkg)? {
+            println!(
+                "Imported {} ({} version(s)) to {}",
+                pkg.name,
+                pkg.versions.len(),
+                path.display()
+            );
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Guess the package type of a file from its extension, like reapack-index does.
+fn package_type_from_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|x| x.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "lua" | "eel" | "py" => "script",
+        "" | "jsfx" => "effect",
+        "dll" | "dylib" | "so" => "extension",
+        "reaperthemezip" => "theme",
+        "reaperlangpack" => "langpack",
+        "rtracktemplate" => "tracktpl",
+        "rpp" => "projecttpl",
+        "txt" => "midinotenames",
+        "reaperautoitem" => "autoitem",
+        _ => "data",
+    }
+}
+
+/// The time of the last commit that changed the given file, or the current time if it isn't in a git repository.
+fn file_commit_time(path: &Path) -> String {
+    let output = std::process::Command::new("git")
+        .current_dir(path.parent().unwrap_or(path))
+        .args(["log", "-1", "--format=%cI", "--"])
+        .arg(path)
+        .output();
+    match output {
+        Ok(output) if output.status.success() && !output.stdout.trim_ascii().is_empty() => {
+            String::from_utf8_lossy(output.stdout.trim_ascii()).into()
+        }
+        _ => chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Find the files matched by a `@provides` pattern, relative to the given folder.
+fn find_provided_files(dir: &Path, pattern: &str) -> Result<Vec<RelativePathBuf>> {
+    let matcher = globset::GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher();
+    let mut result = vec![];
+    for entry in walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|x| x.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relpath) = RelativePathBuf::from_path(entry.path().strip_prefix(dir)?) else {
+            continue;
+        };
+        if matcher.is_match(relpath.as_str()) {
+            result.push(relpath);
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Create a package from a file with reapack-index style metadata headers, returning the package's path.
+/// Returns None if the file is not a package, or if the package already exists in the repository.
+fn import_headers_package(
+    repo: &Repository,
+    root: &Path,
+    path: &Path,
+    headers: &Headers,
+) -> Result<Option<PathBuf>> {
+    let Some(version) = headers.get("version") else {
+        return Ok(None);
+    };
+    if headers.noindex() {
+        return Ok(None);
+    }
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let identifier = sanitize_filename::sanitize(&file_name);
+    let pkg_path = repo.path().join(&identifier);
+    if repo.package(&identifier)?.is_some() || pkg_path.exists() {
+        log::warn!("package `{identifier}` already exists in the repository, skipping");
+        return Ok(None);
+    }
+
+    let dir = path.parent().unwrap_or(root);
+    let category = dir
+        .strip_prefix(root)
+        .ok()
+        .and_then(|x| RelativePathBuf::from_path(x).ok())
+        .filter(|x| !x.as_str().is_empty())
+        .map(|x| x.to_string())
+        .unwrap_or_else(|| "Uncategorized".into());
+    let pkg_type = package_type_from_path(path);
+
+    // collect files to copy, mapping the path in the version folder to the original file
+    let mut files: Vec<(RelativePathBuf, PathBuf)> = vec![];
+    let mut entrypoints = Entrypoints::new();
+    let mut provides = headers.provides();
+    let own_sections = match provides.iter().position(|x| x.pattern == ".") {
+        Some(i) => provides.remove(i).sections,
+        None if pkg_type == "script" => vec!["main".into()],
+        None => vec![],
+    };
+    files.push((file_name.as_ref().into(), path.into()));
+    for section in own_sections {
+        entrypoints
+            .entry(section)
+            .or_default()
+            .push(escape_glob(&file_name));
+    }
+    for provided in provides {
+        let matches = find_provided_files(dir, &provided.pattern)?;
+        if matches.is_empty() {
+            log::warn!(
+                "no files matched `{}` in {}",
+                provided.pattern,
+                path.display()
+            );
+        }
+        for relpath in matches {
+            let target = match &provided.target {
+                Some(target) if target.ends_with('/') => {
+                    RelativePathBuf::from(target.as_str()).join(relpath.file_name().unwrap_or(""))
+                }
+                Some(target) => target.as_str().into(),
+                None => relpath.clone(),
+            }
+            .normalize();
+            if target.components().next() == Some(Component::ParentDir) {
+                log::warn!(
+                    "skipping `{}` in {}, files outside of the package's folder are not supported",
+                    target,
+                    path.display()
+                );
+                continue;
+            }
+            for section in provided.sections.iter() {
+                entrypoints
+                    .entry(section.clone())
+                    .or_default()
+                    .push(escape_glob(target.as_str()));
+            }
+            files.push((target, relpath.to_path(dir)));
+        }
+    }
+
+    // copy files
+    let ver_path = pkg_path.join(sanitize_filename::sanitize(version));
+    for (target, source) in files.iter() {
+        let target = target.to_path(&ver_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source, target)?;
+    }
+
+    // configs
+    let author = headers.get("author").filter(|x| *x != repo.author());
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    write_version_config(&ver_path, &file_commit_time(path), None, None)?;
+    write_package_config(
+        &pkg_path,
+        &identifier,
+        headers.get("description").unwrap_or(&stem),
+        &category,
+        pkg_type,
+        author,
+        Some(&entrypoints),
+    )?;
+    if let Some(changelog) = headers.get("changelog") {
+        fs::write(ver_path.join("CHANGELOG.txt"), changelog)?;
+    }
+    if let Some(about) = headers.get("about") {
+        fs::write(pkg_path.join("README.md"), about)?;
+    }
+
+    Ok(Some(pkg_path))
+}
+
+/// Import all files with reapack-index style metadata headers in the given folder,
+/// returning the number of packages imported.
+pub(crate) fn import_from_headers(repo: &Repository, root: &Path) -> Result<usize> {
+    let root = std::path::absolute(root)?;
+    let mut count = 0;
+    let entries = walkdir::WalkDir::new(&root)
+        .sort_by_file_name()
+        .into_iter()
+        // skip hidden folders like `.git`
+        .filter_entry(|x| x.depth() == 0 || !x.file_name().to_string_lossy().starts_with('.'));
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        // skip binary files
+        let Ok(text) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let headers = Headers::parse(&text);
+        if let Some(path) = import_headers_package(repo, &root, entry.path(), &headers)? {
+            println!("Imported {} to {}", entry.path().display(), path.display());
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_relpath_01() {
+        assert_eq!(
+            source_relpath("../my-package/foo/a.lua", "my-package"),
+            "foo/a.lua"
+        );
+        assert_eq!(
+            source_relpath("../../my-package/a.lua", "my-package"),
+            "a.lua"
+        );
+        assert_eq!(source_relpath("a.lua", "my-package"), "a.lua");
+        assert_eq!(source_relpath("my-package", "my-package"), "my-package");
+        assert_eq!(source_relpath("Other/a.lua", "my-package"), "Other/a.lua");
+    }
+
+    #[test]
+    fn escape_glob_01() {
+        assert_eq!(escape_glob("My script.lua"), "My script.lua");
+        assert_eq!(escape_glob("a[1]*.lua"), "a[[]1[]][*].lua");
+    }
+}
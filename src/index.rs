@@ -0,0 +1,478 @@
+//! A simplified model of a ReaPack index, parsed from XML.
+//! Used for comparing a generated index against an existing one.
+
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Result;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("invalid index, expected <{0}> element")]
+pub(crate) struct InvalidIndex(&'static str);
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct IndexModel {
+    pub(crate) name: Option<String>,
+    /// Packages keyed by `{category}/{name}`
+    pub(crate) packages: BTreeMap<String, PackageEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct PackageEntry {
+    pub(crate) category: String,
+    pub(crate) name: String,
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) description: Option<String>,
+    pub(crate) versions: BTreeMap<String, VersionEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct VersionEntry {
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) changelog: Option<String>,
+    /// Sources keyed by their `file` attribute, followed by the `platform` attribute if present
+    pub(crate) sources: BTreeMap<String, SourceEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct SourceEntry {
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) url: String,
+}
+
+fn attributes(node: roxmltree::Node) -> BTreeMap<String, String> {
+    node.attributes()
+        .map(|attr| (attr.name().to_string(), attr.value().to_string()))
+        .collect()
+}
+
+fn child_elements<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |x| x.is_element() && x.tag_name().name() == tag)
+}
+
+fn text(node: roxmltree::Node) -> String {
+    node.children().filter_map(|x| x.text()).collect::<String>()
+}
+
+impl IndexModel {
+    pub(crate) fn parse(xml: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml)?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "index" {
+            return Err(InvalidIndex("index").into());
+        }
+
+        let mut result = IndexModel {
+            name: root.attribute("name").map(Into::into),
+            packages: BTreeMap::new(),
+        };
+
+        for category in child_elements(root, "category") {
+            let category_name = category.attribute("name").unwrap_or_default();
+            for pkg in child_elements(category, "reapack") {
+                let name = pkg.attribute("name").unwrap_or_default();
+                let description = child_elements(pkg, "metadata")
+                    .flat_map(|x| child_elements(x, "description"))
+                    .next()
+                    .map(text);
+
+                let mut versions = BTreeMap::new();
+                for ver in child_elements(pkg, "version") {
+                    let changelog = child_elements(ver, "changelog").next().map(text);
+                    let mut sources = BTreeMap::new();
+                    for src in child_elements(ver, "source") {
+                        let attributes = attributes(src);
+                        let key = match attributes.get("platform") {
+                            Some(platform) => format!(
+                                "{} ({})",
+                                src.attribute("file").unwrap_or_default(),
+                                platform
+                            ),
+                            None => src.attribute("file").unwrap_or_default().to_string(),
+                        };
+                        sources.insert(
+                            key,
+                            SourceEntry {
+                                attributes,
+                                url: text(src).trim().to_string(),
+                            },
+                        );
+                    }
+                    versions.insert(
+                        ver.attribute("name").unwrap_or_default().to_string(),
+                        VersionEntry {
+                            attributes: attributes(ver),
+                            changelog,
+                            sources,
+                        },
+                    );
+                }
+
+                result.packages.insert(
+                    format!("{}/{}", category_name, name),
+                    PackageEntry {
+                        category: category_name.into(),
+                        name: name.into(),
+                        attributes: attributes(pkg),
+                        description,
+                        versions,
+                    },
+                );
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// A single difference between two indexes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Change {
+    PackageAdded {
+        package: String,
+        versions: usize,
+    },
+    PackageRemoved {
+        package: String,
+    },
+    PackageChanged {
+        package: String,
+        fields: Vec<String>,
+    },
+    VersionAdded {
+        package: String,
+        version: String,
+        sources: usize,
+    },
+    VersionRemoved {
+        package: String,
+        version: String,
+    },
+    VersionChanged {
+        package: String,
+        version: String,
+        fields: Vec<String>,
+    },
+    SourceAdded {
+        package: String,
+        version: String,
+        source: String,
+    },
+    SourceRemoved {
+        package: String,
+        version: String,
+        source: String,
+    },
+    SourceChanged {
+        package: String,
+        version: String,
+        source: String,
+        fields: Vec<String>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::PackageAdded { package, versions } => {
+                write!(f, "+ package {package} ({versions} version(s))")
+            }
+            Change::PackageRemoved { package } => write!(f, "- package {package}"),
+            Change::PackageChanged { package, fields } => {
+                write!(f, "~ package {package} ({})", fields.join(", "))
+            }
+            Change::VersionAdded {
+                package,
+                version,
+                sources,
+            } => write!(f, "+ version {package} {version} ({sources} file(s))"),
+            Change::VersionRemoved { package, version } => {
+                write!(f, "- version {package} {version}")
+            }
+            Change::VersionChanged {
+                package,
+                version,
+                fields,
+            } => write!(f, "~ version {package} {version} ({})", fields.join(", ")),
+            Change::SourceAdded {
+                package,
+                version,
+                source,
+            } => write!(f, "+ source {package} {version}: {source}"),
+            Change::SourceRemoved {
+                package,
+                version,
+                source,
+            } => write!(f, "- source {package} {version}: {source}"),
+            Change::SourceChanged {
+                package,
+                version,
+                source,
+                fields,
+            } => write!(
+                f,
+                "~ source {package} {version}: {source} ({})",
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+/// List the names of attributes that differ between the two maps.
+fn changed_attributes(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// Compare two indexes, returning the changes needed to go from `old` to `new`.
+pub(crate) fn diff(old: &IndexModel, new: &IndexModel) -> Vec<Change> {
+    let mut changes = vec![];
+
+    for key in old.packages.keys() {
+        if !new.packages.contains_key(key) {
+            changes.push(Change::PackageRemoved {
+                package: key.clone(),
+            });
+        }
+    }
+
+    for (key, new_pkg) in new.packages.iter() {
+        let Some(old_pkg) = old.packages.get(key) else {
+            changes.push(Change::PackageAdded {
+                package: key.clone(),
+                versions: new_pkg.versions.len(),
+            });
+            continue;
+        };
+
+        let mut fields = changed_attributes(&old_pkg.attributes, &new_pkg.attributes);
+        if old_pkg.description != new_pkg.description {
+            fields.push("description".into());
+        }
+        if !fields.is_empty() {
+            changes.push(Change::PackageChanged {
+                package: key.clone(),
+                fields,
+            });
+        }
+
+        for version in old_pkg.versions.keys() {
+            if !new_pkg.versions.contains_key(version) {
+                changes.push(Change::VersionRemoved {
+                    package: key.clone(),
+                    version: version.clone(),
+                });
+            }
+        }
+
+        for (version, new_ver) in new_pkg.versions.iter() {
+            let Some(old_ver) = old_pkg.versions.get(version) else {
+                changes.push(Change::VersionAdded {
+                    package: key.clone(),
+                    version: version.clone(),
+                    sources: new_ver.sources.len(),
+                });
+                continue;
+            };
+
+            let mut fields = changed_attributes(&old_ver.attributes, &new_ver.attributes);
+            if old_ver.changelog != new_ver.changelog {
+                fields.push("changelog".into());
+            }
+            if !fields.is_empty() {
+                changes.push(Change::VersionChanged {
+                    package: key.clone(),
+                    version: version.clone(),
+                    fields,
+                });
+            }
+
+            for source in old_ver.sources.keys() {
+                if !new_ver.sources.contains_key(source) {
+                    changes.push(Change::SourceRemoved {
+                        package: key.clone(),
+                        version: version.clone(),
+                        source: source.clone(),
+                    });
+                }
+            }
+            for (source, new_src) in new_ver.sources.iter() {
+                let Some(old_src) = old_ver.sources.get(source) else {
+                    changes.push(Change::SourceAdded {
+                        package: key.clone(),
+                        version: version.clone(),
+                        source: source.clone(),
+                    });
+                    continue;
+                };
+                let mut fields = changed_attributes(&old_src.attributes, &new_src.attributes);
+                if old_src.url != new_src.url {
+                    fields.push("url".into());
+                }
+                if !fields.is_empty() {
+                    changes.push(Change::SourceChanged {
+                        package: key.clone(),
+                        version: version.clone(),
+                        source: source.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+/// The name of a package from its `{category}/{name}` key.
+fn package_name(key: &str) -> &str {
+    key.rsplit_once('/').map_or(key, |(_, name)| name)
+}
+
+/// A one-line summary of the changes, e.g. `added my-pkg 1.2.0 (3 files), removed old-pkg`.
+/// Added and removed packages and versions are listed, other changes are only counted.
+pub(crate) fn summary(changes: &[Change]) -> String {
+    let mut parts = vec![];
+    let mut other = 0;
+    for change in changes {
+        match change {
+            Change::PackageAdded { package, versions } => parts.push(format!(
+                "added {} ({versions} version{})",
+                package_name(package),
+                if *versions == 1 { "" } else { "s" }
+            )),
+            Change::PackageRemoved { package } => {
+                parts.push(format!("removed {}", package_name(package)))
+            }
+            Change::VersionAdded {
+                package,
+                version,
+                sources,
+            } => parts.push(format!(
+                "added {} {version} ({sources} file{})",
+                package_name(package),
+                if *sources == 1 { "" } else { "s" }
+            )),
+            Change::VersionRemoved { package, version } => {
+                parts.push(format!("removed {} {version}", package_name(package)))
+            }
+            _ => other += 1,
+        }
+    }
+    if other > 0 {
+        parts.push(format!(
+            "{other} other change{}",
+            if other == 1 { "" } else { "s" }
+        ));
+    }
+    if parts.is_empty() {
+        return "no changes".into();
+    }
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"<?xml version="1.1" encoding="UTF-8"?>
+<index version="1" name="repo">
+	<category name="Category">
+		<reapack desc="Package A" type="script" name="pkg-a">
+			<version name="0.0.1" author="Me" time="2024-01-01T00:00:00+00:00">
+				<source file="../pkg-a/a.lua" main="main">https://example.com/1/pkg-a/0.0.1/a.lua</source>
+			</version>
+		</reapack>
+		<reapack desc="Package B" type="script" name="pkg-b">
+			<version name="0.0.1" author="Me" time="2024-01-01T00:00:00+00:00">
+				<source file="../pkg-b/b.lua" main="main">https://example.com/1/pkg-b/0.0.1/b.lua</source>
+			</version>
+		</reapack>
+	</category>
+</index>"#;
+
+    const NEW: &str = r#"<?xml version="1.1" encoding="UTF-8"?>
+<index version="1" name="repo">
+	<category name="Category">
+		<reapack desc="Package A" type="script" name="pkg-a">
+			<version name="0.0.1" author="Me" time="2024-01-01T00:00:00+00:00">
+				<source file="../pkg-a/a.lua" main="main">https://example.com/2/pkg-a/0.0.1/a.lua</source>
+			</version>
+			<version name="0.0.2" author="Me" time="2024-01-02T00:00:00+00:00">
+				<changelog><![CDATA[Fixed things]]></changelog>
+				<source file="../pkg-a/a.lua" main="main">https://example.com/2/pkg-a/0.0.2/a.lua</source>
+				<source file="../pkg-a/b.lua">https://example.com/2/pkg-a/0.0.2/b.lua</source>
+			</version>
+		</reapack>
+	</category>
+</index>"#;
+
+    #[test]
+    fn parse_01() {
+        let index = IndexModel::parse(NEW).unwrap();
+        assert_eq!(index.name.as_deref(), Some("repo"));
+        let pkg = &index.packages["Category/pkg-a"];
+        assert_eq!(pkg.versions.len(), 2);
+        let ver = &pkg.versions["0.0.2"];
+        assert_eq!(ver.changelog.as_deref(), Some("Fixed things"));
+        assert_eq!(
+            ver.sources["../pkg-a/a.lua"].url,
+            "https://example.com/2/pkg-a/0.0.2/a.lua"
+        );
+    }
+
+    #[test]
+    fn diff_01() {
+        let old = IndexModel::parse(OLD).unwrap();
+        let new = IndexModel::parse(NEW).unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::PackageRemoved {
+                    package: "Category/pkg-b".into()
+                },
+                Change::SourceChanged {
+                    package: "Category/pkg-a".into(),
+                    version: "0.0.1".into(),
+                    source: "../pkg-a/a.lua".into(),
+                    fields: vec!["url".into()],
+                },
+                Change::VersionAdded {
+                    package: "Category/pkg-a".into(),
+                    version: "0.0.2".into(),
+                    sources: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_01() {
+        let old = IndexModel::parse(OLD).unwrap();
+        let new = IndexModel::parse(NEW).unwrap();
+        assert_eq!(
+            summary(&diff(&old, &new)),
+            "removed pkg-b, added pkg-a 0.0.2 (2 files), 1 other change"
+        );
+        assert_eq!(summary(&[]), "no changes");
+    }
+
+    #[test]
+    fn diff_02() {
+        let index = IndexModel::parse(OLD).unwrap();
+        assert!(diff(&index, &index).is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+//! Install a version into a REAPER resource folder the same way ReaPack would, for testing packages locally.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use relative_path::{Component, RelativePath, RelativePathBuf};
+use thiserror::Error;
+
+use crate::{
+    config::{PackageType, Platform},
+    import::fetch,
+    repo::{Package, Repository, Source, Version},
+};
+
+#[derive(Error, Debug)]
+#[error("source would be installed outside of its folder: `{0}`")]
+pub(crate) struct InstallPathOutsideFolder(PathBuf);
+
+/// The folder in the REAPER resource path that ReaPack installs packages of this type to.
+fn type_folder(pkg_type: &PackageType) -> &'static str {
+    match pkg_type {
+        PackageType::Script => "Scripts",
+        PackageType::Extension => "UserPlugins",
+        PackageType::Effect => "Effects",
+        PackageType::Data => "Data",
+        PackageType::Theme => "ColorThemes",
+        PackageType::LangPack => "LangPack",
+        PackageType::WebInterface => "reaper_www_root",
+        PackageType::ProjectTemplate => "ProjectTemplates",
+        PackageType::TrackTemplate => "TrackTemplates",
+        PackageType::MIDINoteNames => "MIDINoteNames",
+        PackageType::AutomationItem => "AutomationItems",
+    }
+}
+
+/// The path of a source relative to the REAPER resource path.
+///
+/// Like ReaPack, scripts and effects are installed to `{type folder}/{repository}/{category}/{file}`, and may only
+/// traverse up to the repository folder. Other types are installed to `{type folder}/{file}`, ignoring `..`.
+/// Returns None if the source would be installed outside of its folder.
+fn install_relpath(
+    pkg_type: PackageType,
+    repo_identifier: &str,
+    category: &RelativePath,
+    file: &RelativePath,
+) -> Option<RelativePathBuf> {
+    let folder = RelativePathBuf::from(type_folder(&pkg_type));
+    let (base, path) = match pkg_type {
+        PackageType::Script | PackageType::Effect => {
+            let base = folder.join(repo_identifier);
+            let path = base.join(category).join(file).normalize();
+            (base, path)
+        }
+        _ => {
+            // ReaPack ignores `..` for these types instead of traversing upwards
+            let mut path = folder.clone();
+            for component in file.components() {
+                if let Component::Normal(x) = component {
+                    path.push(x);
+                }
+            }
+            (folder, path)
+        }
+    };
+    let inside = path.strip_prefix(&base).is_ok_and(|rest| {
+        !rest.as_str().is_empty() && rest.components().next() != Some(Component::ParentDir)
+    });
+    inside.then_some(path)
+}
+
+/// The platform of this machine, as specific as possible. `all` if REAPER doesn't run on it.
+pub(crate) fn current_platform() -> Platform {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("windows", "x86") => Platform::Win32,
+        ("windows", "x86_64") => Platform::Win64,
+        ("windows", "aarch64") => Platform::WindowsArm64ec,
+        ("macos", "x86") => Platform::Darwin32,
+        ("macos", "x86_64") => Platform::Darwin64,
+        ("macos", "aarch64") => Platform::DarwinArm64,
+        ("linux", "x86") => Platform::Linux32,
+        ("linux", "x86_64") => Platform::Linux64,
+        ("linux", "arm") => Platform::LinuxArmv7l,
+        ("linux", "aarch64") => Platform::LinuxAarch64,
+        _ => Platform::All,
+    }
+}
+
+/// Whether ReaPack installs files of the given platform on the target platform, e.g. `windows` files are installed
+/// on `win64`.
+fn platform_matches(platform: Platform, target: Platform) -> bool {
+    use Platform::*;
+    platform == target
+        || target == All
+        || match platform {
+            All => true,
+            Windows => matches!(target, Win32 | Win64 | WindowsArm64ec),
+            Darwin => matches!(target, Darwin32 | Darwin64 | DarwinArm64),
+            Linux => matches!(target, Linux32 | Linux64 | LinuxArmv7l | LinuxAarch64),
+            _ => false,
+        }
+}
+
+/// The path each source of the version will be installed to on the given platform, relative to the REAPER resource
+/// path. Sources of other platforms are skipped.
+fn install_relpaths<'a>(
+    repo: &Repository,
+    pkg: &Package,
+    ver: &Version,
+    sources: &'a [Source],
+    platform: Platform,
+) -> Result<Vec<(&'a Source, RelativePathBuf)>> {
+    let mut result = vec![];
+    for src in sources {
+        // `arch` lists the source once for each of its platforms, taking priority over `platform`
+        let mut platforms = src.arch_platforms(ver)?;
+        if platforms.is_empty() {
+            platforms.push(src.platform(pkg, ver)?.unwrap_or(Platform::All));
+        }
+        if !platforms.iter().any(|x| platform_matches(*x, platform)) {
+            continue;
+        }
+        let file = src.output_relpath_from_category(pkg, ver);
+        let pkg_type = src.source_type(pkg, ver)?.unwrap_or(pkg.pkg_type());
+        let Some(relpath) = install_relpath(pkg_type, &repo.identifier(), pkg.category(), &file)
+        else {
+            return Err(InstallPathOutsideFolder(src.path().to_path_buf()).into());
+        };
+        result.push((src, relpath));
+    }
+    Ok(result)
+}
+
+/// Copy the sources of a version for the given platform into the REAPER resource path. Sources that only exist
+/// remotely are downloaded. Returns the installed paths.
+pub(crate) fn install(
+    repo: &Repository,
+    pkg: &Package,
+    ver: &Version,
+    reaper_path: &Path,
+    platform: Platform,
+) -> Result<Vec<PathBuf>> {
+    let sources = ver.sources(pkg)?;
+    let mut result = vec![];
+    for (src, relpath) in install_relpaths(repo, pkg, ver, &sources, platform)? {
+        let path = relpath.to_logical_path(reaper_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if src.path().exists() {
+            fs::copy(src.path(), &path)?;
+        } else {
+            fs::write(&path, fetch(&src.url(repo, pkg, ver)?)?)?;
+        }
+        result.push(path);
+    }
+    Ok(result)
+}
+
+/// Remove the sources of a version for the given platform from the REAPER resource path, along with any folders
+/// left empty. Returns the removed paths.
+pub(crate) fn uninstall(
+    repo: &Repository,
+    pkg: &Package,
+    ver: &Version,
+    reaper_path: &Path,
+    platform: Platform,
+) -> Result<Vec<PathBuf>> {
+    let sources = ver.sources(pkg)?;
+    let mut result = vec![];
+    for (_, relpath) in install_relpaths(repo, pkg, ver, &sources, platform)? {
+        let path = relpath.to_logical_path(reaper_path);
+        if !path.exists() {
+            continue;
+        }
+        fs::remove_file(&path)?;
+
+        // remove empty parent folders, but never the type folder itself
+        let mut parent = relpath.parent();
+        while let Some(dir) = parent {
+            if dir.components().count() <= 1 {
+                break;
+            }
+            if fs::remove_dir(dir.to_logical_path(reaper_path)).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+        result.push(path);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_relpath_01() {
+        let category = RelativePath::new("Utility/Sub");
+        assert_eq!(
+            install_relpath(
+                PackageType::Script,
+                "MyRepo",
+                category,
+                RelativePath::new("../../my-package/script.lua")
+            ),
+            Some("Scripts/MyRepo/my-package/script.lua".into())
+        );
+        assert_eq!(
+            install_relpath(
+                PackageType::Script,
+                "MyRepo",
+                category,
+                RelativePath::new("../../../script.lua")
+            ),
+            None
+        );
+        assert_eq!(
+            install_relpath(
+                PackageType::Theme,
+                "MyRepo",
+                category,
+                RelativePath::new("../../my-theme/my-theme.ReaperThemeZip")
+            ),
+            Some("ColorThemes/my-theme/my-theme.ReaperThemeZip".into())
+        );
+    }
+
+    #[test]
+    fn platform_matches_01() {
+        assert!(platform_matches(Platform::All, Platform::Win64));
+        assert!(platform_matches(Platform::Windows, Platform::Win64));
+        assert!(platform_matches(Platform::Win64, Platform::Win64));
+        assert!(!platform_matches(Platform::Win32, Platform::Win64));
+        assert!(!platform_matches(Platform::Darwin, Platform::Linux64));
+        assert!(platform_matches(Platform::Linux64, Platform::All));
+    }
+}
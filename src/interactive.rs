@@ -0,0 +1,118 @@
+//! Prompts for `publish --interactive`, asking for everything that wasn't given as an argument.
+
+use std::path::Path;
+
+use anyhow::Result;
+use inquire::{MultiSelect, Select, Text};
+
+use crate::repo::Repository;
+
+/// Ask for an existing package, with fuzzy search over the package identifiers.
+pub(crate) fn select_package(repo: &Repository) -> Result<String> {
+    let mut identifiers: Vec<String> = repo
+        .packages()?
+        .iter()
+        .map(|pkg| pkg.identifier().to_string())
+        .collect();
+    identifiers.sort();
+    Ok(Select::new("Package:", identifiers).prompt()?)
+}
+
+/// Ask for the identifier of a new package.
+pub(crate) fn prompt_identifier() -> Result<String> {
+    Ok(Text::new("Identifier of the new package:").prompt()?)
+}
+
+/// Ask for the version name, suggesting the given one.
+pub(crate) fn prompt_version(suggested: &str) -> Result<String> {
+    Ok(Text::new("Version:").with_default(suggested).prompt()?)
+}
+
+/// Ask for a changelog message. Returns None if nothing was entered.
+pub(crate) fn prompt_changelog() -> Result<Option<String>> {
+    let text = Text::new("Changelog:")
+        .with_help_message("leave empty to skip")
+        .prompt()?;
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| format!("{text}\n")))
+}
+
+/// Ask which of the given files should be added to the action list. `selected` are the indices of the files
+/// that are initially selected.
+pub(crate) fn select_entrypoints(files: &[String], selected: &[usize]) -> Result<Vec<String>> {
+    Ok(
+        MultiSelect::new("Files to add to the action list:", files.to_vec())
+            .with_default(selected)
+            .prompt()?,
+    )
+}
+
+/// Paths of the files to be published, relative to the given folder, or the file name if a file is given.
+pub(crate) fn list_files(source_path: &Path) -> Result<Vec<String>> {
+    if !source_path.is_dir() {
+        return Ok(source_path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .into_iter()
+            .collect());
+    }
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(source_path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let relpath = entry.path().strip_prefix(source_path)?;
+            files.push(relpath.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Escape a file name, so that it can be used as a glob pattern that only matches itself.
+pub(crate) fn escape_glob(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '{' | '}' => {
+                result.push('[');
+                result.push(c);
+                result.push(']');
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Add an `[entrypoints]` section with the given files to a version config, overriding the package's entrypoints.
+pub(crate) fn with_version_entrypoints(config_text: &str, files: &[String]) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = config_text.parse()?;
+    let mut table = toml_edit::Table::new();
+    table["main"] = toml_edit::value(
+        files
+            .iter()
+            .map(|x| escape_glob(x))
+            .collect::<toml_edit::Array>(),
+    );
+    doc["entrypoints"] = toml_edit::Item::Table(table);
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_glob_01() {
+        assert_eq!(escape_glob("My script.lua"), "My script.lua");
+        assert_eq!(
+            escape_glob("a [b] {c}*?.lua"),
+            "a [[]b[]] [{]c[}][*][?].lua"
+        );
+        let glob = globset::Glob::new(&escape_glob("a [b]*.lua"))
+            .unwrap()
+            .compile_matcher();
+        assert!(glob.is_match("a [b]*.lua"));
+        assert!(!glob.is_match("a b.lua"));
+    }
+}
@@ -0,0 +1,137 @@
+//! A Markdown table of every package, to be included in the repository's README.
+
+use anyhow::Result;
+use relative_path::RelativePathBuf;
+use thiserror::Error;
+
+use crate::repo::{url_encode_path, Repository};
+
+/// Marks the start of the generated listing in a README, so that it can be updated in-place.
+pub(crate) const START_MARKER: &str = "<!-- reapack-indexer:packages:start -->";
+/// Marks the end of the generated listing in a README.
+pub(crate) const END_MARKER: &str = "<!-- reapack-indexer:packages:end -->";
+
+/// Descriptions longer than this are cut off with an ellipsis.
+const MAX_DESCRIPTION_LENGTH: usize = 120;
+
+#[derive(Error, Debug)]
+#[error("the file does not contain the markers `{START_MARKER}` and `{END_MARKER}`, please add them where the package listing should go")]
+pub(crate) struct MarkersNotFound;
+
+/// The first paragraph of a Markdown document, skipping headings, images, HTML and code blocks.
+/// Line breaks are joined with spaces, and long paragraphs are shortened.
+fn short_description(markdown: &str) -> Option<String> {
+    let mut lines = vec![];
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let is_paragraph_text = !(line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("![")
+            || line.starts_with('<')
+            || line.starts_with("---")
+            || line.starts_with("==="));
+        if is_paragraph_text {
+            lines.push(line);
+        } else if !lines.is_empty() {
+            break;
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    let text = lines.join(" ");
+    if text.chars().count() <= MAX_DESCRIPTION_LENGTH {
+        return Some(text);
+    }
+    let mut result: String = text.chars().take(MAX_DESCRIPTION_LENGTH - 1).collect();
+    result = result.trim_end().to_string();
+    result.push('…');
+    Some(result)
+}
+
+/// Escape text to be placed in a Markdown table cell.
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Generate a Markdown table of every package, sorted by category and name.
+/// Package names link to the package folders, relative to the root of the repository.
+pub(crate) fn generate(repo: &Repository) -> Result<String> {
+    let mut packages = repo.indexed_packages()?;
+    packages.sort_by_key(|pkg| (pkg.category().to_string(), pkg.name().to_lowercase()));
+
+    let mut lines = vec![
+        "| Name | Type | Category | Latest version | Description |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for pkg in packages.iter() {
+        let latest = pkg
+            .latest_indexed_version(repo)?
+            .map(|ver| ver.name().to_string())
+            .unwrap_or_else(|| "-".into());
+        let description = pkg
+            .readme_markdown(repo)?
+            .and_then(|x| short_description(&x))
+            .unwrap_or_default();
+        let relpath = RelativePathBuf::from_path(pkg.path().strip_prefix(repo.path())?)?;
+        let link = url_encode_path(&relpath);
+        lines.push(format!(
+            "| [{}]({}) | {} | {} | {} | {} |",
+            escape_cell(&pkg.name()),
+            link,
+            <&str>::from(&pkg.pkg_type()),
+            escape_cell(pkg.category().as_str()),
+            escape_cell(&latest),
+            escape_cell(&description),
+        ));
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Replace the text between the listing markers in a document with the given listing.
+pub(crate) fn replace_between_markers(
+    document: &str,
+    listing: &str,
+) -> Result<String, MarkersNotFound> {
+    let start = document.find(START_MARKER).ok_or(MarkersNotFound)? + START_MARKER.len();
+    let end = start + document[start..].find(END_MARKER).ok_or(MarkersNotFound)?;
+    Ok(format!(
+        "{}\n{}{}",
+        &document[..start],
+        listing,
+        &document[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_description_01() {
+        let text = "# Title\n\n![screenshot](x.png)\n\nDoes a thing\nvery well.\n\nMore text.";
+        assert_eq!(
+            short_description(text),
+            Some("Does a thing very well.".into())
+        );
+        assert_eq!(short_description("# Title\n\n```lua\ncode\n```\n"), None);
+    }
+
+    #[test]
+    fn replace_between_markers_01() {
+        let document = format!("intro\n{START_MARKER}\nold\n{END_MARKER}\noutro\n");
+        assert_eq!(
+            replace_between_markers(&document, "new\n").unwrap(),
+            format!("intro\n{START_MARKER}\nnew\n{END_MARKER}\noutro\n")
+        );
+        assert!(replace_between_markers("no markers", "new\n").is_err());
+    }
+}
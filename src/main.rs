@@ -1,22 +1,71 @@
+mod archive;
+mod changelog;
+mod compat;
 mod config;
+mod confirm;
+mod doctor;
+mod feed;
+mod gc;
+mod hash;
+mod headers;
+mod hooks;
+mod import;
+mod index;
+mod install;
+mod interactive;
+mod listing;
+mod manifest;
+mod migrate;
+mod mirror;
+mod origin;
+mod pack;
+mod plan;
+mod query;
+mod release;
 mod repo;
+mod report;
+mod search;
+mod serve;
+mod set;
+mod sign;
+mod site;
+mod state;
+mod stats;
+mod storage;
 mod templates;
+mod tui;
+mod validate;
+mod verify;
 mod version;
+mod warnings;
+mod watch;
+mod workspace;
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
-use log::error;
-use repo::{Package, Repository, Version};
+use config::{
+    ActionListSection, Deprecation, PackageConfig, PackageType, Platform, RepositoryConfig,
+    XmlVersion,
+};
+use hooks::HookType;
+use log::warn;
+use manifest::{checksums_path, manifest_path, IndexFormat, Manifest};
+use plan::PlannedFile;
+use repo::{local_url_pattern, Package, Repository, Version};
+use sign::SignTool;
+use site::Site;
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
     fs::{self},
-    ops::Deref,
+    io::IsTerminal,
     path::{self, Path, PathBuf},
 };
+use storage::{NoStorageConfig, Storage};
 use templates::{PackageTemplateParams, RepositoryTemplateParams, VersionTemplateParams};
 use thiserror::Error;
+use validate::Severity;
+use version::VersionPart;
 
 #[derive(Error, Debug)]
 #[error("repository already exists: `{0}`")]
@@ -27,8 +76,12 @@ pub(crate) struct RepositoryAlreadyExists(PathBuf);
 pub(crate) struct PackageDoesNotExist(String);
 
 #[derive(Error, Debug)]
-#[error("package already exists: `{0}`")]
-pub(crate) struct PackageAlreadyExists(PathBuf);
+#[error("package not found: `{0}`")]
+pub(crate) struct PackageNotFound(String);
+
+#[derive(Error, Debug)]
+#[error("version not found: `{0}`")]
+pub(crate) struct VersionNotFound(String);
 
 #[derive(Error, Debug)]
 #[error("version already exists: `{0}`")]
@@ -38,6 +91,10 @@ pub(crate) struct VersionAlreadyExists(String);
 #[error("the source folder to publish does not exist: `{0}`")]
 pub(crate) struct SourceDoesNotExist(PathBuf);
 
+#[derive(Error, Debug)]
+#[error("the source to publish overlaps with the repository, please publish from a folder outside of the repository: `{0}`")]
+pub(crate) struct SourceOverlapsRepository(PathBuf);
+
 #[derive(Error, Debug)]
 #[error("the package name is not filename-safe, please choose a different package name: `{0}`")]
 pub(crate) struct InvalidPackageName(String);
@@ -48,11 +105,74 @@ pub(crate) struct InvalidPackageName(String);
 )]
 pub(crate) struct InvalidPackageVersion(String);
 
+#[derive(Error, Debug)]
+#[error("packages of type `{0}` have no entrypoint")]
+pub(crate) struct EntrypointNotSupported(String);
+
+#[derive(Error, Debug)]
+#[error("no stub is available for `{0}`, stubs can be created for .lua, .eel and .py scripts, and effects")]
+pub(crate) struct NoStubForFile(String);
+
+#[derive(Error, Debug)]
+#[error("the stub entrypoint already exists: `{0}`")]
+pub(crate) struct StubAlreadyExists(PathBuf);
+
+#[derive(Error, Debug)]
+#[error("the package has no versions: `{0}`")]
+pub(crate) struct PackageHasNoVersions(String);
+
+#[derive(Error, Debug)]
+pub(crate) enum EditorError {
+    #[error("failed to launch editor `{0}`, please set the EDITOR environment variable")]
+    FailedToLaunch(String),
+    #[error("editor `{0}` exited with an error")]
+    ExitedWithError(String),
+}
+
+#[derive(Error, Debug)]
+#[error("operation cancelled")]
+pub(crate) struct Cancelled;
+
+#[derive(Error, Debug)]
+#[error("--interactive can only be used in a terminal")]
+pub(crate) struct InteractiveRequiresTerminal;
+
+#[derive(Error, Debug)]
+#[error("{0} problem(s) found in the environment")]
+pub(crate) struct DoctorFailed(usize);
+
+#[derive(Error, Debug)]
+#[error("export produced {0} warning(s), which is more than the maximum of {1}")]
+pub(crate) struct TooManyWarnings(usize, usize);
+
+#[derive(Error, Debug)]
+#[error("the index is out of date, please run `export` again: {0}")]
+pub(crate) struct IndexOutOfDate(PathBuf);
+
+#[derive(Error, Debug)]
+#[error("{0} of {1} source URL(s) could not be verified")]
+pub(crate) struct UrlsNotVerified(usize, usize);
+
+#[derive(Error, Debug)]
+#[error("validation failed with {0} error(s)")]
+pub(crate) struct ValidationFailed(usize);
+
 /// Generate a Reapack index
 #[derive(Parser)]
 struct Args {
     #[command(subcommand)]
     command: Commands,
+    /// Answer yes to all confirmation prompts
+    #[arg(short, long, global = true, default_value_t = false)]
+    yes: bool,
+    /// Never ask for confirmation, and use the default answer instead.
+    /// This is also the case when not running in a terminal
+    #[arg(long, global = true, default_value_t = false)]
+    no_input: bool,
+    /// Path to the pandoc executable used to convert Markdown READMEs.
+    /// Overrides the PANDOC_PATH environment variable and `pandoc_path` in repository.toml
+    #[arg(long, global = true)]
+    pandoc: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -62,9 +182,90 @@ enum Commands {
         /// Path to the folder to be processed
         #[arg(short, long)]
         repo: PathBuf,
-        /// Path to write the generated Reapack index XML file
-        #[arg(default_value = "index.xml")]
-        output_path: PathBuf,
+        /// Path to write the generated Reapack index XML file.
+        /// Defaults to `output_path` in repository.toml, or `index.xml` in the current folder.
+        /// Other formats use the same path with their own extension, e.g. `index.json`
+        output_path: Option<PathBuf>,
+        /// Format of the generated index. The JSON index has the same contents as `manifest.json`
+        #[arg(long, value_enum, default_value_t = IndexFormat::Xml)]
+        format: IndexFormat,
+        /// Fail if more than this number of warnings are emitted
+        #[arg(long)]
+        max_warnings: Option<usize>,
+        /// Keep running, and export again whenever a file in the repository changes
+        #[arg(long, default_value_t = false)]
+        watch: bool,
+        /// Also write a `manifest.json` next to the index, listing every package, version and source file
+        /// with its hash, URL and install path
+        #[arg(long, default_value_t = false)]
+        manifest: bool,
+        /// Also write a `SHA256SUMS` file next to the index, with the hash of every source file in the index.
+        /// Paths are relative to the repository, so the file can be checked with `sha256sum -c` from there
+        #[arg(long, default_value_t = false)]
+        checksums: bool,
+        /// Also generate a static website for browsing the repository in this folder
+        #[arg(long)]
+        html: Option<PathBuf>,
+        /// Also write an Atom feed of the most recent versions to this file
+        #[arg(long)]
+        feed: Option<PathBuf>,
+        /// Leave package descriptions and changelogs out of the XML index, to make it smaller
+        #[arg(long, default_value_t = false)]
+        slim: bool,
+        /// Version of the XML declaration of the index. Defaults to `xml_version` in repository.toml, or 1.0
+        #[arg(long, value_enum)]
+        xml_version: Option<XmlVersion>,
+        /// Don't indent the XML index
+        #[arg(long, default_value_t = false)]
+        no_indent: bool,
+        /// Sort the attributes of each element in the XML index by name
+        #[arg(long, default_value_t = false)]
+        sort_attributes: bool,
+        /// Use `file://` URLs to the sources on this machine instead of the URL pattern, to test the index in ReaPack
+        /// without hosting the repository
+        #[arg(long, default_value_t = false)]
+        local_urls: bool,
+        /// Upload the sources to the bucket configured in `[storage]` of repository.toml, and link to them there
+        #[arg(long, default_value_t = false, conflicts_with = "local_urls")]
+        upload: bool,
+        /// Tag to use for the `git_tag` URL variable. Defaults to the most recent tag of the current commit
+        #[arg(long)]
+        tag: Option<String>,
+        /// Don't write anything, only fail if the existing index differs from the one that would be generated.
+        /// Useful in CI, to check that `export` was run after the repository was changed
+        #[arg(long, default_value_t = false, conflicts_with_all = ["watch", "manifest", "checksums", "html", "feed", "upload"])]
+        check: bool,
+    },
+    /// Create a new package with a configuration for the given package type
+    New {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// The package type: script, extension, effect, data, theme, langpack, webinterface, projecttpl, tracktpl,
+        /// midinotenames or autoitem
+        #[arg(short = 't', long = "type", default_value = "script")]
+        pkg_type: PackageType,
+        /// Display name of the package. Defaults to the identifier
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Category of the package
+        #[arg(short, long, default_value = "Category")]
+        category: String,
+        /// File name of the entrypoint for scripts, or of the stub file for effects. Defaults to `{identifier}.lua`
+        /// for scripts and `{identifier}.jsfx` for effects
+        #[arg(short, long)]
+        entrypoint: Option<String>,
+        /// Also write a stub entrypoint file into this folder, ready to be published with `publish`
+        #[arg(long)]
+        stub: Option<PathBuf>,
+        /// Only show the files that would be created
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// With --dry-run, also show the contents of the generated files
+        #[arg(long, default_value_t = false, requires = "dry_run")]
+        show_content: bool,
     },
     /// Add a new version of a package, by copying the given folder to the repository
     Publish {
@@ -75,17 +276,45 @@ enum Commands {
         #[arg(short, long, default_value_t = false)]
         new: bool,
         /// Name of the package
-        #[arg(short, long)]
-        identifier: String,
+        #[arg(short, long, required_unless_present = "interactive")]
+        identifier: Option<String>,
         /// Path to the folder to be copied
         path: PathBuf,
         /// Version of the package
         version: Option<String>,
+        /// Prompt for the package, version, changelog and entrypoints, instead of only using the arguments
+        #[arg(long, default_value_t = false)]
+        interactive: bool,
+        /// Only show the files that would be created
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// With --dry-run, also show the contents of the generated config files
+        #[arg(long, default_value_t = false, requires = "dry_run")]
+        show_content: bool,
+        /// Text of the version's changelog. If no changelog is given, a template is written to CHANGELOG.txt
+        #[arg(long, group = "changelog_source")]
+        changelog: Option<String>,
+        /// Read the version's changelog from this file
+        #[arg(long, group = "changelog_source")]
+        changelog_file: Option<PathBuf>,
+        /// Generate the version's changelog from the commits that changed the source folder since the previous
+        /// version was published
+        #[arg(long, default_value_t = false, group = "changelog_source")]
+        changelog_from_git: bool,
+        /// With --changelog-from-git, collect the commits since this tag instead of since the previous version
+        #[arg(long, requires = "changelog_from_git")]
+        since_tag: Option<String>,
     },
     /// Create a new repository
     Init {
         /// Path to the folder to initialise
         repo: PathBuf,
+        /// Only show the files that would be created
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// With --dry-run, also show the contents of the generated config files
+        #[arg(long, default_value_t = false, requires = "dry_run")]
+        show_content: bool,
     },
     /// Show a configuration file template
     Template {
@@ -93,6 +322,431 @@ enum Commands {
         #[command(subcommand)]
         template: TemplateType,
     },
+    /// Copy the latest version of a package to a new version, e.g. to fix its changelog or configuration
+    Bump {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// Increment the major version, e.g. 1.2.3 to 2.0.0
+        #[arg(long, group = "part")]
+        major: bool,
+        /// Increment the minor version, e.g. 1.2.3 to 1.3.0
+        #[arg(long, group = "part")]
+        minor: bool,
+        /// Increment the patch version, e.g. 1.2.3 to 1.2.4. If no part is given, the last number is incremented
+        #[arg(long, group = "part")]
+        patch: bool,
+    },
+    /// Combine the changelogs of a package, or of every package, into a single Markdown document, newest first
+    Changelog {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package. If not given, the changelogs of all packages are combined
+        identifier: Option<String>,
+        /// Only include versions newer than this version
+        #[arg(long)]
+        since: Option<String>,
+        /// Write the document to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Exclude a version from the index, without deleting its files
+    Yank {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package
+        #[arg(short, long)]
+        identifier: String,
+        /// Version of the package
+        version: String,
+        /// Add the version back to the index
+        #[arg(long, default_value_t = false)]
+        undo: bool,
+    },
+    /// Mark a package as deprecated, adding a notice to its description in the index
+    Deprecate {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// Explain why the package is deprecated, e.g. which package to use instead
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Remove the deprecation
+        #[arg(long, default_value_t = false, conflicts_with = "message")]
+        undo: bool,
+    },
+    /// Delete a package, or a single version of a package, from the repository
+    Remove {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package
+        #[arg(short, long)]
+        identifier: String,
+        /// Version to remove. If not given, the whole package is removed
+        version: Option<String>,
+    },
+    /// Copy a version into a REAPER resource folder, at the paths ReaPack would install it to
+    Install {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// Version to install. Defaults to the latest version in the index
+        version: Option<String>,
+        /// Path to the REAPER resource folder, e.g. a portable REAPER installation used for testing
+        #[arg(long)]
+        reaper_path: PathBuf,
+        /// Remove the version's files from the REAPER resource folder instead
+        #[arg(long)]
+        uninstall: bool,
+        /// Only install the files of this platform, e.g. `win64`. Defaults to the platform of this machine, use
+        /// `all` to install the files of every platform
+        #[arg(long)]
+        platform: Option<Platform>,
+    },
+    /// Delete old versions of every package, keeping only the newest versions
+    Prune {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Number of versions to keep for each package. The latest version is always kept
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        keep: u64,
+        /// Only list the versions that would be removed
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Move the old versions into this folder instead of deleting them
+        #[arg(long)]
+        archive_to: Option<PathBuf>,
+    },
+    /// Move versions published before a date out of the repository, keeping the latest version of each package
+    Archive {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Archive versions published before this date, e.g. `2023-01-01` or `2023-01-01T12:00:00Z`
+        #[arg(long, value_parser = parse_date)]
+        before: DateTime<Utc>,
+        /// Folder to move the versions to. Defaults to `archive` in the repository
+        #[arg(long)]
+        to: Option<PathBuf>,
+        /// Keep the archived versions in the index, downloading their files from this URL instead.
+        /// This should point to the archive folder, e.g. `https://example.com/archive`
+        #[arg(long)]
+        url: Option<String>,
+        /// Only list the versions that would be archived
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Open the configuration file of a package or version in your editor
+    Edit {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// Version of the package. If not given, the package configuration is edited
+        version: Option<String>,
+        /// Edit the package's README.md instead
+        #[arg(long, default_value_t = false, conflicts_with_all = ["version", "changelog"])]
+        readme: bool,
+        /// Edit the version's CHANGELOG.txt instead. Defaults to the latest version
+        #[arg(long, default_value_t = false)]
+        changelog: bool,
+    },
+    /// Change the identifier of a package, moving its folder
+    Rename {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Current name of the package
+        old: String,
+        /// New name of the package
+        new: String,
+    },
+    /// Set fields in the configuration of a package, or of the repository if no package is given.
+    /// Values are parsed as TOML, e.g. `author=Jane`, `max_versions_in_index=10` or `entrypoints.main=["a.lua"]`. String fields keep the value as text, e.g. `name=1.2`
+    Set {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package
+        #[arg(short, long)]
+        identifier: Option<String>,
+        /// Fields to set, as `key=value`
+        #[arg(required = true, value_name = "KEY=VALUE")]
+        assignments: Vec<String>,
+    },
+    /// Create a new package with the same configuration as an existing package
+    Copy {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package to copy
+        source: String,
+        /// Name of the new package
+        new: String,
+        /// Also copy the latest version of the package into the new package
+        #[arg(long, default_value_t = false)]
+        with_latest: bool,
+    },
+    /// Check the whole repository for problems, without generating an index
+    #[command(visible_aliases = ["lint", "validate"])]
+    Check {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+    },
+    /// Show a summary of the repository, and whether the index needs to be exported again
+    Status {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the exported index. Defaults to `output_path` in repository.toml, or `index.xml` in the current folder like `export`
+        #[arg(long)]
+        index: Option<PathBuf>,
+    },
+    /// Show the number of versions and files of each package, their size, and how often versions are published
+    Stats {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Print the statistics as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Generate a Markdown table of all packages, for the repository's README
+    ReadmeGen {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Replace the table between the `<!-- reapack-indexer:packages:start -->` and
+        /// `<!-- reapack-indexer:packages:end -->` markers in this file, instead of printing it
+        #[arg(short, long)]
+        update: Option<PathBuf>,
+    },
+    /// Show a report card of the repository: how many packages have descriptions, changelogs, recent releases,
+    /// screenshots and hashes, and which issues to fix first
+    Report {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Print one line per package, or per version, using a format string
+    Query {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Format of each line, e.g. `{identifier}\t{latest}\t{type}`.
+        /// Available placeholders: identifier, name, category, type, author, latest, versions, deprecated, path.
+        /// With --versions, also: version, time, yanked, files
+        #[arg(short, long)]
+        format: Option<String>,
+        /// Print one line per version instead of per package
+        #[arg(long, default_value_t = false)]
+        versions: bool,
+    },
+    /// Search packages by identifier, name, category and README, printing the best matches first
+    Search {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Words to search for. Packages must match every word
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Maximum number of results to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Check that every source URL in the generated index can be downloaded
+    VerifyUrls {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Number of requests to make at the same time
+        #[arg(short, long, default_value_t = 8)]
+        concurrency: usize,
+        /// Number of times to retry a request after a server error or a network failure
+        #[arg(long, default_value_t = 2)]
+        retries: usize,
+    },
+    /// Create a detached signature for the exported index
+    Sign {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the exported index. Defaults to `output_path` in repository.toml, or `index.xml` in the current folder like `export`
+        #[arg(long)]
+        index: Option<PathBuf>,
+        /// The program used to sign the index
+        #[arg(long, value_enum, default_value_t = SignTool::Minisign)]
+        tool: SignTool,
+        /// The secret key file for minisign, or the key ID for GPG. Defaults to the program's default key
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+    /// Check the detached signature of an exported index
+    Verify {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the exported index. Defaults to `output_path` in repository.toml, or `index.xml` in the current folder like `export`
+        #[arg(long)]
+        index: Option<PathBuf>,
+        /// The program used to sign the index
+        #[arg(long, value_enum, default_value_t = SignTool::Minisign)]
+        tool: SignTool,
+        /// The public key file for minisign. GPG uses the keys in your keyring instead
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+    /// Compare the index that would be generated against an existing index file
+    Diff {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the existing index. Defaults to `output_path` in repository.toml, or `index.xml` in the current folder like `export`
+        #[arg(long)]
+        index: Option<PathBuf>,
+    },
+    /// Create packages from an existing ReaPack index, downloading their source files into the repository
+    Import {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Path or URL of the index to import
+        #[arg(required_unless_present = "from_headers")]
+        index: Option<String>,
+        /// Import a reapack-index style repository from the given folder instead,
+        /// reading package metadata from the headers of each file
+        #[arg(long, conflicts_with = "index")]
+        from_headers: Option<PathBuf>,
+    },
+    /// Create versions of a package from the releases of a GitHub repository
+    ImportRelease {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// Name of the package
+        #[arg(short, long)]
+        identifier: String,
+        /// Create a new package instead of adding versions to an existing one
+        #[arg(short, long, default_value_t = false)]
+        new: bool,
+        /// The GitHub repository to import releases from, e.g. `owner/name`
+        #[arg(long)]
+        github: String,
+        /// Pattern of the release tags, with `{version}` in place of the version name
+        #[arg(long, default_value = "v{version}")]
+        tag_pattern: String,
+        /// Only import release assets matching this glob pattern, e.g. `*.dll`
+        #[arg(long)]
+        assets: Option<String>,
+        /// Link to the assets on GitHub instead of downloading them into the repository
+        #[arg(long, default_value_t = false)]
+        remote: bool,
+        /// Also import pre-releases
+        #[arg(long, default_value_t = false)]
+        prerelease: bool,
+    },
+    /// Download every source file referenced by an index, e.g. to back up or self-host a repository
+    Mirror {
+        /// Path or URL of the index to mirror
+        index: String,
+        /// Folder to download the files to. A copy of the index is saved here as well
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Download every version into a `{package}/{version}` folder, instead of only the latest version
+        #[arg(long, default_value_t = false)]
+        all_versions: bool,
+    },
+    /// Create a zip archive of a version's files, laid out as they would be installed in REAPER
+    Pack {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Name of the package
+        identifier: String,
+        /// Version of the package. Defaults to the latest version
+        version: Option<String>,
+        /// Path to write the archive to. Defaults to `{identifier}-{version}.zip` in the current folder
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve the repository over HTTP for testing in ReaPack, generating the index on every request
+    Serve {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Check that the tools needed by this program are installed and working
+    Doctor {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Publish a new version of every package whose source folder changed since its latest version.
+    /// The source folders are listed in a workspace file, e.g. `[packages]` then `my-package = "../my-package"`
+    Sync {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the workspace file. Defaults to `workspace.toml` in the repository
+        #[arg(short, long)]
+        workspace: Option<PathBuf>,
+        /// Only list the packages that would be published
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Browse and manage the repository in a terminal UI
+    Tui {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Upgrade configuration files written for older versions of this program, keeping their comments
+    Migrate {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Only list the files that would be upgraded
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// List the contents of a repository
+    List {
+        /// Path to the repository
+        #[arg(short, long)]
+        repo: PathBuf,
+        /// The type of items to list
+        #[command(subcommand)]
+        list: ListType,
+    },
+    /// Manage git hooks that keep the index up to date
+    Hooks {
+        #[command(subcommand)]
+        command: HooksCommand,
+    },
+    /// Find folders and files that aren't part of any package or version, then delete or adopt them
+    Gc {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Only list the problems that were found
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -102,6 +756,37 @@ enum TemplateType {
     Version,
 }
 
+#[derive(Subcommand)]
+enum HooksCommand {
+    /// Install a git hook that fails if the index is out of date, by running `export --check`
+    Install {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Path to the index. Defaults to `output_path` in repository.toml, or `index.xml` in the current folder like `export`
+        #[arg(long)]
+        index: Option<PathBuf>,
+        /// The git hook to install
+        #[arg(long, value_enum, default_value_t = HookType::PreCommit)]
+        hook: HookType,
+        /// Also run `check` in the hook, failing if the repository has validation errors
+        #[arg(long, default_value_t = false)]
+        check: bool,
+        /// Replace an existing hook, even if it wasn't installed by this program
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ListType {
+    /// List all versions of a package
+    Versions {
+        /// Name of the package
+        identifier: String,
+    },
+}
+
 fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
     fs::create_dir_all(&dst)?;
     for entry in fs::read_dir(src)? {
@@ -116,144 +801,740 @@ fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result
     Ok(())
 }
 
-fn main() -> Result<()> {
-    // initialise logging
-    colog::init();
+/// Create a version folder by copying the source folder or file into it, then writing the changelog and
+/// `version.toml`. If the copied files don't match the package's entrypoints, the user is asked whether to keep
+/// the version, and it is removed otherwise.
+fn write_version(
+    pkg: &Package,
+    source_path: &Path,
+    ver_path: &Path,
+    ver_config_text: &str,
+    changelog: Option<&str>,
+) -> Result<Version> {
+    // create package dir
+    if !ver_path.exists() {
+        fs::create_dir(ver_path)?;
+    }
 
-    let args = Args::parse();
+    // don't create package config yet, do it after source files have been copied
 
-    match &args.command {
-        Commands::Export { output_path, repo } => {
-            let output_path: Cow<Path> = if output_path.exists() && output_path.metadata()?.is_dir()
-            {
-                output_path.join("index.xml").into()
-            } else {
-                output_path.into()
-            };
+    // copy the source to the version folder
+    {
+        let metadata = source_path.metadata()?;
+        if metadata.is_dir() {
+            copy_dir_all(source_path, ver_path)?;
+        } else if metadata.is_file() {
+            let dst_path = ver_path.join(source_path.file_name().unwrap());
+            fs::copy(source_path, dst_path)?;
+        }
+    }
+
+    if let Some(changelog) = changelog {
+        fs::write(ver_path.join("CHANGELOG.txt"), changelog)?;
+    }
+
+    // create package config
+    fs::write(ver_path.join(Version::CONFIG_FILENAME), ver_config_text)?;
 
-            let repo = repo::Repository::read(repo)?;
-            let index = repo.generate_index()?;
-            fs::write(&output_path, index)?;
-            println!("Wrote repository index to: {}", output_path.display());
+    // check that the copied files match the entrypoints, while the context is still fresh
+    let ver = Version::read(ver_path)?;
+    let result = ver
+        .sources(pkg)
+        .and_then(|sources| ver.check_entrypoints(pkg, &sources));
+    if let Err(err) = result {
+        log::warn!("{err}");
+        if !confirm::confirm("Keep this version anyway?", false)? {
+            fs::remove_dir_all(ver_path)?;
+            return Err(Cancelled.into());
         }
-        Commands::Publish {
-            identifier,
-            version: version_name,
-            path: source_path,
-            repo: repo_path,
-            new: should_create_new_package,
-        } => {
-            let repo = Repository::read(repo_path)?;
+    }
+    Ok(ver)
+}
 
-            // check that the source path exists
-            if !source_path.exists() {
-                return Err(SourceDoesNotExist(source_path.into()).into());
-            }
+/// Parse a date given on the command line, either as RFC 3339 or as a plain `YYYY-MM-DD` date at midnight UTC.
+fn parse_date(text: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Ok(date.to_utc());
+    }
+    match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        Ok(date) => Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc()),
+        Err(_) => Err(format!("invalid date `{text}`, expected e.g. `2023-01-01`")),
+    }
+}
 
-            // check that the identifier and version are sane
-            {
-                let opt = sanitize_filename::Options {
-                    truncate: true,  // true by default, truncates to 255 bytes
-                    windows: true, // default value depends on the OS, removes reserved names like `con` from start of strings on Windows
-                    replacement: "", // str to replace sanitized chars/strings
-                };
-                let sanitized_identifier =
-                    sanitize_filename::sanitize_with_options(identifier, opt.clone());
-                if &sanitized_identifier != identifier {
-                    return Err(InvalidPackageName(identifier.clone()).into());
-                }
-                if let Some(version) = version_name {
-                    let sanitized_version =
-                        sanitize_filename::sanitize_with_options(version, opt.clone());
-                    if &sanitized_version != version {
-                        return Err(InvalidPackageVersion(version.clone()).into());
-                    }
-                }
-            }
+/// Check whether one of the paths is inside the other, or they are the same path.
+/// Both paths should be canonicalized first.
+fn paths_overlap(a: &Path, b: &Path) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Check that the given name can be used as a file name on all platforms.
+fn is_filename_safe(name: &str) -> bool {
+    let opt = sanitize_filename::Options {
+        truncate: true,  // true by default, truncates to 255 bytes
+        windows: true, // default value depends on the OS, removes reserved names like `con` from start of strings on Windows
+        replacement: "", // str to replace sanitized chars/strings
+    };
+    sanitize_filename::sanitize_with_options(name, opt) == name
+}
 
-            // get or create the package
-            let pkg = if *should_create_new_package {
-                repo.add_package(&identifier)?
+/// Open the given file in the user's preferred editor, and wait for the editor to close.
+fn open_in_editor(path: &Path) -> Result<(), EditorError> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(windows) {
+                "notepad".into()
             } else {
-                let packages = repo.packages()?;
-                let pkg = packages
-                    .iter()
-                    .find(|pkg| pkg.identifier() == identifier.as_str());
-                let Some(pkg) = pkg else {
-                    return Err(PackageDoesNotExist(identifier.into()).into());
-                };
-                pkg.clone()
-            };
+                "vi".into()
+            }
+        });
 
-            // check that the version doesn't exist
-            let versions = pkg.versions()?;
-            let version_name: String = match version_name {
-                Some(version_name) => {
-                    let existing_version =
-                        versions.iter().find(|v| v.name() == version_name.as_str());
-                    if existing_version.is_some() {
-                        return Err(VersionAlreadyExists(version_name.into()).into());
-                    }
-                    version_name.into()
-                }
-                None => match versions
-                    .iter()
-                    .max_by(|a, b| Version::compare_version_names(&a.name(), &b.name()))
-                {
-                    Some(latest_version) => Version::increment_version(&latest_version.name())?,
-                    None => "0.0.1".into(),
-                },
-            };
-            let ver_path = pkg.path().join(&version_name);
-            let ver_config_path = ver_path.join("version.toml");
+    // the editor may be given with arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(&editor);
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|_| EditorError::FailedToLaunch(editor.clone()))?;
+    if !status.success() {
+        return Err(EditorError::ExitedWithError(editor));
+    }
+    Ok(())
+}
 
-            // create package dir
-            if !ver_path.exists() {
-                fs::create_dir(&ver_path)?;
-            }
+/// Options of the `export` command.
+struct ExportOptions<'a> {
+    format: IndexFormat,
+    max_warnings: Option<usize>,
+    manifest: bool,
+    checksums: bool,
+    slim: bool,
+    xml_version: Option<XmlVersion>,
+    /// Don't indent the index, overriding the repository config
+    no_indent: bool,
+    /// Sort attributes, overriding the repository config
+    sort_attributes: bool,
+    /// Use `file://` URLs instead of the URL pattern
+    local_urls: bool,
+    /// Upload the sources to the configured storage bucket
+    upload: bool,
+    /// Tag used for the `git_tag` URL variable, instead of the most recent tag
+    tag: Option<&'a str>,
+    html: Option<&'a Path>,
+    feed: Option<&'a Path>,
+    /// Only compare the generated index against the existing one, without writing anything
+    check: bool,
+}
 
-            // don't create package config yet, do it after source files have been copied
+/// Compare a generated index against the existing index file. Line endings are ignored, since git may convert them
+/// on checkout. Returns whether the existing index is up to date, and prints the differences if it isn't.
+fn check_index(output_path: &Path, index: &str, format: IndexFormat) -> Result<bool> {
+    let existing = match fs::read_to_string(output_path) {
+        Ok(existing) => existing,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            println!("Index does not exist: {}", output_path.display());
+            return Ok(false);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if existing.replace("\r\n", "\n") == index.replace("\r\n", "\n") {
+        println!("Index is up to date: {}", output_path.display());
+        return Ok(true);
+    }
 
-            // copy the source to the version folder
-            {
-                let metadata = source_path.metadata()?;
-                if metadata.is_dir() {
-                    copy_dir_all(source_path, ver_path)?;
-                } else if metadata.is_file() {
-                    let dst_path = ver_path.join(source_path.file_name().unwrap());
-                    fs::copy(source_path, dst_path)?;
+    println!("Index is out of date: {}", output_path.display());
+    if format == IndexFormat::Xml {
+        let changes = index::IndexModel::parse(&existing)
+            .and_then(|old| Ok(index::diff(&old, &index::IndexModel::parse(index)?)));
+        match changes {
+            Ok(changes) if changes.is_empty() => println!("  (only the formatting differs)"),
+            Ok(changes) => {
+                for change in changes.iter() {
+                    println!("  {}", change);
                 }
             }
+            Err(err) => println!("  (failed to compare the indexes: {})", err),
+        }
+    }
+    Ok(false)
+}
 
-            // create package config
-            {
-                let current_time = Utc::now().to_rfc3339();
-                let config_text = templates::generate_version_config(
-                    &VersionTemplateParams::default().time(&current_time),
-                );
-                fs::write(&ver_config_path, config_text)?;
-            }
-
-            println!("Created version {}", &version_name);
-            println!(
-                "Please edit the version configuration file: {}",
-                ver_config_path.display()
+/// Generate the index and write it to the given path, then show a summary of any warnings.
+fn export(repo_path: &Path, output_path: &Path, options: &ExportOptions) -> Result<()> {
+    let mut repo = Repository::read(repo_path)?;
+    repo.set_slim(options.slim);
+    repo.set_xml_format(
+        options.xml_version,
+        options.no_indent.then_some(false),
+        options.sort_attributes.then_some(true),
+    );
+    if let Some(tag) = options.tag {
+        repo.set_git_tag(tag.into());
+    }
+    if options.local_urls {
+        repo.set_url_pattern(local_url_pattern(repo.path()));
+    }
+    let storage_config = match options.upload {
+        true => Some(repo.storage().cloned().ok_or(NoStorageConfig)?),
+        false => None,
+    };
+    let storage = storage_config.as_ref().map(Storage::new).transpose()?;
+    if let Some(storage) = &storage {
+        repo.set_url_pattern(storage.url_pattern());
+    }
+    if templates::has_placeholder(repo.url_pattern()) && repo.url_command().is_none() {
+        let suggestion = match origin::origin_url_pattern(repo.path()) {
+            Some(url_pattern) => format!(", e.g. based on the git remote: {url_pattern}"),
+            None => String::new(),
+        };
+        warnings::warn(
+            "placeholder-url-pattern",
+            repo.path(),
+            format!("url_pattern in repository.toml still contains placeholder text{suggestion}"),
+        );
+    }
+    let index = match options.format {
+        IndexFormat::Xml => repo.generate_index()?,
+        IndexFormat::Json => Manifest::generate(&repo)?.to_json()?,
+    };
+    if let (IndexFormat::Xml, Some(min_version)) = (options.format, repo.min_reapack_version()) {
+        for x in compat::check(&index::IndexModel::parse(&index)?, min_version) {
+            warnings::warn(
+                "incompatible-feature",
+                &repo.path().join(&x.package),
+                format!(
+                    "{} requires ReaPack v{}, but min_reapack_version is {}",
+                    x.feature, x.since, min_version
+                ),
             );
         }
-        Commands::Init { repo } => {
-            let repo = path::absolute(repo)?;
-            let repo_config_path = repo.join("repository.toml");
-            if repo_config_path.exists() {
-                return Err(RepositoryAlreadyExists(repo).into());
-            }
+    }
 
-            let identifier = repo.file_name().map(|x| x.to_string_lossy());
+    let up_to_date = if options.check {
+        check_index(output_path, &index, options.format)?
+    } else {
+        // read the previous index before overwriting it, to summarise the changes
+        let previous = match options.format {
+            IndexFormat::Xml => fs::read_to_string(output_path)
+                .ok()
+                .and_then(|x| index::IndexModel::parse(&x).ok()),
+            IndexFormat::Json => None,
+        };
+        // upload before writing the index, so that it never links to missing files
+        if let Some(storage) = &storage {
+            let (uploaded, unchanged) = storage.upload_sources(&repo)?;
+            println!("Uploaded {uploaded} file(s) to storage, {unchanged} already up to date");
+        }
+        fs::write(output_path, &index)?;
+        println!("Wrote repository index to: {}", output_path.display());
+        if let Some(previous) = previous {
+            let changes = index::diff(&previous, &index::IndexModel::parse(&index)?);
+            println!("Changes: {}", index::summary(&changes));
+        }
 
-            let mut params = RepositoryTemplateParams::default();
-            if let Some(identifier) = &identifier {
-                params = params.identifier(identifier);
-            }
+        if options.manifest {
+            let manifest_path = manifest_path(output_path);
+            Manifest::generate(&repo)?.write(&manifest_path)?;
+            println!("Wrote manifest to: {}", manifest_path.display());
+        }
+
+        if options.checksums {
+            let checksums_path = checksums_path(output_path);
+            fs::write(&checksums_path, Manifest::generate(&repo)?.checksums())?;
+            println!("Wrote checksums to: {}", checksums_path.display());
+        }
+
+        if let Some(html) = options.html {
+            let pages = Site::new(&repo, html, output_path).write(html)?;
+            println!("Wrote {} page(s) to: {}", pages.len(), html.display());
+        }
+
+        if let Some(feed) = options.feed {
+            fs::write(feed, feed::generate_feed(&repo)?)?;
+            println!("Wrote feed to: {}", feed.display());
+        }
+
+        let mut outputs = vec![output_path.to_path_buf()];
+        if options.manifest {
+            outputs.push(manifest_path(output_path));
+        }
+        if options.checksums {
+            outputs.push(checksums_path(output_path));
+        }
+        outputs.extend(options.html.map(Path::to_path_buf));
+        outputs.extend(options.feed.map(Path::to_path_buf));
+        repo.record_export_outputs(&outputs)?;
+        repo.pin_version_commits()?;
+        true
+    };
+
+    // summarise warnings
+    let warnings = warnings::take();
+    if !warnings.is_empty() {
+        println!("{} warning(s) during export:", warnings.len());
+        for (package, rules) in warnings::group(&warnings, repo.path()) {
+            println!("  {}:", package);
+            for (rule, warnings) in rules {
+                println!("    {} ({}):", rule, warnings.len());
+                for warning in warnings {
+                    let summary = warning.message.lines().next().unwrap_or_default();
+                    println!("      {}", summary);
+                }
+            }
+        }
+    }
+    if let Some(max_warnings) = options.max_warnings {
+        if warnings.len() > max_warnings {
+            return Err(TooManyWarnings(warnings.len(), max_warnings).into());
+        }
+    }
+    if !up_to_date {
+        return Err(IndexOutOfDate(output_path.into()).into());
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // initialise logging
+    colog::init();
+
+    let args = Args::parse();
+    confirm::configure(args.yes, args.no_input);
+    if let Some(pandoc) = &args.pandoc {
+        repo::set_pandoc_path(path::absolute(pandoc)?);
+    }
+
+    match &args.command {
+        Commands::Export {
+            output_path,
+            repo,
+            format,
+            max_warnings,
+            watch,
+            manifest,
+            checksums,
+            slim,
+            xml_version,
+            no_indent,
+            sort_attributes,
+            local_urls,
+            upload,
+            tag,
+            html,
+            feed,
+            check,
+        } => {
+            let output_path = match output_path {
+                Some(output_path) => output_path.clone(),
+                None => Repository::read(repo)?.default_index_path(*format),
+            };
+            let output_path: Cow<Path> = if output_path.exists() && output_path.metadata()?.is_dir()
+            {
+                output_path.join(format.default_filename()).into()
+            } else {
+                output_path.into()
+            };
+
+            let options = ExportOptions {
+                format: *format,
+                max_warnings: *max_warnings,
+                manifest: *manifest,
+                checksums: *checksums,
+                slim: *slim,
+                xml_version: *xml_version,
+                no_indent: *no_indent,
+                sort_attributes: *sort_attributes,
+                local_urls: *local_urls,
+                upload: *upload,
+                tag: tag.as_deref(),
+                html: html.as_deref(),
+                feed: feed.as_deref(),
+                check: *check,
+            };
+            if *watch {
+                let mut ignored = vec![
+                    output_path.to_path_buf(),
+                    manifest_path(&output_path),
+                    checksums_path(&output_path),
+                    repo.join(state::ExportState::FILENAME),
+                ];
+                ignored.extend(html.clone());
+                ignored.extend(feed.clone());
+                watch::watch(repo, &ignored, || export(repo, &output_path, &options))?;
+            } else {
+                export(repo, &output_path, &options)?;
+            }
+        }
+        Commands::New {
+            repo,
+            identifier,
+            pkg_type,
+            name,
+            category,
+            entrypoint,
+            stub,
+            dry_run,
+            show_content,
+        } => {
+            let repo = Repository::read(repo)?;
+            if !is_filename_safe(identifier) {
+                return Err(InvalidPackageName(identifier.into()).into());
+            }
+            let typ: &str = pkg_type.into();
+            let entrypoint = match (pkg_type, entrypoint) {
+                (PackageType::Script | PackageType::Effect, Some(entrypoint)) => {
+                    Some(entrypoint.clone())
+                }
+                (PackageType::Script, None) => Some(format!("{identifier}.lua")),
+                (PackageType::Effect, None) => Some(format!("{identifier}.jsfx")),
+                (_, Some(_)) => return Err(EntrypointNotSupported(typ.into()).into()),
+                (_, None) => None,
+            };
+            let name = name.as_deref().unwrap_or(identifier);
+
+            // only scripts can have entrypoints in the action list
+            let entrypoints = match (pkg_type, &entrypoint) {
+                (PackageType::Script, Some(entrypoint)) => {
+                    templates::script_entrypoints(&[entrypoint])
+                }
+                _ => String::new(),
+            };
+            let stub_file = match (stub, &entrypoint) {
+                (Some(stub), Some(entrypoint)) => {
+                    let Some(content) = templates::stub_entrypoint(typ, name, entrypoint) else {
+                        return Err(NoStubForFile(entrypoint.clone()).into());
+                    };
+                    let stub_path = stub.join(entrypoint);
+                    if stub_path.exists() {
+                        return Err(StubAlreadyExists(stub_path).into());
+                    }
+                    Some((stub, stub_path, content))
+                }
+                (Some(_), None) => return Err(NoStubForFile(typ.into()).into()),
+                (None, _) => None,
+            };
+
+            let params = PackageTemplateParams::default()
+                .name(name)
+                .category(category)
+                .typ(typ)
+                .identifier(identifier)
+                .author(repo.author())
+                .entrypoints(&entrypoints);
+
+            if *dry_run {
+                let pkg_path = repo.new_package_path(identifier)?;
+                let files = [PlannedFile::new(
+                    pkg_path
+                        .strip_prefix(repo.path())?
+                        .join(Package::CONFIG_FILENAME),
+                    Some(templates::generate_package_config(&params)),
+                )];
+                plan::print_plan(repo.path(), &files, *show_content);
+                if let Some((stub, stub_path, content)) = stub_file {
+                    let files = [PlannedFile::new(
+                        stub_path.strip_prefix(stub)?,
+                        Some(content),
+                    )];
+                    plan::print_plan(stub, &files, *show_content);
+                }
+                return Ok(());
+            }
+
+            let pkg = repo.add_package_with_params(identifier, params)?;
+            println!("Created package: {}", pkg.path().display());
+
+            if let Some((_, stub_path, content)) = stub_file {
+                if let Some(parent) = stub_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&stub_path, content)?;
+                println!("Created stub entrypoint: {}", stub_path.display());
+            }
+            println!(
+                "Please edit the package configuration file: {}",
+                pkg.config_path().display()
+            );
+        }
+        Commands::Publish {
+            identifier,
+            version: version_name,
+            path: source_path,
+            repo: repo_path,
+            new: should_create_new_package,
+            interactive,
+            dry_run,
+            show_content,
+            changelog,
+            changelog_file,
+            changelog_from_git,
+            since_tag,
+        } => {
+            let repo = Repository::read(repo_path)?;
+
+            if *interactive && !std::io::stdin().is_terminal() {
+                return Err(InteractiveRequiresTerminal.into());
+            }
+
+            // check that the source path exists
+            if !source_path.exists() {
+                return Err(SourceDoesNotExist(source_path.into()).into());
+            }
+
+            // copying a folder into itself would never finish, and copying the repository into a version
+            // duplicates every package
+            if paths_overlap(&source_path.canonicalize()?, &repo.path().canonicalize()?) {
+                return Err(SourceOverlapsRepository(source_path.into()).into());
+            }
+
+            let identifier = match identifier {
+                Some(identifier) => identifier.clone(),
+                None if *should_create_new_package => interactive::prompt_identifier()?,
+                None => interactive::select_package(&repo)?,
+            };
+
+            // check that the identifier is sane
+            if !is_filename_safe(&identifier) {
+                return Err(InvalidPackageName(identifier).into());
+            }
+
+            // get the package, it is only created after the version name has been checked
+            let existing_pkg = if *should_create_new_package {
+                None
+            } else {
+                let Some(pkg) = repo.package(&identifier)? else {
+                    return Err(PackageDoesNotExist(identifier).into());
+                };
+                Some(pkg)
+            };
+            let pkg_path = match &existing_pkg {
+                Some(pkg) => pkg.path().to_path_buf(),
+                None => repo.new_package_path(&identifier)?,
+            };
+
+            // check that the version doesn't exist
+            let versions = match &existing_pkg {
+                Some(pkg) => pkg.versions()?,
+                None => vec![],
+            };
+            let version_name: String = match version_name {
+                Some(version_name) => version_name.into(),
+                None => {
+                    let suggested = match Version::latest(&versions) {
+                        Some(latest_version) => Version::increment_version(&latest_version.name())?,
+                        None => "0.0.1".into(),
+                    };
+                    if *interactive {
+                        interactive::prompt_version(&suggested)?
+                    } else {
+                        suggested
+                    }
+                }
+            };
+            if !is_filename_safe(&version_name) {
+                return Err(InvalidPackageVersion(version_name).into());
+            }
+            if versions.iter().any(|v| v.name() == version_name.as_str()) {
+                return Err(VersionAlreadyExists(version_name).into());
+            }
+            let ver_path = pkg_path.join(&version_name);
+            let ver_config_path = ver_path.join("version.toml");
+            let now = Utc::now();
+            let current_time = now.to_rfc3339();
+
+            let changelog = match (changelog, changelog_file) {
+                (Some(text), _) => Some(format!("{}\n", text.trim())),
+                (_, Some(path)) => Some(fs::read_to_string(path)?),
+                _ if *changelog_from_git => {
+                    let previous_version = Version::latest(&versions);
+                    let since = match (since_tag, previous_version) {
+                        (Some(tag), _) => changelog::GitSince::Tag(tag),
+                        (None, Some(ver)) => changelog::GitSince::Time(ver.time()),
+                        (None, None) => changelog::GitSince::Beginning,
+                    };
+                    let text = changelog::from_git(source_path, since)?;
+                    if text.is_none() {
+                        warnings::warn(
+                            "empty-git-changelog",
+                            source_path,
+                            "no commits found for the changelog, writing a template instead".into(),
+                        );
+                    }
+                    text
+                }
+                _ if *interactive => interactive::prompt_changelog()?,
+                _ => None,
+            };
+            let changelog_is_template = changelog.is_none();
+            let changelog = changelog.unwrap_or_else(|| {
+                templates::changelog_template(&version_name, &now.format("%Y-%m-%d").to_string())
+            });
+
+            // new packages use the default template, which is a script package
+            let is_script = existing_pkg
+                .as_ref()
+                .is_none_or(|pkg| pkg.pkg_type() == PackageType::Script);
+            let mut pkg_entrypoints = None;
+            let mut ver_entrypoints = None;
+            if *interactive && is_script {
+                let files = interactive::list_files(source_path)?;
+                let selected: Vec<usize> = match &existing_pkg {
+                    Some(pkg) => {
+                        let main = pkg
+                            .entrypoints()?
+                            .and_then(|x| x.get(&ActionListSection::Main));
+                        files
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, file)| main.is_some_and(|x| x.is_match(file)))
+                            .map(|(i, _)| i)
+                            .collect()
+                    }
+                    // suggest the files that look like scripts
+                    None => files
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, file)| {
+                            let file = file.to_lowercase();
+                            [".lua", ".eel", ".py"].iter().any(|x| file.ends_with(x))
+                        })
+                        .map(|(i, _)| i)
+                        .collect(),
+                };
+                let entrypoints = interactive::select_entrypoints(&files, &selected)?;
+                if existing_pkg.is_none() {
+                    let patterns: Vec<String> = entrypoints
+                        .iter()
+                        .map(|x| interactive::escape_glob(x))
+                        .collect();
+                    let patterns: Vec<&str> = patterns.iter().map(|x| x.as_str()).collect();
+                    pkg_entrypoints = Some(templates::script_entrypoints(&patterns));
+                } else {
+                    // only override the package's entrypoints if a different selection was made
+                    let unchanged = entrypoints.len() == selected.len()
+                        && selected.iter().all(|i| entrypoints.contains(&files[*i]));
+                    if !unchanged {
+                        ver_entrypoints = Some(entrypoints);
+                    }
+                }
+            }
+            let pkg_params = match &pkg_entrypoints {
+                Some(entrypoints) => PackageTemplateParams::default().entrypoints(entrypoints),
+                None => PackageTemplateParams::default(),
+            };
+            let mut ver_config_text = templates::generate_version_config(
+                &VersionTemplateParams::default().time(&current_time),
+            );
+            if let Some(entrypoints) = &ver_entrypoints {
+                ver_config_text =
+                    interactive::with_version_entrypoints(&ver_config_text, entrypoints)?;
+            }
+
+            if *dry_run {
+                let mut files = vec![];
+                if existing_pkg.is_none() {
+                    files.push(PlannedFile::new(
+                        pkg_path.join(Package::CONFIG_FILENAME),
+                        Some(templates::generate_package_config(&pkg_params)),
+                    ));
+                }
+                if source_path.is_dir() {
+                    for entry in walkdir::WalkDir::new(source_path) {
+                        let entry = entry?;
+                        if entry.file_type().is_file() {
+                            let relpath = entry.path().strip_prefix(source_path)?;
+                            files.push(PlannedFile::new(ver_path.join(relpath), None));
+                        }
+                    }
+                } else {
+                    files.push(PlannedFile::new(
+                        ver_path.join(source_path.file_name().unwrap()),
+                        None,
+                    ));
+                }
+                files.push(PlannedFile::new(
+                    ver_path.join("CHANGELOG.txt"),
+                    Some(changelog.clone()),
+                ));
+                files.push(PlannedFile::new(&ver_config_path, Some(ver_config_text)));
+                for file in files.iter_mut() {
+                    file.path = file.path.strip_prefix(repo.path())?.to_path_buf();
+                }
+                plan::print_plan(repo.path(), &files, *show_content);
+                return Ok(());
+            }
+
+            let created_pkg = existing_pkg.is_none();
+            let pkg = match existing_pkg {
+                Some(pkg) => pkg,
+                None => repo.add_package_with_params(&identifier, pkg_params)?,
+            };
+
+            let result = write_version(
+                &pkg,
+                source_path,
+                &ver_path,
+                &ver_config_text,
+                Some(&changelog),
+            );
+            if result.is_err() && created_pkg {
+                // don't leave a package without versions behind, e.g. when the version was declined
+                fs::remove_dir_all(pkg.path())?;
+            }
+            result?;
+
+            println!("Created version {}", &version_name);
+            println!(
+                "Please edit the version configuration file: {}",
+                ver_config_path.display()
+            );
+            println!(
+                "You can open it in your editor with: edit {} {}",
+                pkg.identifier(),
+                &version_name
+            );
+            if changelog_is_template {
+                println!(
+                    "Please write the changelog: {}",
+                    ver_path.join("CHANGELOG.txt").display()
+                );
+            }
+        }
+        Commands::Init {
+            repo,
+            dry_run,
+            show_content,
+        } => {
+            let repo = path::absolute(repo)?;
+            if Repository::find_config(&repo).is_some() {
+                return Err(RepositoryAlreadyExists(repo).into());
+            }
+            let repo_config_path = repo.join(Repository::CONFIG_FILENAME);
+
+            let identifier = repo.file_name().map(|x| x.to_string_lossy());
+
+            let mut params = RepositoryTemplateParams::default();
+            if let Some(identifier) = &identifier {
+                params = params.identifier(identifier);
+            }
+            let url_pattern = origin::origin_url_pattern(&repo);
+            if let Some(url_pattern) = &url_pattern {
+                println!("Found URL pattern from the git remote `origin`: {url_pattern}");
+                if confirm::confirm("Use this URL pattern?", true)? {
+                    params = params.url_pattern(url_pattern);
+                }
+            }
             let config_text = templates::generate_repository_config(&params);
+            if *dry_run {
+                let files = [PlannedFile::new(
+                    Repository::CONFIG_FILENAME,
+                    Some(config_text),
+                )];
+                plan::print_plan(&repo, &files, *show_content);
+                return Ok(());
+            }
             fs::write(&repo_config_path, config_text)?;
 
             println!("Created repository at {}", &path::absolute(repo)?.display());
@@ -276,6 +1557,971 @@ fn main() -> Result<()> {
             };
             println!("{}", text);
         }
+        Commands::Bump {
+            repo,
+            identifier,
+            major,
+            minor,
+            patch,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let Some(latest) = pkg.latest_version()? else {
+                return Err(PackageHasNoVersions(identifier.into()).into());
+            };
+
+            let part = match (major, minor, patch) {
+                (true, _, _) => Some(VersionPart::Major),
+                (_, true, _) => Some(VersionPart::Minor),
+                (_, _, true) => Some(VersionPart::Patch),
+                _ => None,
+            };
+            let version_name = match part {
+                Some(part) => Version::increment_version_part(&latest.name(), part)?,
+                None => Version::increment_version(&latest.name())?,
+            };
+            if !is_filename_safe(&version_name) {
+                return Err(InvalidPackageVersion(version_name).into());
+            }
+            let ver_path = pkg.path().join(&version_name);
+            if ver_path.exists() {
+                return Err(VersionAlreadyExists(version_name).into());
+            }
+
+            copy_dir_all(latest.path(), &ver_path)?;
+            let mut ver = Version::read(&ver_path)?;
+            ver.set_time(Utc::now())?;
+            if ver.yanked() {
+                ver.set_yanked(false)?;
+            }
+
+            println!("Created version {} from {}", version_name, latest.name());
+            println!(
+                "Please edit the version configuration file: {}",
+                ver.config_path().display()
+            );
+        }
+        Commands::Changelog {
+            repo,
+            identifier,
+            since,
+            output,
+        } => {
+            let repo = Repository::read(repo)?;
+            let pkg = match identifier {
+                Some(identifier) => match repo.package(identifier)? {
+                    Some(pkg) => Some(pkg),
+                    None => return Err(PackageNotFound(identifier.into()).into()),
+                },
+                None => None,
+            };
+            let text = changelog::generate(&repo, pkg.as_ref(), since.as_deref())?;
+            match output {
+                Some(output) => {
+                    fs::write(output, text)?;
+                    println!("Wrote changelog to: {}", output.display());
+                }
+                None => print!("{text}"),
+            }
+        }
+        Commands::Yank {
+            repo,
+            identifier,
+            version: version_name,
+            undo,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let Some(mut ver) = pkg.version(version_name)? else {
+                return Err(VersionNotFound(version_name.into()).into());
+            };
+
+            ver.set_yanked(!undo)?;
+            if *undo {
+                println!("Restored version {} of {}", ver.name(), pkg.identifier());
+            } else {
+                println!("Yanked version {} of {}", ver.name(), pkg.identifier());
+            }
+        }
+        Commands::Deprecate {
+            repo,
+            identifier,
+            message,
+            undo,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(mut pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+
+            if *undo {
+                pkg.set_deprecated(None)?;
+                println!("Removed deprecation of {}", pkg.identifier());
+            } else {
+                pkg.set_deprecated(Some(match message {
+                    Some(message) => Deprecation::Message(message.clone()),
+                    None => Deprecation::Flag(true),
+                }))?;
+                println!("Deprecated {}", pkg.identifier());
+            }
+        }
+        Commands::Install {
+            repo,
+            identifier,
+            version: version_name,
+            reaper_path,
+            uninstall,
+            platform,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let ver = match version_name {
+                Some(version_name) => pkg.version(version_name)?,
+                None => pkg.latest_indexed_version(&repo)?,
+            };
+            let Some(ver) = ver else {
+                return match version_name {
+                    Some(version_name) => Err(VersionNotFound(version_name.into()).into()),
+                    None => Err(PackageHasNoVersions(identifier.into()).into()),
+                };
+            };
+
+            let platform = platform.unwrap_or_else(install::current_platform);
+            if *uninstall {
+                let paths = install::uninstall(&repo, &pkg, &ver, reaper_path, platform)?;
+                for path in paths.iter() {
+                    println!("Removed {}", path.display());
+                }
+                println!(
+                    "Uninstalled {} {} ({} file(s))",
+                    pkg.identifier(),
+                    ver.name(),
+                    paths.len()
+                );
+            } else {
+                let paths = install::install(&repo, &pkg, &ver, reaper_path, platform)?;
+                for path in paths.iter() {
+                    println!("Installed {}", path.display());
+                }
+                println!(
+                    "Installed {} {} ({} file(s))",
+                    pkg.identifier(),
+                    ver.name(),
+                    paths.len()
+                );
+            }
+        }
+        Commands::Remove {
+            repo,
+            identifier,
+            version: version_name,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let (target_path, description) = match version_name {
+                Some(version_name) => {
+                    let Some(ver) = pkg.version(version_name)? else {
+                        return Err(VersionNotFound(version_name.into()).into());
+                    };
+                    (
+                        ver.path().to_path_buf(),
+                        format!("version {} of {}", ver.name(), pkg.identifier()),
+                    )
+                }
+                None => (
+                    pkg.path().to_path_buf(),
+                    format!("package {}", pkg.identifier()),
+                ),
+            };
+
+            let files: Vec<PathBuf> = walkdir::WalkDir::new(&target_path)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .collect();
+
+            println!("The following files will be removed:");
+            for path in files.iter() {
+                println!("  {}", path.display());
+            }
+            if !confirm::confirm(&format!("Remove {}?", description), false)? {
+                return Err(Cancelled.into());
+            }
+
+            fs::remove_dir_all(&target_path)?;
+            println!("Removed {} ({} files)", description, files.len());
+        }
+        Commands::Prune {
+            repo,
+            keep,
+            dry_run,
+            archive_to,
+        } => {
+            let repo = Repository::read(repo)?;
+            let mut targets = vec![];
+            for pkg in repo.packages()? {
+                let mut versions = pkg.versions()?;
+                versions.sort_by(|a, b| Version::compare_version_names(&b.name(), &a.name()));
+                for ver in versions.into_iter().skip(*keep as usize) {
+                    targets.push((pkg.identifier().to_string(), ver));
+                }
+            }
+
+            if targets.is_empty() {
+                println!("No versions to prune");
+                return Ok(());
+            }
+            println!(
+                "The following versions will be {}:",
+                if archive_to.is_some() {
+                    "archived"
+                } else {
+                    "removed"
+                }
+            );
+            for (identifier, ver) in targets.iter() {
+                println!("  {} {}", identifier, ver.name());
+            }
+            if *dry_run {
+                return Ok(());
+            }
+            if !confirm::confirm(&format!("Prune {} version(s)?", targets.len()), false)? {
+                return Err(Cancelled.into());
+            }
+
+            for (identifier, ver) in targets.iter() {
+                match archive_to {
+                    Some(archive_to) => {
+                        let target = archive_to.join(identifier).join(ver.name().as_ref());
+                        if target.exists() {
+                            return Err(VersionAlreadyExists(target.display().to_string()).into());
+                        }
+                        fs::create_dir_all(archive_to.join(identifier))?;
+                        // rename fails across filesystems, so copy instead
+                        copy_dir_all(ver.path(), &target)?;
+                        fs::remove_dir_all(ver.path())?;
+                    }
+                    None => fs::remove_dir_all(ver.path())?,
+                }
+            }
+            println!("Pruned {} version(s)", targets.len());
+        }
+        Commands::Archive {
+            repo,
+            before,
+            to,
+            url,
+            dry_run,
+        } => {
+            let repo = Repository::read(repo)?;
+            let destination = match to {
+                Some(to) => to.clone(),
+                None => repo.path().join("archive"),
+            };
+
+            let mut targets = vec![];
+            for pkg in repo.packages()? {
+                let Some(latest) = pkg.latest_version()? else {
+                    continue;
+                };
+                for ver in pkg.versions()? {
+                    if ver.name() != latest.name() && ver.time() < *before && ver.remote().is_none()
+                    {
+                        targets.push((pkg.clone(), ver));
+                    }
+                }
+            }
+
+            if targets.is_empty() {
+                println!("No versions to archive");
+                return Ok(());
+            }
+            println!(
+                "The following versions will be moved to {}:",
+                destination.display()
+            );
+            for (pkg, ver) in targets.iter() {
+                println!("  {} {}", pkg.identifier(), ver.name());
+            }
+            if *dry_run {
+                return Ok(());
+            }
+            if !confirm::confirm(&format!("Archive {} version(s)?", targets.len()), false)? {
+                return Err(Cancelled.into());
+            }
+
+            for (pkg, ver) in targets.iter_mut() {
+                archive::archive_version(pkg, ver, &destination, url.as_deref())?;
+            }
+            println!("Archived {} version(s)", targets.len());
+        }
+        Commands::Edit {
+            repo,
+            identifier,
+            version: version_name,
+            readme,
+            changelog,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let ver = match version_name {
+                Some(version_name) => match pkg.version(version_name)? {
+                    Some(ver) => Some(ver),
+                    None => return Err(VersionNotFound(version_name.into()).into()),
+                },
+                None if *changelog => match pkg.latest_version()? {
+                    Some(ver) => Some(ver),
+                    None => return Err(PackageHasNoVersions(pkg.identifier().into()).into()),
+                },
+                None => None,
+            };
+
+            let path = match (&ver, readme, changelog) {
+                (_, true, _) => pkg.path().join("README.md"),
+                (Some(ver), _, true) => ver.path().join("CHANGELOG.txt"),
+                (Some(ver), _, false) => ver.config_path(),
+                (None, _, _) => pkg.config_path(),
+            };
+            // new changelogs are edited in a temporary file, so closing the editor without saving doesn't leave the
+            // placeholder behind
+            let temp_path = if *changelog && !path.exists() {
+                let temp_path = tempfile::Builder::new()
+                    .prefix("CHANGELOG-")
+                    .suffix(".txt")
+                    .tempfile()?
+                    .into_temp_path();
+                fs::write(&temp_path, templates::CHANGELOG_PLACEHOLDER)?;
+                Some(temp_path)
+            } else {
+                None
+            };
+            let edit_path = temp_path.as_deref().unwrap_or(&path);
+            println!("Editing {}", path.display());
+            loop {
+                open_in_editor(edit_path)?;
+
+                // check that the edited file can still be read
+                let result = match (&ver, readme, changelog) {
+                    (_, true, _) => break,
+                    (_, _, true) => {
+                        let text = fs::read_to_string(edit_path)?;
+                        if temp_path.is_some() && text == templates::CHANGELOG_PLACEHOLDER {
+                            break;
+                        }
+                        validate::check_changelog_text(&repo, &text).map_err(Into::into)
+                    }
+                    (Some(ver), _, false) => Version::read(ver.path()).and_then(|ver| {
+                        let sources = ver.sources(&pkg)?;
+                        ver.check_entrypoints(&pkg, &sources)
+                    }),
+                    (None, _, _) => Package::read(pkg.path()).map(|_| ()),
+                };
+                let Err(err) = result else {
+                    break;
+                };
+                if !std::io::stdin().is_terminal() {
+                    return Err(err);
+                }
+                println!("The edited file is invalid: {err}");
+                if !confirm::confirm("Edit it again?", true)? {
+                    return Err(err);
+                }
+            }
+            if let Some(temp_path) = temp_path {
+                let text = fs::read_to_string(&temp_path)?;
+                if text == templates::CHANGELOG_PLACEHOLDER {
+                    println!("The changelog was not changed, so it was not created");
+                } else {
+                    fs::write(&path, text)?;
+                }
+            }
+        }
+        Commands::Rename { repo, old, new } => {
+            let repo = Repository::read(repo)?;
+            if !is_filename_safe(new) {
+                return Err(InvalidPackageName(new.clone()).into());
+            }
+            let Some(pkg) = repo.package(old)? else {
+                return Err(PackageNotFound(old.into()).into());
+            };
+
+            let pkg = repo.rename_package(&pkg, new)?;
+            println!("Renamed package {} to {}", old, pkg.identifier());
+            println!("Moved package to: {}", pkg.path().display());
+            warn!("users who have installed `{old}` will see `{new}` as a new package, and must install it again to receive updates");
+        }
+        Commands::Set {
+            repo,
+            identifier,
+            assignments,
+        } => {
+            let repo = Repository::read(repo)?;
+            let config_path = match identifier {
+                Some(identifier) => {
+                    let Some(pkg) = repo.package(identifier)? else {
+                        return Err(PackageNotFound(identifier.into()).into());
+                    };
+                    set::set_fields::<PackageConfig>(&pkg.config_path(), assignments)?;
+                    pkg.config_path()
+                }
+                None => {
+                    let config_path = repo.config_path();
+                    set::set_fields::<RepositoryConfig>(&config_path, assignments)?;
+                    config_path
+                }
+            };
+            println!("Updated {}", config_path.display());
+        }
+        Commands::Copy {
+            repo,
+            source,
+            new,
+            with_latest,
+        } => {
+            let repo = Repository::read(repo)?;
+            if !is_filename_safe(new) {
+                return Err(InvalidPackageName(new.clone()).into());
+            }
+            let Some(pkg) = repo.package(source)? else {
+                return Err(PackageNotFound(source.into()).into());
+            };
+            let latest = if *with_latest {
+                let Some(latest) = pkg.latest_version()? else {
+                    return Err(PackageHasNoVersions(source.into()).into());
+                };
+                Some(latest)
+            } else {
+                None
+            };
+
+            let new_pkg = repo.copy_package(&pkg, new)?;
+            println!("Created package {} from {}", new_pkg.identifier(), source);
+            if let Some(latest) = latest {
+                let ver_path = new_pkg.path().join(latest.name().as_ref());
+                copy_dir_all(latest.path(), &ver_path)?;
+                let mut ver = Version::read(&ver_path)?;
+                ver.set_time(Utc::now())?;
+                if ver.yanked() {
+                    ver.set_yanked(false)?;
+                }
+                println!("Created version {}", latest.name());
+            }
+            println!(
+                "Please edit the package configuration: {}",
+                new_pkg.config_path().display()
+            );
+        }
+        Commands::Check { repo } => {
+            let repo = Repository::read(repo)?;
+            let problems = validate::validate(&repo)?;
+            for problem in problems.iter() {
+                println!("{}", problem);
+            }
+
+            let errors = problems.count(Severity::Error);
+            let warnings = problems.count(Severity::Warning);
+            println!("Found {} error(s), {} warning(s)", errors, warnings);
+            if errors > 0 {
+                return Err(ValidationFailed(errors).into());
+            }
+        }
+        Commands::Status { repo, index } => {
+            let repo = Repository::read(repo)?;
+            let packages = repo.packages()?;
+            let mut version_count = 0;
+            let mut newest_time = None;
+            for pkg in packages.iter() {
+                for ver in pkg.versions()? {
+                    version_count += 1;
+                    newest_time = newest_time.max(Some(ver.time()));
+                }
+            }
+
+            println!("Repository: {}", repo.identifier());
+            println!("Author: {}", repo.author());
+            println!("Packages: {}", packages.len());
+            println!("Versions: {}", version_count);
+            match repo.git_hash() {
+                Ok(hash) => println!("Commit: {}", hash),
+                Err(err) => println!("Commit: unknown ({})", err),
+            }
+            match repo.git_is_dirty() {
+                Ok(true) => println!("Working tree: has uncommitted changes"),
+                Ok(false) => println!("Working tree: clean"),
+                Err(err) => println!("Working tree: unknown ({})", err),
+            }
+
+            let index_path = match index {
+                Some(index) => index.clone(),
+                None => repo.index_path(),
+            };
+            let index_time = index_path
+                .metadata()
+                .and_then(|x| x.modified())
+                .ok()
+                .map(chrono::DateTime::<Utc>::from);
+            match (index_time, newest_time) {
+                (None, _) => println!("Index: not found at {}", index_path.display()),
+                (Some(index_time), Some(newest_time)) if index_time < newest_time => println!(
+                    "Index: out of date, the newest version was published after {} was last exported",
+                    index_path.display()
+                ),
+                (Some(_), _) => println!("Index: up to date"),
+            }
+        }
+        Commands::Stats { repo, json } => {
+            let repo = Repository::read(repo)?;
+            let stats = stats::Stats::generate(&repo)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                stats.print();
+            }
+        }
+        Commands::ReadmeGen { repo, update } => {
+            let repo = Repository::read(repo)?;
+            let table = listing::generate(&repo)?;
+            match update {
+                Some(path) => {
+                    let document = fs::read_to_string(path)?;
+                    let updated = listing::replace_between_markers(&document, &table)?;
+                    if updated == document {
+                        println!("Package listing is up to date: {}", path.display());
+                    } else {
+                        fs::write(path, updated)?;
+                        println!("Updated package listing in: {}", path.display());
+                    }
+                }
+                None => print!("{table}"),
+            }
+        }
+        Commands::Report { repo } => {
+            let repo = Repository::read(repo)?;
+            report::Report::generate(&repo)?.print();
+        }
+        Commands::Query {
+            repo,
+            format,
+            versions,
+        } => {
+            let repo = Repository::read(repo)?;
+            let (default_format, placeholders) = if *versions {
+                (r"{identifier}\t{version}", query::VERSION_PLACEHOLDERS)
+            } else {
+                (r"{identifier}\t{latest}", query::PACKAGE_PLACEHOLDERS)
+            };
+            let format = query::Format::parse(format.as_deref().unwrap_or(default_format))?;
+            format.check_placeholders(placeholders)?;
+
+            let mut packages = repo.packages()?;
+            packages.sort_by(|a, b| a.identifier().cmp(&b.identifier()));
+            for pkg in packages.iter() {
+                let values = query::package_values(&repo, pkg)?;
+                if !*versions {
+                    println!("{}", format.render(&values));
+                    continue;
+                }
+                let mut pkg_versions = pkg.versions()?;
+                pkg_versions.sort_by(|a, b| Version::compare_version_names(&a.name(), &b.name()));
+                for ver in pkg_versions.iter() {
+                    let values = query::version_values(&repo, pkg, ver, &values);
+                    println!("{}", format.render(&values));
+                }
+            }
+        }
+        Commands::Search { repo, query, limit } => {
+            let repo = Repository::read(repo)?;
+            let results = search::search(&repo, &query.join(" "))?;
+            if results.is_empty() {
+                println!("No packages found");
+                return Ok(());
+            }
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .take(*limit)
+                .map(|result| {
+                    vec![
+                        result.pkg.identifier().to_string(),
+                        result.pkg.name().to_string(),
+                        result.pkg.category().to_string(),
+                        result.matched.join(", "),
+                    ]
+                })
+                .collect();
+            println!(
+                "{}",
+                stats::format_table(&["Package", "Name", "Category", "Matched"], &rows)
+            );
+            if results.len() > *limit {
+                println!("... and {} more", results.len() - *limit);
+            }
+        }
+        Commands::VerifyUrls {
+            repo,
+            concurrency,
+            retries,
+        } => {
+            let repo = Repository::read(repo)?;
+            let checks = verify::collect_urls(&repo)?;
+            println!("Checking {} source URL(s)...", checks.len());
+            let problems = verify::verify_urls(&checks, *concurrency, *retries);
+            for (check, problem) in problems.iter() {
+                println!("{}: {}", check.url, problem);
+                println!("  from {}", check.path.display());
+            }
+            if !problems.is_empty() {
+                return Err(UrlsNotVerified(problems.len(), checks.len()).into());
+            }
+            println!("All source URLs are reachable");
+        }
+        Commands::Sign {
+            repo,
+            index,
+            tool,
+            key,
+        } => {
+            let index = match index {
+                Some(index) => index.clone(),
+                None => Repository::read(repo)?.index_path(),
+            };
+            let signature_path = tool.sign(&index, key.as_deref())?;
+            println!("Wrote signature to: {}", signature_path.display());
+        }
+        Commands::Verify {
+            repo,
+            index,
+            tool,
+            key,
+        } => {
+            let index = match index {
+                Some(index) => index.clone(),
+                None => Repository::read(repo)?.index_path(),
+            };
+            tool.verify(&index, key.as_deref())?;
+            println!(
+                "Signature is valid: {}",
+                tool.signature_path(&index).display()
+            );
+        }
+        Commands::Diff { repo, index } => {
+            let repo = Repository::read(repo)?;
+            let index_path = match index {
+                Some(index) => index.clone(),
+                None => repo.index_path(),
+            };
+            let old = index::IndexModel::parse(&fs::read_to_string(&index_path)?)?;
+            let new = index::IndexModel::parse(&repo.generate_index()?)?;
+
+            let changes = index::diff(&old, &new);
+            if changes.is_empty() {
+                println!("No changes compared to {}", index_path.display());
+            } else {
+                for change in changes.iter() {
+                    println!("{}", change);
+                }
+                println!(
+                    "{} change(s) compared to {}",
+                    changes.len(),
+                    index_path.display()
+                );
+            }
+        }
+        Commands::Import {
+            repo,
+            index,
+            from_headers,
+        } => {
+            let repo = Repository::read(repo)?;
+            let count = match (index, from_headers) {
+                (_, Some(from_headers)) => import::import_from_headers(&repo, from_headers)?,
+                (Some(index), None) => {
+                    let index = String::from_utf8(import::fetch(index)?)?;
+                    let index = index::IndexModel::parse(&index)?;
+                    import::import(&repo, &index)?
+                }
+                (None, None) => unreachable!("clap requires one of the arguments"),
+            };
+            println!("Imported {} package(s)", count);
+        }
+        Commands::ImportRelease {
+            repo,
+            identifier,
+            new,
+            github,
+            tag_pattern,
+            assets,
+            remote,
+            prerelease,
+        } => {
+            let repo = Repository::read(repo)?;
+            let pkg = if *new {
+                if !is_filename_safe(identifier) {
+                    return Err(InvalidPackageName(identifier.clone()).into());
+                }
+                repo.add_package(identifier)?
+            } else {
+                let Some(pkg) = repo.package(identifier)? else {
+                    return Err(PackageNotFound(identifier.into()).into());
+                };
+                pkg
+            };
+
+            let created = release::import_releases(
+                &pkg,
+                &release::ReleaseImportOptions {
+                    github,
+                    tag_pattern,
+                    assets: assets.as_deref(),
+                    remote: *remote,
+                    prerelease: *prerelease,
+                },
+            )?;
+            for version in created.iter() {
+                println!("Created version {}", version);
+            }
+            println!("Imported {} release(s)", created.len());
+        }
+        Commands::Mirror {
+            index,
+            output,
+            all_versions,
+        } => {
+            let text = String::from_utf8(import::fetch(index)?)?;
+            let model = index::IndexModel::parse(&text)?;
+            fs::create_dir_all(output)?;
+            let count = mirror::mirror(&model, output, *all_versions)?;
+            fs::write(output.join("index.xml"), text)?;
+            println!("Mirrored {} file(s) to {}", count, output.display());
+        }
+        Commands::Pack {
+            repo,
+            identifier,
+            version,
+            output,
+        } => {
+            let repo = Repository::read(repo)?;
+            let Some(pkg) = repo.package(identifier)? else {
+                return Err(PackageNotFound(identifier.into()).into());
+            };
+            let ver = match version {
+                Some(version) => pkg.version(version)?,
+                None => pkg.latest_version()?,
+            };
+            let Some(ver) = ver else {
+                return Err(match version {
+                    Some(version) => VersionNotFound(version.into()).into(),
+                    None => PackageHasNoVersions(identifier.into()).into(),
+                });
+            };
+
+            let output = match output {
+                Some(output) => output.clone(),
+                None => format!("{}-{}.zip", pkg.identifier(), ver.name()).into(),
+            };
+            let count = pack::pack(&repo, &pkg, &ver, &output)?;
+            println!("Wrote {} file(s) to {}", count, output.display());
+        }
+        Commands::Serve { repo, port } => {
+            serve::serve(repo, *port)?;
+        }
+        Commands::Doctor { repo } => {
+            let mut failures = 0;
+            for diagnostic in doctor::diagnose(repo) {
+                match diagnostic.outcome {
+                    doctor::Outcome::Ok(details) => {
+                        println!("[ok] {}: {}", diagnostic.name, details)
+                    }
+                    doctor::Outcome::Skipped(reason) => {
+                        println!("[skipped] {}: {}", diagnostic.name, reason)
+                    }
+                    doctor::Outcome::Failed { problem, remedy } => {
+                        failures += 1;
+                        println!("[failed] {}: {}", diagnostic.name, problem);
+                        println!("    fix: {}", remedy);
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(DoctorFailed(failures).into());
+            }
+        }
+        Commands::Sync {
+            repo,
+            workspace: workspace_path,
+            dry_run,
+        } => {
+            let repo = Repository::read(repo)?;
+            let workspace_path = match workspace_path {
+                Some(path) => path.clone(),
+                None => repo.path().join("workspace.toml"),
+            };
+            let targets = workspace::plan(&repo, &workspace_path)?;
+            let changed: Vec<&workspace::SyncTarget> =
+                targets.iter().filter(|x| x.changed == Some(true)).collect();
+            for target in targets.iter() {
+                let status = match (&target.latest, target.changed) {
+                    (_, Some(false)) => "unchanged".to_string(),
+                    (Some(latest), None) => format!(
+                        "latest version {} is hosted remotely and can't be compared, skipping",
+                        latest.name()
+                    ),
+                    (Some(latest), Some(true)) => format!(
+                        "changed since {}, will publish {}",
+                        latest.name(),
+                        target.next_version()?
+                    ),
+                    (None, _) => {
+                        format!("no versions yet, will publish {}", target.next_version()?)
+                    }
+                };
+                println!("{}: {}", target.pkg.identifier(), status);
+            }
+            if changed.is_empty() || *dry_run {
+                return Ok(());
+            }
+
+            let current_time = Utc::now().to_rfc3339();
+            let ver_config_text = templates::generate_version_config(
+                &VersionTemplateParams::default().time(&current_time),
+            );
+            for target in changed {
+                let version_name = target.next_version()?;
+                let ver_path = target.pkg.path().join(&version_name);
+                if ver_path.exists() {
+                    return Err(VersionAlreadyExists(ver_path.display().to_string()).into());
+                }
+                write_version(
+                    &target.pkg,
+                    &target.source,
+                    &ver_path,
+                    &ver_config_text,
+                    None,
+                )?;
+                println!(
+                    "Created version {} of {}",
+                    version_name,
+                    target.pkg.identifier()
+                );
+            }
+        }
+        Commands::Tui { repo } => {
+            tui::run(repo)?;
+        }
+        Commands::Migrate { repo, dry_run } => {
+            let files = migrate::plan(repo)?;
+            if files.is_empty() {
+                println!("All configuration files are up to date");
+                return Ok(());
+            }
+            println!("The following files will be upgraded:");
+            for file in files.iter() {
+                println!("  {}:", file.path.display());
+                for change in file.changes.iter() {
+                    println!("    {}", change);
+                }
+            }
+            if *dry_run {
+                return Ok(());
+            }
+            if !confirm::confirm(&format!("Upgrade {} file(s)?", files.len()), true)? {
+                return Err(Cancelled.into());
+            }
+            for file in files.iter() {
+                file.write()?;
+            }
+            println!("Upgraded {} file(s)", files.len());
+        }
+        Commands::List { repo, list } => {
+            let repo = Repository::read(repo)?;
+            match list {
+                ListType::Versions { identifier } => {
+                    let Some(pkg) = repo.package(identifier)? else {
+                        return Err(PackageNotFound(identifier.into()).into());
+                    };
+
+                    let mut versions = pkg.versions()?;
+                    versions.sort_by(|a, b| Version::compare_version_names(&a.name(), &b.name()));
+                    if versions.is_empty() {
+                        println!("Package {} has no versions", pkg.identifier());
+                    }
+                    for ver in versions.iter() {
+                        let source_count = ver.sources(&pkg).map(|x| x.len()).unwrap_or(0);
+                        println!(
+                            "{}\t{}\t{} {}{}",
+                            ver.name(),
+                            ver.time().to_rfc3339(),
+                            source_count,
+                            if source_count == 1 { "file" } else { "files" },
+                            if ver.yanked() { "\t(yanked)" } else { "" }
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Hooks { command } => match command {
+            HooksCommand::Install {
+                repo,
+                index,
+                hook,
+                check,
+                force,
+            } => {
+                let index = match index {
+                    Some(index) => index.clone(),
+                    None => Repository::read(repo)?.index_path(),
+                };
+                let path = hooks::install(repo, &index, *hook, *check, *force)?;
+                println!("Installed hook: {}", path.display());
+            }
+        },
+        Commands::Gc { repo, dry_run } => {
+            let repo = Repository::read(repo)?;
+            let problems = gc::find_problems(&repo)?;
+            if problems.is_empty() {
+                println!("No problems found");
+                return Ok(());
+            }
+            println!("Found {} problem(s):", problems.len());
+            for problem in problems.iter() {
+                println!("  {}", problem);
+            }
+            if *dry_run {
+                return Ok(());
+            }
+            for problem in problems.iter() {
+                let options: &[&str] = if problem.can_adopt() {
+                    &["Delete", "Adopt", "Skip"]
+                } else {
+                    &["Delete", "Skip"]
+                };
+                match confirm::choose(&problem.to_string(), options, "Skip")? {
+                    "Delete" => {
+                        problem.delete()?;
+                        println!("Deleted {}", problem.path().display());
+                    }
+                    "Adopt" => {
+                        if let Some(config_path) = problem.adopt()? {
+                            println!(
+                                "Created {}, please edit it before exporting",
+                                config_path.display()
+                            );
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
     }
 
     Ok(())
@@ -283,9 +2529,20 @@ fn main() -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+    use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+    use std::path::Path;
 
-    use super::*;
+    use super::paths_overlap;
+
+    #[test]
+    fn paths_overlap_01() {
+        let repo = Path::new("/home/me/repo");
+        assert!(paths_overlap(Path::new("/home/me/repo"), repo));
+        assert!(paths_overlap(Path::new("/home/me/repo/pkg/0.0.1"), repo));
+        assert!(paths_overlap(Path::new("/home/me"), repo));
+        assert!(!paths_overlap(Path::new("/home/me/repository"), repo));
+        assert!(!paths_overlap(Path::new("/home/me/src"), repo));
+    }
 
     #[test]
     fn test_01() {
@@ -0,0 +1,249 @@
+//! A JSON manifest of everything in the index, for tools that would otherwise have to parse the index XML.
+//! The same document is written by `export --format json` in place of the XML index.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    config::{ActionListSection, PackageType, Platform},
+    hash::hash_file,
+    repo::{Package, Repository, Source, Version},
+};
+
+/// The format of the exported index.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IndexFormat {
+    /// The ReaPack XML index
+    Xml,
+    /// The JSON manifest, see the README for its schema
+    Json,
+}
+
+impl IndexFormat {
+    /// The file name used when no output file is given.
+    pub(crate) fn default_filename(&self) -> &'static str {
+        match self {
+            IndexFormat::Xml => "index.xml",
+            IndexFormat::Json => "index.json",
+        }
+    }
+
+    /// The file extension of the format, without the dot.
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            IndexFormat::Xml => "xml",
+            IndexFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Manifest {
+    /// Name of the repository, the same as the `name` attribute of the index
+    pub(crate) name: String,
+    /// Packages sorted by category, then identifier
+    pub(crate) packages: Vec<PackageManifest>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct PackageManifest {
+    /// Name of the package folder
+    pub(crate) identifier: String,
+    /// Display name of the package
+    pub(crate) name: String,
+    pub(crate) category: String,
+    /// Package type as written in the index, e.g. "script" or "effect"
+    #[serde(rename = "type")]
+    pub(crate) pkg_type: String,
+    /// Short description, the same as the `desc` attribute in the index
+    pub(crate) description: String,
+    /// The package's README (or `@about` header) as Markdown, or None if it has neither
+    pub(crate) readme: Option<String>,
+    /// Website, donation and screenshot links, in the order they're listed in the index
+    pub(crate) links: Vec<LinkManifest>,
+    /// Deprecation notice, or None if the package isn't deprecated
+    pub(crate) deprecated: Option<String>,
+    /// Versions sorted from newest to oldest
+    pub(crate) versions: Vec<VersionManifest>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct LinkManifest {
+    /// "website", "donation" or "screenshot"
+    pub(crate) rel: String,
+    pub(crate) url: String,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct VersionManifest {
+    pub(crate) name: String,
+    pub(crate) author: String,
+    /// Release time in RFC 3339 format
+    pub(crate) time: DateTime<Utc>,
+    /// Changelog as plain text
+    pub(crate) changelog: Option<String>,
+    pub(crate) sources: Vec<SourceManifest>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct SourceManifest {
+    /// Path of the file in the repository
+    pub(crate) path: String,
+    /// The `file` attribute of the source in the index, relative to the package's category
+    pub(crate) file: String,
+    /// Path that ReaPack installs the file to, relative to the repository's folder in REAPER
+    pub(crate) target: String,
+    pub(crate) url: String,
+    /// Type of the source if it differs from the package type, the same as the `type` attribute in the index
+    #[serde(rename = "type")]
+    pub(crate) src_type: Option<PackageType>,
+    /// Platform of the source, or None if it's installed on every platform. Sources with several `arch` platforms
+    /// are listed once per platform, like in the index.
+    pub(crate) platform: Option<Platform>,
+    /// SHA-256 hash of the file, or None if the file isn't in the repository (e.g. archived versions)
+    pub(crate) sha256: Option<String>,
+    pub(crate) size: Option<u64>,
+    pub(crate) sections: Vec<ActionListSection>,
+}
+
+/// The path of the manifest written next to the given index.
+pub(crate) fn manifest_path(output_path: &Path) -> PathBuf {
+    output_path.with_file_name("manifest.json")
+}
+
+/// The path of the checksums file written next to the given index.
+pub(crate) fn checksums_path(output_path: &Path) -> PathBuf {
+    output_path.with_file_name("SHA256SUMS")
+}
+
+impl Manifest {
+    /// Build the manifest from the same packages and versions that are included in the index.
+    pub(crate) fn generate(repo: &Repository) -> Result<Self> {
+        let mut packages = vec![];
+        for pkg in repo.indexed_packages()? {
+            packages.push(PackageManifest::generate(repo, &pkg)?);
+        }
+        packages.sort_by(|a, b| (&a.category, &a.identifier).cmp(&(&b.category, &b.identifier)));
+        Ok(Self {
+            name: repo.identifier().to_string(),
+            packages,
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// A `SHA256SUMS` file of every source file in the manifest, in the format of `sha256sum`.
+    /// Paths are relative to the repository, sorted, and listed once even if several versions share a file.
+    /// Files that aren't in the repository (e.g. archived versions) are left out.
+    pub(crate) fn checksums(&self) -> String {
+        let mut lines: Vec<(&str, &str)> = self
+            .packages
+            .iter()
+            .flat_map(|pkg| pkg.versions.iter())
+            .flat_map(|ver| ver.sources.iter())
+            .filter_map(|src| Some((src.path.as_str(), src.sha256.as_deref()?)))
+            .collect();
+        lines.sort();
+        lines.dedup();
+        lines
+            .iter()
+            .map(|(path, hash)| format!("{hash}  {path}\n"))
+            .collect()
+    }
+}
+
+impl PackageManifest {
+    fn generate(repo: &Repository, pkg: &Package) -> Result<Self> {
+        let mut versions = pkg.indexed_versions(repo)?;
+        versions.sort_by(|a, b| Version::compare_version_names(&b.name(), &a.name()));
+        Ok(Self {
+            identifier: pkg.identifier().to_string(),
+            name: pkg.name().to_string(),
+            category: pkg.category().to_string(),
+            pkg_type: <&str>::from(&pkg.pkg_type()).to_string(),
+            description: pkg.description().to_string(),
+            readme: pkg.readme_markdown(repo)?,
+            links: pkg
+                .links()
+                .into_iter()
+                .map(|(rel, url)| LinkManifest {
+                    rel: rel.to_string(),
+                    url: url.to_string(),
+                })
+                .collect(),
+            deprecated: pkg.deprecation_notice(),
+            versions: versions
+                .iter()
+                .map(|ver| VersionManifest::generate(repo, pkg, ver))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl VersionManifest {
+    fn generate(repo: &Repository, pkg: &Package, ver: &Version) -> Result<Self> {
+        // skip the same files that are skipped in the index
+        let mut manifests = vec![];
+        for src in ver.indexed_sources(pkg)?.iter() {
+            for platform in src.indexed_platforms(pkg, ver)? {
+                manifests.push(SourceManifest::generate(repo, pkg, ver, src, platform)?);
+            }
+        }
+        Ok(Self {
+            name: ver.name().to_string(),
+            author: ver.resolved_author(repo, pkg).to_string(),
+            time: ver.time(),
+            changelog: ver.changelog_text(repo, pkg)?,
+            sources: manifests,
+        })
+    }
+}
+
+impl SourceManifest {
+    fn generate(
+        repo: &Repository,
+        pkg: &Package,
+        ver: &Version,
+        src: &Source,
+        platform: Option<Platform>,
+    ) -> Result<Self> {
+        let (sha256, size) = match hash_file(src.path()) {
+            Ok((hash, size)) => (Some(hash), Some(size)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (None, None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut sections: Vec<ActionListSection> =
+            src.sections(pkg, ver)?.iter().copied().collect();
+        sections.sort_by_key(|x| <&str>::from(x));
+        Ok(Self {
+            path: src
+                .path()
+                .strip_prefix(repo.path())
+                .unwrap_or(src.path())
+                .to_string_lossy()
+                .replace('\\', "/"),
+            file: src.output_relpath_from_category(pkg, ver).to_string(),
+            target: src.output_relpath(pkg, ver).to_string(),
+            url: src.url(repo, pkg, ver)?,
+            src_type: src.source_type(pkg, ver)?,
+            platform,
+            sha256,
+            size,
+            sections,
+        })
+    }
+}
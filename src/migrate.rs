@@ -0,0 +1,91 @@
+//! Upgrade configuration files written for older versions of this tool to the current schema.
+//!
+//! Files are edited with `toml_edit`, so comments and formatting are kept as they are.
+
+use std::{
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+use anyhow::Result;
+use toml_edit::DocumentMut;
+
+use crate::repo::{Package, Repository, Version};
+
+/// The kind of configuration file that a migration applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigKind {
+    Repository,
+    Package,
+    Version,
+}
+
+struct Migration {
+    kinds: &'static [ConfigKind],
+    description: &'static str,
+    /// Upgrade the document in-place, returning whether anything was changed
+    apply: fn(&mut DocumentMut) -> bool,
+}
+
+/// Every migration, in the order they are applied. The schema hasn't changed in an incompatible way yet, so
+/// there are none so far.
+const MIGRATIONS: &[Migration] = &[];
+
+/// A configuration file that needs to be upgraded.
+pub(crate) struct MigratedFile {
+    pub(crate) path: PathBuf,
+    /// Descriptions of the applied migrations
+    pub(crate) changes: Vec<&'static str>,
+    /// The upgraded contents of the file
+    text: String,
+}
+
+impl MigratedFile {
+    pub(crate) fn write(&self) -> Result<()> {
+        fs::write(&self.path, &self.text)?;
+        Ok(())
+    }
+}
+
+/// Apply every relevant migration to the given file. Returns None if the file is already up to date.
+fn migrate_file(path: PathBuf, kind: ConfigKind) -> Result<Option<MigratedFile>> {
+    let mut doc: DocumentMut = fs::read_to_string(&path)?.parse()?;
+    let changes: Vec<&'static str> = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.kinds.contains(&kind))
+        .filter(|migration| (migration.apply)(&mut doc))
+        .map(|migration| migration.description)
+        .collect();
+    if changes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(MigratedFile {
+        path,
+        changes,
+        text: doc.to_string(),
+    }))
+}
+
+/// Find every configuration file in the repository that needs to be upgraded, without writing anything.
+/// The repository isn't read, so configs that fail to parse with the current schema are still found.
+pub(crate) fn plan(repo_path: &Path) -> Result<Vec<MigratedFile>> {
+    let repo_path = path::absolute(repo_path)?;
+    let mut files = vec![];
+    if let Some(config_path) = Repository::find_config(&repo_path) {
+        files.push((config_path, ConfigKind::Repository));
+    }
+    for pkg_path in Package::discover_package_paths(&repo_path)? {
+        for ver_path in Version::discover_version_paths(&pkg_path)? {
+            files.push((ver_path.join(Version::CONFIG_FILENAME), ConfigKind::Version));
+        }
+        files.push((pkg_path.join(Package::CONFIG_FILENAME), ConfigKind::Package));
+    }
+
+    let mut result = vec![];
+    for (path, kind) in files {
+        if let Some(file) = migrate_file(path, kind)? {
+            result.push(file);
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,99 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use relative_path::{Component, RelativePath, RelativePathBuf};
+
+use crate::{
+    import::fetch,
+    index::{IndexModel, PackageEntry, VersionEntry},
+    repo::Version,
+};
+
+/// The path of a source within the mirror, from its category and `file` attribute.
+/// Returns None if the path would be outside of the mirror.
+fn mirror_relpath(category: &str, file: &str) -> Option<RelativePathBuf> {
+    let path = RelativePath::new(category).join(file).normalize();
+    if path.components().next() == Some(Component::ParentDir) || path.as_str().is_empty() {
+        return None;
+    }
+    Some(path)
+}
+
+/// The versions of a package to mirror, either all of them or only the latest.
+fn versions_to_mirror(pkg: &PackageEntry, all_versions: bool) -> Vec<(&String, &VersionEntry)> {
+    if all_versions {
+        return pkg.versions.iter().collect();
+    }
+    pkg.versions
+        .iter()
+        .max_by(|(a, _), (b, _)| Version::compare_version_names(a, b))
+        .into_iter()
+        .collect()
+}
+
+/// Download every source in the index into the output folder, at the path given by its `file` attribute relative
+/// to its category. With `all_versions`, each version is placed in a `{package}/{version}` folder, otherwise only
+/// the latest version of each package is downloaded.
+/// Returns the number of downloaded files.
+pub(crate) fn mirror(index: &IndexModel, output: &Path, all_versions: bool) -> Result<usize> {
+    let mut written: Vec<RelativePathBuf> = vec![];
+    for pkg in index.packages.values() {
+        for (version_name, ver) in versions_to_mirror(pkg, all_versions) {
+            let base = if all_versions {
+                RelativePathBuf::from(pkg.name.as_str()).join(version_name)
+            } else {
+                RelativePathBuf::new()
+            };
+            for src in ver.sources.values() {
+                let file = src.attributes.get("file").unwrap_or(&pkg.name);
+                let Some(relpath) = mirror_relpath(&pkg.category, file) else {
+                    log::warn!(
+                        "skipping source `{}` of {} {}, it would be written outside of the mirror",
+                        file,
+                        pkg.name,
+                        version_name
+                    );
+                    continue;
+                };
+                let relpath = base.join(relpath);
+                if written.contains(&relpath) {
+                    log::warn!(
+                        "skipping duplicate source `{}` of {} {}, only one platform can be mirrored",
+                        relpath,
+                        pkg.name,
+                        version_name
+                    );
+                    continue;
+                }
+
+                let path = relpath.to_logical_path(output);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, fetch(&src.url)?)?;
+                println!("Downloaded {}", relpath);
+                written.push(relpath);
+            }
+        }
+    }
+    Ok(written.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirror_relpath_01() {
+        assert_eq!(
+            mirror_relpath("Category", "../my-package/script.lua"),
+            Some("my-package/script.lua".into())
+        );
+        assert_eq!(
+            mirror_relpath("Category/Sub", "script.lua"),
+            Some("Category/Sub/script.lua".into())
+        );
+        assert_eq!(mirror_relpath("Category", "../../script.lua"), None);
+        assert_eq!(mirror_relpath("Category", ".."), None);
+    }
+}
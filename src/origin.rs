@@ -0,0 +1,100 @@
+//! Derive the URL pattern of a repository from the `origin` remote of its git repository.
+
+use std::{path::Path, process::Command};
+
+use relative_path::RelativePath;
+
+use crate::repo::url_encode_path;
+
+/// Split a git remote URL into its host and repository path, e.g. `git@github.com:user/repo.git` into
+/// `github.com` and `user/repo`.
+fn parse_remote_url(url: &str) -> Option<(&str, &str)> {
+    let url = url.trim();
+    let (host, path) = match url.split_once("://") {
+        // e.g. https://github.com/user/repo.git, ssh://git@github.com:22/user/repo.git
+        Some((_, rest)) => {
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit('@').next()?;
+            let host = host.split(':').next()?;
+            (host, path)
+        }
+        // e.g. git@github.com:user/repo.git
+        None => {
+            let (authority, path) = url.split_once(':')?;
+            (authority.rsplit('@').next()?, path)
+        }
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+    Some((host, path))
+}
+
+/// The URL pattern serving raw files from the given remote, for the supported hosts: GitHub, GitLab and Codeberg.
+/// `prefix` is the path of the repository folder within the git repository, e.g. `scripts/`.
+fn url_pattern_for_remote(url: &str, prefix: &str) -> Option<String> {
+    let (host, path) = parse_remote_url(url)?;
+    let base = match host.to_lowercase().as_str() {
+        "github.com" => format!("https://raw.githubusercontent.com/{path}"),
+        "gitlab.com" => format!("https://gitlab.com/{path}/-/raw"),
+        "codeberg.org" => format!("https://codeberg.org/{path}/raw/commit"),
+        _ => return None,
+    };
+    Some(format!("{base}/{{git_commit}}/{prefix}{{relpath}}"))
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+/// The URL pattern derived from the `origin` remote of the git repository containing `dir`, or None if there is no
+/// such remote, or it isn't hosted on a supported host.
+pub(crate) fn origin_url_pattern(dir: &Path) -> Option<String> {
+    let url = git_output(dir, &["remote", "get-url", "origin"])?;
+    let prefix = git_output(dir, &["rev-parse", "--show-prefix"])?;
+    let prefix = match prefix.trim_end_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", url_encode_path(RelativePath::new(prefix))),
+    };
+    url_pattern_for_remote(&url, &prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_pattern_for_remote_01() {
+        assert_eq!(
+            url_pattern_for_remote("git@github.com:user/repo.git", "").as_deref(),
+            Some("https://raw.githubusercontent.com/user/repo/{git_commit}/{relpath}")
+        );
+        assert_eq!(
+            url_pattern_for_remote("https://github.com/user/repo", "scripts/").as_deref(),
+            Some("https://raw.githubusercontent.com/user/repo/{git_commit}/scripts/{relpath}")
+        );
+        assert_eq!(
+            url_pattern_for_remote("ssh://git@gitlab.com:22/group/sub/repo.git", "").as_deref(),
+            Some("https://gitlab.com/group/sub/repo/-/raw/{git_commit}/{relpath}")
+        );
+        assert_eq!(
+            url_pattern_for_remote("https://codeberg.org/user/repo.git/", "").as_deref(),
+            Some("https://codeberg.org/user/repo/raw/commit/{git_commit}/{relpath}")
+        );
+        assert_eq!(
+            url_pattern_for_remote("https://example.com/user/repo", ""),
+            None
+        );
+        assert_eq!(url_pattern_for_remote("/home/user/repo", ""), None);
+    }
+}
@@ -0,0 +1,42 @@
+use std::{fs, io::Write, path::Path};
+
+use anyhow::Result;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::repo::{Package, Repository, Version};
+
+/// Write a zip archive of a version's sources, laid out the same way as they are installed by ReaPack.
+/// The changelog is included as `CHANGELOG.txt` in the root of the archive.
+/// Returns the number of files added to the archive.
+pub(crate) fn pack(
+    repo: &Repository,
+    pkg: &Package,
+    ver: &Version,
+    output: &Path,
+) -> Result<usize> {
+    let sources = ver.indexed_sources(pkg)?;
+
+    let mut zip = ZipWriter::new(fs::File::create(output)?);
+    let options = SimpleFileOptions::default();
+    let mut count = 0;
+    for src in sources.iter() {
+        if src.is_external() {
+            log::warn!(
+                "skipping {}, it is an external source",
+                src.output_relpath(pkg, ver)
+            );
+            continue;
+        }
+        zip.start_file(src.output_relpath(pkg, ver).as_str(), options)?;
+        zip.write_all(&fs::read(src.path())?)?;
+        count += 1;
+    }
+
+    if let Some(changelog) = ver.changelog_text(repo, pkg)? {
+        zip.start_file("CHANGELOG.txt", options)?;
+        zip.write_all(changelog.as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(count)
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+/// A file that a command would create, used to preview the command with `--dry-run`.
+pub(crate) struct PlannedFile {
+    /// Path of the file, relative to the root of the preview
+    pub(crate) path: PathBuf,
+    /// Rendered contents of the file, or None for files copied from elsewhere
+    pub(crate) content: Option<String>,
+}
+
+impl PlannedFile {
+    pub(crate) fn new(path: impl Into<PathBuf>, content: Option<String>) -> Self {
+        Self {
+            path: path.into(),
+            content,
+        }
+    }
+}
+
+/// Render the given files as an indented tree, with folders shown once above their contents.
+fn render_tree(files: &[PlannedFile]) -> Vec<(usize, String, Option<&PlannedFile>)> {
+    let mut files: Vec<&PlannedFile> = files.iter().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut lines = vec![];
+    let mut previous: Vec<String> = vec![];
+    for file in files {
+        let components: Vec<String> = file
+            .path
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let common = previous
+            .iter()
+            .zip(components.iter())
+            .take_while(|(a, b)| a == b)
+            .count()
+            // the file itself is never shared with the previous path
+            .min(components.len() - 1);
+        for (depth, name) in components.iter().enumerate().skip(common) {
+            if depth + 1 == components.len() {
+                lines.push((depth, name.clone(), Some(file)));
+            } else {
+                lines.push((depth, format!("{name}/"), None));
+            }
+        }
+        previous = components;
+    }
+    lines
+}
+
+/// Print the files that a command would create under `root`, optionally with their rendered contents.
+pub(crate) fn print_plan(root: &Path, files: &[PlannedFile], show_content: bool) {
+    println!("Would create the following files in {}:", root.display());
+    for (depth, name, file) in render_tree(files) {
+        let indent = "  ".repeat(depth + 1);
+        println!("{indent}{name}");
+        let Some(file) = file else {
+            continue;
+        };
+        if !show_content {
+            continue;
+        }
+        match &file.content {
+            Some(content) => {
+                for line in content.lines() {
+                    println!("{indent}  | {line}");
+                }
+            }
+            None => println!("{indent}  (copied)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_tree_01() {
+        let files = vec![
+            PlannedFile::new("pkg/0.0.1/version.toml", Some("".into())),
+            PlannedFile::new("pkg/package.toml", Some("".into())),
+            PlannedFile::new("pkg/0.0.1/script.lua", None),
+            PlannedFile::new("pkg/0.0.1/data/a.txt", None),
+        ];
+        let lines: Vec<(usize, String)> = render_tree(&files)
+            .into_iter()
+            .map(|(depth, name, _)| (depth, name))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (0, "pkg/".to_string()),
+                (1, "0.0.1/".to_string()),
+                (2, "data/".to_string()),
+                (3, "a.txt".to_string()),
+                (2, "script.lua".to_string()),
+                (2, "version.toml".to_string()),
+                (1, "package.toml".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Format strings for the `query` command, e.g. `{identifier}\t{latest}`.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::repo::{Package, Repository, Version};
+
+#[derive(Error, Debug)]
+#[error("unknown placeholder `{{{0}}}` in format, available placeholders are: {1}")]
+pub(crate) struct UnknownPlaceholder(String, String);
+
+#[derive(Error, Debug)]
+#[error("unclosed `{{` in format: `{0}`")]
+pub(crate) struct UnclosedPlaceholder(String);
+
+/// A part of a parsed format string.
+#[derive(Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Placeholder(String),
+}
+
+/// A format string with `{name}` placeholders.
+/// The escapes `\t`, `\n` and `\\` are supported, so that tabs can be passed easily from a shell.
+#[derive(Debug)]
+pub(crate) struct Format(Vec<Part>);
+
+impl Format {
+    pub(crate) fn parse(text: &str) -> Result<Self, UnclosedPlaceholder> {
+        let mut parts = vec![];
+        let mut current = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => current.push('\t'),
+                    Some('n') => current.push('\n'),
+                    Some(other) => current.push(other),
+                    None => current.push('\\'),
+                },
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(UnclosedPlaceholder(text.into())),
+                        }
+                    }
+                    if !current.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut current)));
+                    }
+                    parts.push(Part::Placeholder(name.trim().into()));
+                }
+                c => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            parts.push(Part::Text(current));
+        }
+        Ok(Self(parts))
+    }
+
+    /// Check that every placeholder is one of the given names.
+    pub(crate) fn check_placeholders(&self, names: &[&str]) -> Result<(), UnknownPlaceholder> {
+        for part in self.0.iter() {
+            if let Part::Placeholder(name) = part {
+                if !names.contains(&name.as_str()) {
+                    return Err(UnknownPlaceholder(name.clone(), names.join(", ")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn render(&self, values: &BTreeMap<&str, String>) -> String {
+        let mut result = String::new();
+        for part in self.0.iter() {
+            match part {
+                Part::Text(text) => result.push_str(text),
+                Part::Placeholder(name) => {
+                    result.push_str(values.get(name.as_str()).map_or("", |x| x.as_str()))
+                }
+            }
+        }
+        result
+    }
+}
+
+pub(crate) const PACKAGE_PLACEHOLDERS: &[&str] = &[
+    "identifier",
+    "name",
+    "category",
+    "type",
+    "author",
+    "latest",
+    "versions",
+    "deprecated",
+    "path",
+];
+
+pub(crate) const VERSION_PLACEHOLDERS: &[&str] = &[
+    "identifier",
+    "name",
+    "category",
+    "type",
+    "author",
+    "latest",
+    "versions",
+    "deprecated",
+    "path",
+    "version",
+    "time",
+    "yanked",
+    "files",
+];
+
+/// The values of [PACKAGE_PLACEHOLDERS] for a package.
+pub(crate) fn package_values<'a>(
+    repo: &Repository,
+    pkg: &Package,
+) -> Result<BTreeMap<&'a str, String>> {
+    let versions = pkg.versions()?;
+    let latest = pkg.latest_released_version()?;
+    Ok(BTreeMap::from([
+        ("identifier", pkg.identifier().to_string()),
+        ("name", pkg.name().to_string()),
+        ("category", pkg.category().to_string()),
+        ("type", <&str>::from(&pkg.pkg_type()).to_string()),
+        ("author", pkg.author().unwrap_or(repo.author()).to_string()),
+        (
+            "latest",
+            latest.map(|ver| ver.name().to_string()).unwrap_or_default(),
+        ),
+        ("versions", versions.len().to_string()),
+        ("deprecated", pkg.deprecation_notice().is_some().to_string()),
+        ("path", pkg.path().display().to_string()),
+    ]))
+}
+
+/// The values of [VERSION_PLACEHOLDERS] for a version, given the values of its package.
+/// The `author` placeholder is replaced by the version's author.
+pub(crate) fn version_values<'a>(
+    repo: &Repository,
+    pkg: &Package,
+    ver: &Version,
+    package_values: &BTreeMap<&'a str, String>,
+) -> BTreeMap<&'a str, String> {
+    let mut values = package_values.clone();
+    values.insert("author", ver.resolved_author(repo, pkg).to_string());
+    values.insert("path", ver.path().display().to_string());
+    values.insert("version", ver.name().to_string());
+    values.insert("time", ver.time().to_rfc3339());
+    values.insert("yanked", ver.yanked().to_string());
+    values.insert(
+        "files",
+        ver.sources(pkg).map(|x| x.len()).unwrap_or(0).to_string(),
+    );
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_01() {
+        let format = Format::parse(r"{identifier}\t{ latest }\\n{").unwrap_err();
+        assert_eq!(format.0, r"{identifier}\t{ latest }\\n{");
+
+        let format = Format::parse(r"{identifier}\t{ latest }\\n").unwrap();
+        assert_eq!(
+            format.0,
+            vec![
+                Part::Placeholder("identifier".into()),
+                Part::Text("\t".into()),
+                Part::Placeholder("latest".into()),
+                Part::Text("\\n".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_01() {
+        let format = Format::parse(r"{identifier}\t{latest}").unwrap();
+        assert!(format.check_placeholders(PACKAGE_PLACEHOLDERS).is_ok());
+        assert!(format.check_placeholders(&["identifier"]).is_err());
+        let values = BTreeMap::from([
+            ("identifier", "my-package".to_string()),
+            ("latest", "1.0".to_string()),
+        ]);
+        assert_eq!(format.render(&values), "my-package\t1.0");
+    }
+}
@@ -0,0 +1,195 @@
+//! Import versions of a package from the releases of a GitHub repository.
+
+use std::{fs, io::Read};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use globset::{Glob, GlobMatcher};
+use relative_path::RelativePathBuf;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    config::RemoteConfig,
+    import::{fetch, write_version_config, DownloadFailed},
+    repo::{url_encode_path, Package, Version},
+};
+
+#[derive(Error, Debug)]
+#[error("invalid GitHub repository `{0}`, expected `owner/name`")]
+pub(crate) struct InvalidGithubRepository(String);
+
+#[derive(Error, Debug)]
+#[error("tag pattern must contain `{{version}}`: `{0}`")]
+pub(crate) struct InvalidTagPattern(String);
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    draft: bool,
+    prerelease: bool,
+    published_at: Option<DateTime<Utc>>,
+    body: Option<String>,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Options for [import_releases].
+pub(crate) struct ReleaseImportOptions<'a> {
+    /// The GitHub repository, as `owner/name`
+    pub(crate) github: &'a str,
+    /// Pattern of the release tags, with `{version}` in place of the version name, e.g. `v{version}`
+    pub(crate) tag_pattern: &'a str,
+    /// Only import assets matching this glob pattern
+    pub(crate) assets: Option<&'a str>,
+    /// Link to the assets on GitHub instead of downloading them into the repository
+    pub(crate) remote: bool,
+    /// Also import pre-releases
+    pub(crate) prerelease: bool,
+}
+
+/// Find the version name in a tag, given a pattern like `v{version}`.
+fn version_from_tag<'a>(tag: &'a str, pattern: &str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once("{version}")?;
+    let version = tag.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    (!version.is_empty()).then_some(version)
+}
+
+/// Fetch all releases of a GitHub repository, newest first.
+/// The `GITHUB_TOKEN` environment variable is used for authentication if set.
+fn fetch_releases(github: &str) -> Result<Vec<Release>> {
+    let token = std::env::var("GITHUB_TOKEN").ok();
+    let mut releases = vec![];
+    for page in 1.. {
+        let url =
+            format!("https://api.github.com/repos/{github}/releases?per_page=100&page={page}");
+        let mut request = ureq::get(&url).set("Accept", "application/vnd.github+json");
+        if let Some(token) = &token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        let response = request
+            .call()
+            .map_err(|err| DownloadFailed(url.clone(), err.to_string()))?;
+        let mut text = String::new();
+        response.into_reader().read_to_string(&mut text)?;
+        let page: Vec<Release> = serde_json::from_str(&text)?;
+        if page.is_empty() {
+            break;
+        }
+        releases.extend(page);
+    }
+    Ok(releases)
+}
+
+/// Create a version of the package for every matching release that isn't in the package yet.
+/// Returns the names of the created versions.
+pub(crate) fn import_releases(
+    pkg: &Package,
+    options: &ReleaseImportOptions,
+) -> Result<Vec<String>> {
+    if options.github.split('/').filter(|x| !x.is_empty()).count() != 2 {
+        return Err(InvalidGithubRepository(options.github.into()).into());
+    }
+    if !options.tag_pattern.contains("{version}") {
+        return Err(InvalidTagPattern(options.tag_pattern.into()).into());
+    }
+    let assets: Option<GlobMatcher> = match options.assets {
+        Some(pattern) => Some(Glob::new(pattern)?.compile_matcher()),
+        None => None,
+    };
+
+    let existing: Vec<String> = pkg
+        .versions()?
+        .iter()
+        .map(|ver| ver.name().to_string())
+        .collect();
+
+    let mut created = vec![];
+    for release in fetch_releases(options.github)? {
+        if release.draft || (release.prerelease && !options.prerelease) {
+            continue;
+        }
+        let Some(version_name) = version_from_tag(&release.tag_name, options.tag_pattern) else {
+            continue;
+        };
+        if existing.iter().any(|x| x == version_name) {
+            continue;
+        }
+        if !crate::is_filename_safe(version_name) {
+            log::warn!(
+                "skipping release {}, `{}` can't be used as a version name",
+                release.tag_name,
+                version_name
+            );
+            continue;
+        }
+        let release_assets: Vec<&Asset> = release
+            .assets
+            .iter()
+            .filter(|asset| assets.as_ref().is_none_or(|x| x.is_match(&asset.name)))
+            .collect();
+        if release_assets.is_empty() {
+            log::warn!(
+                "skipping release {}, it has no matching assets",
+                release.tag_name
+            );
+            continue;
+        }
+
+        let ver_path = pkg.path().join(version_name);
+        fs::create_dir_all(&ver_path)?;
+        if !options.remote {
+            for asset in release_assets.iter() {
+                fs::write(
+                    ver_path.join(&asset.name),
+                    fetch(&asset.browser_download_url)?,
+                )?;
+            }
+        }
+
+        let time = release.published_at.unwrap_or_else(Utc::now).to_rfc3339();
+        write_version_config(&ver_path, &time, None, None)?;
+        if let Some(body) = release.body.as_ref().filter(|x| !x.trim().is_empty()) {
+            fs::write(ver_path.join("CHANGELOG.txt"), body.replace("\r\n", "\n"))?;
+        }
+
+        if options.remote {
+            let tag = RelativePathBuf::from(release.tag_name.as_str());
+            Version::read(&ver_path)?.set_remote(RemoteConfig {
+                url: format!(
+                    "https://github.com/{}/releases/download/{}",
+                    options.github,
+                    url_encode_path(&tag)
+                ),
+                files: release_assets
+                    .iter()
+                    .map(|asset| RelativePathBuf::from(asset.name.as_str()))
+                    .collect(),
+            })?;
+        }
+
+        created.push(version_name.to_string());
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_from_tag_01() {
+        assert_eq!(version_from_tag("v1.2.0", "v{version}"), Some("1.2.0"));
+        assert_eq!(version_from_tag("1.2.0", "v{version}"), None);
+        assert_eq!(
+            version_from_tag("my-plugin-1.2.0-final", "my-plugin-{version}-final"),
+            Some("1.2.0")
+        );
+        assert_eq!(version_from_tag("v", "v{version}"), None);
+    }
+}
@@ -3,12 +3,12 @@ use chrono::DateTime;
 use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use itertools::Itertools;
 use leon::{Template, Values};
-use log::{error, warn};
+use log::error;
 use once_cell::sync::OnceCell;
 use relative_path::{PathExt, RelativePath, RelativePathBuf};
 use std::{
-    borrow::Cow,
-    collections::{HashMap, HashSet},
+    borrow::{Borrow, Cow},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::{self},
     path::{self, Path, PathBuf},
 };
@@ -16,15 +16,32 @@ use thiserror::Error;
 use xml_builder::{XMLBuilder, XMLElement, XMLVersion};
 
 use crate::{
-    config::{ActionListSection, PackageConfig, PackageType, RepositoryConfig, VersionConfig},
+    config::{
+        ActionListSection, Deprecation, ExternalSource, PackageConfig, PackageType, Platform,
+        RemoteConfig, RepositoryConfig, StorageConfig, VersionConfig, XmlVersion,
+    },
+    hash::{hash_file, sha256_hex},
+    headers::Headers,
+    manifest::IndexFormat,
+    state::ExportState,
     templates::{self, PackageTemplateParams},
+    version::{self, UnknownVersionFormat},
+    warnings,
 };
 
 type Entrypoints = HashMap<ActionListSection, GlobSet>;
 
+/// Files in version folders that aren't sources, unless the package or version sets its own `exclude` patterns.
+/// These are files created by file managers and editors.
+const DEFAULT_EXCLUDE: &[&str] = &["**/.DS_Store", "**/._*", "**/Thumbs.db", "**/desktop.ini"];
+
+/// Files in version folders that are never sources, whatever the `exclude` patterns are. This is the README of the
+/// version, which is used as the package description instead.
+const ALWAYS_EXCLUDE: &[&str] = &["README.md", "README.rtf"];
+
 #[derive(Error, Debug)]
-#[error("the given path is not a repository (does not have a repository.toml file): {0}")]
-pub(crate) struct NotARepository(PathBuf);
+#[error("the given path is not a repository (none of these config files were found: {1}): {0}")]
+pub(crate) struct NotARepository(PathBuf, String);
 
 #[derive(Error, Debug)]
 #[error("no sources found in package version: `{0}`")]
@@ -43,13 +60,37 @@ pub(crate) struct NoEntrypointsDefinedForScriptPackage(PathBuf);
 pub(crate) struct NoEntrypointsFoundForScriptPackage(PathBuf);
 
 #[derive(Error, Debug)]
-#[error("pandoc is required for converting Markdown files to RTF, please specify the path to the pandoc executable with --pandoc")]
+#[error("source matches platform patterns with different platforms ({1} and {2}): `{0}`")]
+pub(crate) struct ConflictingPlatforms(PathBuf, String, String);
+
+#[derive(Error, Debug)]
+#[error("source matches type patterns with different types ({1} and {2}): `{0}`")]
+pub(crate) struct ConflictingSourceTypes(PathBuf, String, String);
+
+#[derive(Error, Debug)]
+#[error("target `{1}` must be a path inside the package folder: `{0}`")]
+pub(crate) struct InvalidTarget(PathBuf, RelativePathBuf);
+
+#[derive(Error, Debug)]
+#[error("file `{1}` in entrypoint_files is not a source of the version: `{0}`")]
+pub(crate) struct EntrypointFileNotFound(PathBuf, RelativePathBuf);
+
+#[derive(Error, Debug)]
+#[error("pandoc is required for converting Markdown files to RTF, please specify the path to the pandoc executable with --pandoc, the PANDOC_PATH environment variable, or `pandoc_path` in repository.toml")]
 pub(crate) struct PandocNotInstalled;
 
 #[derive(Error, Debug)]
 #[error("pandoc returned unexpected output")]
 pub(crate) struct PandocOutputError;
 
+#[derive(Error, Debug)]
+#[error("the configured pandoc executable does not exist: {0}")]
+pub(crate) struct PandocPathNotFound(PathBuf);
+
+#[derive(Error, Debug)]
+#[error("pandoc failed ({0}): {1}")]
+pub(crate) struct PandocFailed(PathBuf, String);
+
 #[derive(Error, Debug)]
 #[error("package already exists: `{0}`")]
 pub(crate) struct PackageAlreadyExists(PathBuf);
@@ -59,13 +100,51 @@ pub(crate) struct PackageAlreadyExists(PathBuf);
 pub(crate) struct PathIsAFile(PathBuf);
 
 #[derive(Error, Debug)]
-#[error("unable to parse this version string, please specify the new version manually: {0}")]
-pub(crate) struct UnknownVersionFormat(String);
+#[error("package identifiers `{0}` and `{1}` only differ by case, they would be the same folder on Windows and macOS")]
+pub(crate) struct DuplicatePackageIdentifier(String, String);
+
+#[derive(Error, Debug)]
+#[error("versions `{1}` and `{2}` only differ by case, they would be the same folder on Windows and macOS: `{0}`")]
+pub(crate) struct DuplicateVersionName(PathBuf, String, String);
+
+/// Replace placeholders like `{identifier}` in the text with the given values.
+/// Unknown placeholders and other braces are kept as-is. Values are inserted as they are, placeholders in them
+/// aren't replaced.
+fn render_placeholders(text: &str, values: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let placeholder = values.iter().find_map(|(key, value)| {
+            rest[1..]
+                .strip_prefix(key)
+                .and_then(|x| x.strip_prefix('}'))
+                .map(|after| (*value, after))
+        });
+        match placeholder {
+            Some((value, after)) => {
+                result.push_str(value);
+                rest = after;
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
 
 /// Try to read an RTF file at the given path.
-/// If no RTF file is found, read and convert a Markdown file to RTF.
+/// If no RTF file is found, read and convert a Markdown file to RTF, replacing the given placeholders.
 /// If no Markdown file is found, return None.
-fn read_rtf_or_md_file(path: &Path) -> Result<Option<String>> {
+fn read_rtf_or_md_file(
+    path: &Path,
+    placeholders: &[(&str, &str)],
+    repo: &Repository,
+) -> Result<Option<String>> {
     let rtf_path = path.with_extension("rtf");
     if rtf_path.exists() {
         return Ok(Some(fs::read_to_string(rtf_path)?));
@@ -73,27 +152,168 @@ fn read_rtf_or_md_file(path: &Path) -> Result<Option<String>> {
 
     let md_path = path.with_extension("md");
     if md_path.exists() {
-        let mut pandoc = pandoc::new();
-        // TODO: Allow overriding pandoc path
-        // pandoc.add_pandoc_path_hint(custom_path);
-        pandoc.add_input(&md_path);
-        pandoc.add_option(pandoc::PandocOption::Standalone);
-        pandoc.set_output(pandoc::OutputKind::Pipe);
-        pandoc.set_output_format(pandoc::OutputFormat::Rtf, vec![]);
-        // pandoc::PandocError::PandocNotFound
-        let output = pandoc.execute().map_err(|e| match e {
-            pandoc::PandocError::PandocNotFound => anyhow::Error::from(PandocNotInstalled),
-            e => e.into(),
-        })?;
-        let pandoc::PandocOutput::ToBuffer(output) = output else {
-            return Err(PandocOutputError.into());
-        };
-        return Ok(Some(output));
+        let text = render_placeholders(&fs::read_to_string(&md_path)?, placeholders);
+        return markdown_to_rtf_or_plain_text(text, &md_path, repo).map(Some);
     }
 
     Ok(None)
 }
 
+/// Convert Markdown text read from the given file with `convert`. If pandoc is not installed, the text is formatted
+/// with `plain_text` instead.
+pub(crate) fn convert_markdown_or_plain_text(
+    text: String,
+    path: &Path,
+    convert: impl FnOnce(String) -> Result<String>,
+    plain_text: impl FnOnce(&str) -> String,
+) -> Result<String> {
+    match convert(text.clone()) {
+        Ok(result) => Ok(result),
+        Err(err) if err.is::<PandocNotInstalled>() => {
+            warnings::warn(
+                "pandoc-not-installed",
+                path,
+                "pandoc is not installed, the README is included as plain text. Install pandoc to format it"
+                    .into(),
+            );
+            Ok(plain_text(&text))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Convert Markdown text read from the given file to RTF. If pandoc is not installed, the text is included as plain
+/// text instead.
+fn markdown_to_rtf_or_plain_text(text: String, path: &Path, repo: &Repository) -> Result<String> {
+    convert_markdown_or_plain_text(
+        text,
+        path,
+        |text| repo.markdown_to_rtf(text),
+        plain_text_rtf,
+    )
+}
+
+/// The pandoc executable set with `--pandoc`, which overrides the configured path.
+static PANDOC_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Use the given pandoc executable for the rest of the program, instead of the configured one.
+pub(crate) fn set_pandoc_path(path: PathBuf) {
+    let _ = PANDOC_PATH.set(path);
+}
+
+/// The pandoc executable at the configured path, which is either the executable or the folder containing it.
+pub(crate) fn pandoc_executable(pandoc_path: &Path) -> PathBuf {
+    if pandoc_path.is_dir() {
+        pandoc_path.join(format!("pandoc{}", std::env::consts::EXE_SUFFIX))
+    } else {
+        pandoc_path.to_path_buf()
+    }
+}
+
+/// Run the given pandoc executable, converting Markdown text to another format.
+fn run_pandoc(
+    executable: &Path,
+    text: &str,
+    format: pandoc::OutputFormat,
+    standalone: bool,
+) -> Result<String> {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+    if !executable.is_file() {
+        return Err(PandocPathNotFound(executable.into()).into());
+    }
+    let mut command = Command::new(executable);
+    command.args(["--from", "markdown", "--to", &format.to_string()]);
+    if standalone {
+        command.arg("--standalone");
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // pandoc reads all of its input before writing anything, so this can't deadlock
+    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PandocFailed(executable.into(), stderr.trim().to_string()).into());
+    }
+    String::from_utf8(output.stdout).map_err(|_| PandocOutputError.into())
+}
+
+/// Convert Markdown text to another format using pandoc.
+/// With `standalone`, a complete document is generated rather than a fragment.
+/// `pandoc_path` is the pandoc executable or the folder containing it, which must exist. Otherwise pandoc is searched
+/// for in `PATH`.
+pub(crate) fn convert_markdown(
+    text: String,
+    format: pandoc::OutputFormat,
+    standalone: bool,
+    pandoc_path: Option<&Path>,
+) -> Result<String> {
+    // the pandoc crate can only search folders for an executable named `pandoc`, so run the configured one directly
+    if let Some(path) = pandoc_path {
+        return run_pandoc(&pandoc_executable(path), &text, format, standalone);
+    }
+    let mut pandoc = pandoc::new();
+    pandoc.set_input(pandoc::InputKind::Pipe(text));
+    pandoc.set_input_format(pandoc::InputFormat::Markdown, vec![]);
+    if standalone {
+        pandoc.add_option(pandoc::PandocOption::Standalone);
+    }
+    pandoc.set_output(pandoc::OutputKind::Pipe);
+    pandoc.set_output_format(format, vec![]);
+    // pandoc::PandocError::PandocNotFound
+    let output = pandoc.execute().map_err(|e| match e {
+        pandoc::PandocError::PandocNotFound => anyhow::Error::from(PandocNotInstalled),
+        e => e.into(),
+    })?;
+    let pandoc::PandocOutput::ToBuffer(output) = output else {
+        return Err(PandocOutputError.into());
+    };
+    Ok(output)
+}
+
+/// Escape text to be inserted into an RTF document.
+fn rtf_escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\line "),
+            c if c.is_ascii() => result.push(c),
+            // RTF uses signed 16-bit UTF-16 code units, with `?` as the fallback for old readers
+            c => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    result.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+    result
+}
+
+/// An RTF document containing the text as-is, for when it can't be converted by pandoc.
+fn plain_text_rtf(text: &str) -> String {
+    format!("{{\\rtf1\\ansi\n{}\n}}\n", rtf_escape(text.trim_end()))
+}
+
+/// Add a bold paragraph at the end of an RTF document, before its closing brace.
+fn rtf_append_paragraph(rtf: &str, text: &str) -> String {
+    let paragraph = format!("{{\\pard \\b {}\\b0\\par}}\n", rtf_escape(text));
+    match rtf.trim_end().strip_suffix('}') {
+        Some(body) => format!("{body}{paragraph}}}"),
+        None => format!("{rtf}{paragraph}"),
+    }
+}
+
 fn read_txt_file(path: &Path) -> Result<Option<String>> {
     if path.exists() {
         Ok(Some(fs::read_to_string(path)?))
@@ -102,7 +322,86 @@ fn read_txt_file(path: &Path) -> Result<Option<String>> {
     }
 }
 
-fn cdata(text: &str) -> String {
+/// Find paths that are equal to an earlier path, ignoring case.
+/// Returns pairs of `(duplicate index, original index)`.
+fn find_collisions(paths: &[RelativePathBuf]) -> Vec<(usize, usize)> {
+    let paths: Vec<RelativePathBuf> = paths.iter().map(|x| x.normalize()).collect();
+    find_case_duplicates(&paths)
+}
+
+/// Find names that are equal to an earlier name when ignoring case, which would be the same file on
+/// case-insensitive filesystems. Returns pairs of `(duplicate index, original index)`.
+pub(crate) fn find_case_duplicates(names: &[impl AsRef<str>]) -> Vec<(usize, usize)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut result = vec![];
+    for (i, name) in names.iter().enumerate() {
+        let key = name.as_ref().to_lowercase();
+        match seen.get(&key) {
+            Some(original) => result.push((i, *original)),
+            None => {
+                seen.insert(key, i);
+            }
+        }
+    }
+    result
+}
+
+/// The value of a source's `main` attribute for the given sections.
+/// With `legacy`, sources registered only in the main section use `main="true"`, which older ReaPack clients
+/// understand. Other sections have no legacy form, so they are always listed.
+fn main_attribute(sections: &HashSet<ActionListSection>, legacy: bool) -> String {
+    if legacy && sections.len() == 1 && sections.contains(&ActionListSection::Main) {
+        return "true".into();
+    }
+    sections.iter().map(Into::<&str>::into).sorted().join(" ")
+}
+
+/// Encode a hex SHA-256 digest as a hex multihash, the format of a source's `hash` attribute.
+/// The digest is prefixed with the multihash code of SHA-256 (0x12) and the digest length in bytes (0x20).
+fn sha256_multihash(hex_digest: &str) -> String {
+    format!("1220{hex_digest}")
+}
+
+/// The `<metadata>` element of the index or a package, with a description and `<link>` elements given as
+/// `(rel, url)` pairs. Returns None if there is nothing to add.
+fn metadata_element(desc: Option<&str>, links: &[(&str, &str)]) -> Option<XMLElement> {
+    if desc.is_none() && links.is_empty() {
+        return None;
+    }
+    let mut metadata = XMLElement::new("metadata");
+    if let Some(desc) = desc {
+        let mut description = XMLElement::new("description");
+        description.add_text(cdata(desc)).unwrap();
+        metadata.add_child(description).unwrap();
+    }
+    for (rel, url) in links {
+        let mut link = XMLElement::new("link");
+        link.add_attribute("rel", rel);
+        link.add_text(cdata(url)).unwrap();
+        metadata.add_child(link).unwrap();
+    }
+    Some(metadata)
+}
+
+/// Edit a TOML config file in-place, preserving its comments and formatting.
+pub(crate) fn update_toml_file(
+    path: &Path,
+    update: impl FnOnce(&mut toml_edit::DocumentMut),
+) -> Result<()> {
+    let mut doc: toml_edit::DocumentMut = fs::read_to_string(path)?.parse()?;
+    update(&mut doc);
+    fs::write(path, doc.to_string())?;
+    Ok(())
+}
+
+/// Escape text for use in an XML element, for text that is usually plain, like URLs.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub(crate) fn cdata(text: &str) -> String {
     let mut result = String::with_capacity(text.len() + 12);
     result.push_str("<![CDATA[");
 
@@ -121,17 +420,26 @@ fn cdata(text: &str) -> String {
     result
 }
 
-fn url_encode_path(path: &RelativePath) -> String {
-    use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+/// Characters that are left as-is when encoding paths for URLs.
+const PATH_FRAGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'.')
+    .remove(b'-')
+    .remove(b'_');
 
+pub(crate) fn url_encode_path(path: &RelativePath) -> String {
     let input = path.normalize().to_string();
+    percent_encoding::utf8_percent_encode(&input, PATH_FRAGMENT).to_string()
+}
 
-    const FRAGMENT: &AsciiSet = &NON_ALPHANUMERIC
-        .remove(b'/')
-        .remove(b'.')
-        .remove(b'-')
-        .remove(b'_');
-    utf8_percent_encode(&input, FRAGMENT).to_string()
+/// A URL pattern pointing at files in the given folder on this machine, e.g. `file:///home/me/repo/{relpath}`.
+/// `dir` must be an absolute path.
+pub(crate) fn local_url_pattern(dir: &Path) -> String {
+    // keep the colon of Windows drive letters, e.g. `C:/repo`
+    const FRAGMENT: &percent_encoding::AsciiSet = &PATH_FRAGMENT.remove(b':');
+    let path = dir.to_string_lossy().replace('\\', "/");
+    let encoded = percent_encoding::utf8_percent_encode(path.trim_matches('/'), FRAGMENT);
+    format!("file:///{encoded}/{{relpath}}")
 }
 
 #[derive(Error, Debug)]
@@ -140,6 +448,16 @@ pub(crate) enum GitCommitError {
     FailedToLaunchGit,
     #[error("failed to get commit hash in the given path: {0}")]
     FailedToGetGitHash(PathBuf),
+    #[error("failed to get git status in the given path: {0}")]
+    UnableToGetGitStatus(PathBuf),
+    #[error("file has not been committed yet: {0}")]
+    FileNotCommitted(PathBuf),
+    #[error("not on a git branch in the given path, check out a branch first: {0}")]
+    NoGitBranch(PathBuf),
+    #[error("no git tag found in the given path, create one with `git tag` or pass one with `export --tag`: {0}")]
+    NoGitTag(PathBuf),
+    #[error("failed to get git log in the given path: {0}")]
+    FailedToGetGitLog(PathBuf),
 }
 
 fn get_git_commit(dir: &Path) -> Result<String, GitCommitError> {
@@ -160,6 +478,169 @@ fn get_git_commit(dir: &Path) -> Result<String, GitCommitError> {
     Ok(hash)
 }
 
+/// The name of the branch that is checked out.
+fn get_git_branch(dir: &Path) -> Result<String, GitCommitError> {
+    use std::process::Command;
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let branch = stdout.trim().to_string();
+    // a detached HEAD is reported as `HEAD`
+    if !output.status.success() || branch == "HEAD" {
+        return Err(GitCommitError::NoGitBranch(dir.into()));
+    }
+
+    Ok(branch)
+}
+
+/// The most recent tag reachable from the current commit.
+fn get_git_tag(dir: &Path) -> Result<String, GitCommitError> {
+    use std::process::Command;
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+
+    if !output.status.success() {
+        return Err(GitCommitError::NoGitTag(dir.into()));
+    }
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let tag = stdout.trim().to_string();
+
+    Ok(tag)
+}
+
+/// Check whether the git work tree has uncommitted changes.
+fn get_git_dirty(dir: &Path) -> Result<bool, GitCommitError> {
+    use std::process::Command;
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["status", "--porcelain"])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+
+    if !output.status.success() {
+        return Err(GitCommitError::UnableToGetGitStatus(dir.into()));
+    }
+
+    Ok(!output.stdout.is_empty())
+}
+
+/// Check whether the given file exists in a commit. `relpath` is relative to `dir`, which may be a subfolder of the
+/// git repository.
+fn git_file_exists_in_commit(
+    dir: &Path,
+    commit: &str,
+    relpath: &RelativePath,
+) -> Result<bool, GitCommitError> {
+    use std::process::Command;
+    let status = Command::new("git")
+        .current_dir(dir)
+        // `./` makes git resolve the path from the current folder, instead of the root of the git repository
+        .args(["cat-file", "-e", &format!("{commit}:./{relpath}")])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?
+        .status;
+
+    Ok(status.success())
+}
+
+/// Parse the output of `git log --format=commit:%H --name-only`, into the newest commit of each file.
+fn parse_file_commits(log: &str) -> HashMap<RelativePathBuf, String> {
+    let mut result = HashMap::new();
+    let mut commit = None;
+    for line in log.lines() {
+        if let Some(hash) = line.strip_prefix("commit:") {
+            commit = Some(hash.trim());
+        } else if let Some(commit) = commit.filter(|_| !line.is_empty()) {
+            // the log is sorted newest first, so the first commit of each file is the newest
+            result
+                .entry(RelativePathBuf::from(line))
+                .or_insert_with(|| commit.to_string());
+        }
+    }
+    result
+}
+
+/// The commit that last changed each file in `dir`, by their path relative to `dir`.
+/// This runs git once for the whole folder, which is much faster than running it once per file.
+fn get_git_file_commits(dir: &Path) -> Result<HashMap<RelativePathBuf, String>, GitCommitError> {
+    use std::process::Command;
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--format=commit:%H",
+            "--name-only",
+            "--relative",
+            "--",
+            ".",
+        ])
+        .output()
+        .map_err(|_| GitCommitError::FailedToLaunchGit)?;
+
+    if !output.status.success() {
+        return Err(GitCommitError::FailedToGetGitHash(dir.into()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_file_commits(&stdout))
+}
+
+/// Find all subfolders of `dir` containing a file named `config_filename`.
+fn discover_config_dirs(dir: &Path, config_filename: &str) -> Result<Vec<PathBuf>> {
+    let mut result = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                warnings::warn(
+                    "unreadable-entry",
+                    dir,
+                    format!("failed to read entry {}", err),
+                );
+                continue;
+            }
+        };
+        let path = entry.path();
+
+        let is_dir = match path.metadata() {
+            Ok(metadata) => metadata.is_dir(),
+            Err(err) => {
+                warnings::warn(
+                    "unreadable-entry",
+                    &path,
+                    format!(
+                        "failed to get metadata for entry {} due to {}",
+                        path.display(),
+                        err
+                    ),
+                );
+                continue;
+            }
+        };
+        if !is_dir {
+            continue;
+        }
+        if !path.join(config_filename).exists() {
+            continue;
+        }
+
+        result.push(path);
+    }
+    // read_dir doesn't guarantee any order
+    result.sort();
+    Ok(result)
+}
+
 fn build_entrypoints(
     patterns_map: &HashMap<ActionListSection, Vec<String>>,
 ) -> Result<Entrypoints, globset::Error> {
@@ -177,16 +658,104 @@ fn build_entrypoints(
     Ok(result)
 }
 
+/// The values of the glob patterns that match the given file, without duplicates, e.g. the platforms of a
+/// `platform` map. Patterns are matched against the path of the file relative to its version folder.
+fn match_patterns<T: Clone + PartialEq>(
+    patterns: &BTreeMap<String, T>,
+    relpath: &str,
+) -> Result<Vec<T>, globset::Error> {
+    let mut result: Vec<T> = vec![];
+    for (pattern, value) in patterns.iter() {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()?
+            .compile_matcher();
+        if glob.is_match(relpath) && !result.contains(value) {
+            result.push(value.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// Build a glob set from patterns, where `*` doesn't match path separators.
+fn build_globset(patterns: &[&str]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    builder.build()
+}
+
 #[derive(Debug)]
 pub(crate) struct Repository {
     /// Must be an absolute path
     path: PathBuf,
     config: RepositoryConfig,
+    state: ExportState,
     git_hash: OnceCell<String>,
+    git_tag: OnceCell<String>,
+    git_branch: OnceCell<String>,
+    git_file_commits: OnceCell<HashMap<RelativePathBuf, String>>,
+    /// Leave descriptions and changelogs out of the index
+    slim: bool,
+    /// URL pattern set for this session only, which takes priority over every configured pattern
+    url_pattern_override: Option<String>,
+    url_template: OnceCell<UrlTemplate>,
 }
 
 impl Repository {
-    const CONFIG_FILENAME: &'static str = "repository.toml";
+    pub(crate) const CONFIG_FILENAME: &'static str = "repository.toml";
+    /// Folder in the repository that converted READMEs are cached in
+    pub(crate) const CACHE_DIRNAME: &'static str = ".reapack-indexer-cache";
+
+    /// Config file names to look for, in order, if `REAPACK_INDEXER_CONFIG` is not set.
+    const DEFAULT_CONFIG_LOOKUP: &'static [&'static str] =
+        &[".reapack-indexer.toml", Self::CONFIG_FILENAME];
+
+    /// The config file names to look for, in order.
+    /// Can be overridden with the `REAPACK_INDEXER_CONFIG` environment variable, as a comma-separated list.
+    fn config_lookup() -> Vec<String> {
+        match std::env::var("REAPACK_INDEXER_CONFIG") {
+            Ok(names) if !names.trim().is_empty() => names
+                .split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect(),
+            _ => Self::DEFAULT_CONFIG_LOOKUP
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+        }
+    }
+
+    /// Find the config file for a repository in the given folder.
+    ///
+    /// Dotfiles (e.g. `.reapack-indexer.toml`) are also looked up in parent folders, up to the root of the git
+    /// repository. This allows keeping the config at the git root, when the repository is in a subfolder.
+    pub(crate) fn find_config(dir: &Path) -> Option<PathBuf> {
+        Self::find_config_named(dir, &Self::config_lookup())
+    }
+
+    /// Look for the given config file names in the folder first, then for the dotfiles in its parent folders.
+    /// Parent folders are only searched inside a git repository, up to its root.
+    fn find_config_named(dir: &Path, names: &[String]) -> Option<PathBuf> {
+        let find_in = |folder: &Path, dotfiles_only: bool| {
+            names
+                .iter()
+                .filter(|name| !dotfiles_only || name.starts_with('.'))
+                .map(|name| folder.join(name))
+                .find(|path| path.is_file())
+        };
+        if let Some(path) = find_in(dir, false) {
+            return Some(path);
+        }
+        // without a git repository, there's no telling where the repository ends
+        let git_root = dir.ancestors().find(|x| x.join(".git").exists())?;
+        dir.ancestors()
+            .skip(1)
+            .take_while(|x| x.starts_with(git_root))
+            .find_map(|x| find_in(x, true))
+    }
 
     pub(crate) fn read(dir: &Path) -> Result<Self> {
         // convert to absolute path to ensure we can get the folder names etc
@@ -199,22 +768,35 @@ impl Repository {
             path::absolute(&dir).unwrap().display()
         );
 
-        let config_path = dir.join(Self::CONFIG_FILENAME);
-        if !config_path.exists() {
-            return Err(NotARepository(dir).into());
-        }
+        let Some(config_path) = Self::find_config(&dir) else {
+            return Err(NotARepository(dir, Self::config_lookup().join(", ")).into());
+        };
         let config: RepositoryConfig = toml::from_str(&fs::read_to_string(&config_path)?)?;
+        check_url_pattern(&config_path, &config.url_pattern)?;
+        let state = ExportState::read(&dir)?;
 
         Ok(Self {
             path: dir,
             config,
+            state,
             git_hash: OnceCell::new(),
+            git_tag: OnceCell::new(),
+            git_branch: OnceCell::new(),
+            git_file_commits: OnceCell::new(),
+            slim: false,
+            url_pattern_override: None,
+            url_template: OnceCell::new(),
         })
     }
 
+    /// Path of the repository's config file.
+    pub(crate) fn config_path(&self) -> PathBuf {
+        Self::find_config(&self.path).unwrap_or_else(|| self.path.join(Self::CONFIG_FILENAME))
+    }
+
     /// Unique identifier for this repo.
     /// Will be used as the folder name to store the repo.
-    pub(crate) fn identifier(&self) -> Cow<str> {
+    pub(crate) fn identifier(&self) -> Cow<'_, str> {
         if let Some(identifier) = self.config.identifier.as_ref() {
             identifier.into()
         } else {
@@ -223,7 +805,65 @@ impl Repository {
     }
 
     pub(crate) fn readme(&self) -> Result<Option<String>> {
-        read_rtf_or_md_file(&self.path.join("README.rtf"))
+        read_rtf_or_md_file(
+            &self.path.join("README.rtf"),
+            &[
+                ("identifier", &self.identifier()),
+                ("author", self.author()),
+            ],
+            self,
+        )
+    }
+
+    /// Convert a Markdown README to RTF with pandoc. Conversions are cached in [Self::CACHE_DIRNAME] by the hash of
+    /// the Markdown, so unchanged READMEs aren't converted again on every export.
+    fn markdown_to_rtf(&self, text: String) -> Result<String> {
+        let hash = sha256_hex(text.as_bytes());
+        let cache_dir = self.path.join(Self::CACHE_DIRNAME);
+        let cache_path = cache_dir.join(format!("{hash}.rtf"));
+        if let Ok(rtf) = fs::read_to_string(&cache_path) {
+            return Ok(rtf);
+        }
+
+        let rtf = convert_markdown(
+            text,
+            pandoc::OutputFormat::Rtf,
+            true,
+            self.pandoc_path().as_deref(),
+        )?;
+        // the cache is only an optimisation, so failing to write it isn't an error
+        let write_cache = || -> std::io::Result<()> {
+            fs::create_dir_all(&cache_dir)?;
+            // keep the cache out of git without having to edit the repository's .gitignore
+            fs::write(cache_dir.join(".gitignore"), "*\n")?;
+            fs::write(&cache_path, &rtf)
+        };
+        if let Err(err) = write_cache() {
+            warnings::warn(
+                "readme-cache",
+                &self.path,
+                format!(
+                    "failed to cache converted README in {}: {err}",
+                    cache_dir.display()
+                ),
+            );
+        }
+        Ok(rtf)
+    }
+
+    /// The pandoc executable used to convert READMEs, from `--pandoc`, the `PANDOC_PATH` environment variable or
+    /// `pandoc_path` in repository.toml, in that order. None if pandoc should be searched for in `PATH`.
+    pub(crate) fn pandoc_path(&self) -> Option<PathBuf> {
+        PANDOC_PATH
+            .get()
+            .cloned()
+            .or_else(|| std::env::var_os("PANDOC_PATH").map(PathBuf::from))
+            .or_else(|| {
+                self.config
+                    .pandoc_path
+                    .as_ref()
+                    .map(|path| self.path.join(path))
+            })
     }
 
     pub(crate) fn path(&self) -> &Path {
@@ -234,8 +874,151 @@ impl Repository {
         &self.config.author
     }
 
+    /// The authors allowed to publish packages in this repository, if restricted.
+    pub(crate) fn authors(&self) -> Option<&[String]> {
+        self.config.authors.as_deref()
+    }
+
+    /// Whether `check` should flag empty or unfinished changelogs.
+    pub(crate) fn lint_changelogs(&self) -> bool {
+        self.config.lint_changelogs.unwrap_or(false)
+    }
+
+    /// Whether deprecated packages are left out of the index.
+    pub(crate) fn exclude_deprecated(&self) -> bool {
+        self.config.exclude_deprecated.unwrap_or(false)
+    }
+
+    /// Whether the package is included in the index, i.e. it isn't a deprecated package that is left out.
+    fn includes_package(&self, pkg: &Package) -> bool {
+        !(self.exclude_deprecated() && pkg.deprecation_notice().is_some())
+    }
+
+    /// The packages that are included in the index. Everything generated from the index (the manifest, the site,
+    /// the feed, ...) should use these rather than [Self::packages].
+    pub(crate) fn indexed_packages(&self) -> Result<Vec<Package>> {
+        Ok(self
+            .packages()?
+            .into_iter()
+            .filter(|pkg| self.includes_package(pkg))
+            .collect())
+    }
+
+    /// Whether sources registered only in the main section use the legacy `main="true"` form,
+    /// for ReaPack clients older than v1.1.
+    pub(crate) fn legacy_main_attribute(&self) -> bool {
+        self.config.legacy_main_attribute.unwrap_or(false)
+    }
+
+    /// Whether sources have a `hash` attribute with the SHA-256 hash of the file.
+    pub(crate) fn source_hashes(&self) -> bool {
+        self.config.source_hashes.unwrap_or(false)
+    }
+
+    pub(crate) fn storage(&self) -> Option<&StorageConfig> {
+        self.config.storage.as_ref()
+    }
+
+    pub(crate) fn min_reapack_version(&self) -> Option<&str> {
+        self.config.min_reapack_version.as_deref()
+    }
+
     pub(crate) fn url_pattern(&self) -> &str {
-        &self.config.url_pattern
+        self.url_pattern_override
+            .as_deref()
+            .unwrap_or(&self.config.url_pattern)
+    }
+
+    /// The program that prints the URLs of sources, unless the URL pattern was overridden for this session.
+    pub(crate) fn url_command(&self) -> Option<&[String]> {
+        match self.url_pattern_override {
+            Some(_) => None,
+            None => self.config.url_command.as_deref(),
+        }
+    }
+
+    /// The parsed URL pattern of the sources of a version. Versions can override the pattern of their package, which
+    /// can override the pattern of the repository.
+    pub(crate) fn url_template<'a>(
+        &'a self,
+        pkg: &'a Package,
+        ver: &'a Version,
+    ) -> Result<&'a UrlTemplate, leon::ParseError> {
+        if self.url_pattern_override.is_none() {
+            if let Some(template) = ver.url_template()? {
+                return Ok(template);
+            }
+            if let Some(template) = pkg.url_template()? {
+                return Ok(template);
+            }
+        }
+        self.url_template
+            .get_or_try_init(|| UrlTemplate::parse(self.url_pattern()))
+    }
+
+    /// The default path to export the index to, as configured in `repository.toml`.
+    /// Relative paths are resolved from the repository folder.
+    pub(crate) fn output_path(&self) -> Option<PathBuf> {
+        self.config
+            .output_path
+            .as_ref()
+            .map(|output_path| self.path.join(output_path))
+    }
+
+    /// The path `export` writes the index to when no output path is given: `output_path` from the config, or the
+    /// default file name of the format in the current folder. `output_path` is the path of the XML index, other
+    /// formats are written next to it with their own extension. Commands that read the exported index default to
+    /// this path too.
+    pub(crate) fn default_index_path(&self, format: IndexFormat) -> PathBuf {
+        match (self.output_path(), format) {
+            (Some(output_path), IndexFormat::Xml) => output_path,
+            (Some(output_path), _) => output_path.with_extension(format.extension()),
+            (None, _) => format.default_filename().into(),
+        }
+    }
+
+    /// The path of the exported XML index, see [Self::default_index_path].
+    pub(crate) fn index_path(&self) -> PathBuf {
+        self.default_index_path(IndexFormat::Xml)
+    }
+
+    /// Leave descriptions and changelogs out of the generated index, to make it smaller. Deprecation notices are
+    /// still included.
+    pub(crate) fn set_slim(&mut self, slim: bool) {
+        self.slim = slim;
+    }
+
+    /// Override the formatting of the index for this session only, without changing `repository.toml`.
+    /// Options that are None keep the configured value.
+    pub(crate) fn set_xml_format(
+        &mut self,
+        version: Option<XmlVersion>,
+        indent: Option<bool>,
+        sort_attributes: Option<bool>,
+    ) {
+        self.config.xml_version = version.or(self.config.xml_version);
+        self.config.xml_indent = indent.or(self.config.xml_indent);
+        self.config.xml_sort_attributes = sort_attributes.or(self.config.xml_sort_attributes);
+    }
+
+    /// Override the URL pattern for this session only, without changing `repository.toml`. This also overrides the
+    /// patterns of packages.
+    pub(crate) fn set_url_pattern(&mut self, url_pattern: String) {
+        self.url_pattern_override = Some(url_pattern);
+        self.url_template = OnceCell::new();
+    }
+
+    /// Sort the given categories for display in the index.
+    /// Categories listed in `category_order` come first, in that order. The rest are sorted alphabetically.
+    pub(crate) fn sort_categories(&self, categories: &mut [&RelativePath]) {
+        let order = self.config.category_order.as_deref().unwrap_or_default();
+        categories.sort_by_key(|category| {
+            let position = order
+                .iter()
+                .position(|x| x.normalize() == category.normalize())
+                .unwrap_or(order.len());
+            (position, category.to_string())
+        });
     }
 
     pub(crate) fn git_hash(&self) -> Result<&str, GitCommitError> {
@@ -244,11 +1027,169 @@ impl Repository {
             .map(|x| x.as_str())
     }
 
+    /// The git tag used for the `git_tag` URL variable. Defaults to the most recent tag of the current commit.
+    pub(crate) fn git_tag(&self) -> Result<&str, GitCommitError> {
+        self.git_tag
+            .get_or_try_init(|| get_git_tag(&self.path))
+            .map(|x| x.as_str())
+    }
+
+    /// The branch that is checked out, used for the `git_branch` URL variable.
+    pub(crate) fn git_branch(&self) -> Result<&str, GitCommitError> {
+        self.git_branch
+            .get_or_try_init(|| get_git_branch(&self.path))
+            .map(|x| x.as_str())
+    }
+
+    /// The commit that last changed the given file, used for the `file_commit` URL variable.
+    pub(crate) fn file_git_hash(&self, path: &Path) -> Result<&str, GitCommitError> {
+        let commits = self
+            .git_file_commits
+            .get_or_try_init(|| get_git_file_commits(&self.path))?;
+        path.relative_to(&self.path)
+            .ok()
+            .and_then(|relpath| commits.get(&relpath))
+            .map(|x| x.as_str())
+            .ok_or_else(|| GitCommitError::FileNotCommitted(path.into()))
+    }
+
+    /// Use the given tag for the `git_tag` URL variable for this session only, instead of the most recent tag.
+    pub(crate) fn set_git_tag(&mut self, tag: String) {
+        self.git_tag = OnceCell::with_value(tag);
+    }
+
+    /// A link to the full release notes of the given version, appended to the end of its changelog.
+    pub(crate) fn changelog_url(&self, pkg: &Package, ver: &Version) -> Result<Option<String>> {
+        let Some(pattern) = &self.config.changelog_url_pattern else {
+            return Ok(None);
+        };
+        let template = Template::parse(pattern)?;
+        let values = [("package", pkg.identifier()), ("version", ver.name())];
+        Ok(Some(template.render(&values)?))
+    }
+
+    /// Whether the repository has uncommitted changes.
+    pub(crate) fn git_is_dirty(&self) -> Result<bool, GitCommitError> {
+        get_git_dirty(&self.path)
+    }
+
+    /// Whether versions should keep using the git commit from when they were first exported.
+    pub(crate) fn pin_git_commits(&self) -> bool {
+        self.config.pin_git_commits.unwrap_or(false)
+    }
+
+    /// The length of the `git_commit_short` URL variable, 7 characters by default like `git log --oneline`.
+    pub(crate) fn git_commit_short_length(&self) -> usize {
+        self.config.git_commit_short_length.unwrap_or(7)
+    }
+
+    /// The git commit used to generate source URLs for the given version.
+    /// If commit pinning is enabled and the version has been exported before, this is the commit used at that time.
+    pub(crate) fn version_git_hash(
+        &self,
+        pkg: &Package,
+        ver: &Version,
+    ) -> Result<&str, GitCommitError> {
+        if self.pin_git_commits() {
+            if let Some(hash) = self.state.commit(&pkg.identifier(), &ver.name()) {
+                return Ok(hash);
+            }
+        }
+        self.git_hash()
+    }
+
+    /// Remember the files and folders written by an export, so that `gc` doesn't report them. Paths outside the
+    /// repository are left out. The state file is only written if any of them are new.
+    pub(crate) fn record_export_outputs(&mut self, paths: &[PathBuf]) -> Result<()> {
+        let outputs: Vec<String> = paths
+            .iter()
+            .filter_map(|x| path::absolute(x).ok())
+            .filter_map(|x| {
+                let relpath = x.strip_prefix(&self.path).ok()?;
+                Some(relpath.to_string_lossy().replace('\\', "/"))
+            })
+            .filter(|x| !x.is_empty())
+            .collect();
+        if self.state.add_outputs(outputs) {
+            self.state.write(self.path())?;
+        }
+        Ok(())
+    }
+
+    /// The files and folders written by previous exports, see [Self::record_export_outputs].
+    pub(crate) fn export_outputs(&self) -> Vec<PathBuf> {
+        self.state
+            .outputs
+            .iter()
+            .map(|x| self.path.join(x))
+            .collect()
+    }
+
+    /// Record the current commit for all versions that haven't been pinned yet, then save the state file.
+    /// Versions that aren't part of the current commit are skipped, so they get pinned on a later export.
+    pub(crate) fn pin_version_commits(&self) -> Result<()> {
+        if !self.pin_git_commits() {
+            return Ok(());
+        }
+
+        let hash = self.git_hash()?;
+        let mut state = self.state.clone();
+        for pkg in self.packages()? {
+            for ver in pkg.versions()? {
+                if state.commit(&pkg.identifier(), &ver.name()).is_some() {
+                    continue;
+                }
+                let config_relpath = ver
+                    .path()
+                    .join(Version::CONFIG_FILENAME)
+                    .relative_to(self.path())?;
+                if !git_file_exists_in_commit(self.path(), hash, &config_relpath)? {
+                    warnings::warn(
+                        "uncommitted-version",
+                        ver.path(),
+                        format!(
+                            "not pinning version {} of {} as it has not been committed yet",
+                            ver.name(),
+                            pkg.identifier()
+                        ),
+                    );
+                    continue;
+                }
+                state.set_commit(&pkg.identifier(), &ver.name(), hash);
+            }
+        }
+        state.write(self.path())
+    }
+
     pub(crate) fn packages(&self) -> Result<Vec<Package>> {
         Package::discover_packages(self.path())
     }
 
+    /// Find the package with the given identifier.
+    pub(crate) fn package(&self, identifier: &str) -> Result<Option<Package>> {
+        Ok(self
+            .packages()?
+            .into_iter()
+            .find(|pkg| pkg.identifier() == identifier))
+    }
+
     pub(crate) fn add_package(&self, identifier: &str) -> Result<Package> {
+        let target_path = self.new_package_path(identifier)?;
+        Package::create_package(&target_path, None)
+    }
+
+    /// Create a new package, generating its config from the given template parameters.
+    pub(crate) fn add_package_with_params(
+        &self,
+        identifier: &str,
+        params: PackageTemplateParams,
+    ) -> Result<Package> {
+        let target_path = self.new_package_path(identifier)?;
+        Package::create_package(&target_path, Some(params))
+    }
+
+    /// Find the folder that a new package with the given identifier would be created in, without creating it.
+    pub(crate) fn new_package_path(&self, identifier: &str) -> Result<PathBuf> {
         let existing_packages = self.packages()?;
         if let Some(pkg) = existing_packages
             .iter()
@@ -272,15 +1213,62 @@ impl Repository {
                 }
             }
         };
+        Ok(target_path)
+    }
 
-        // TODO: Allow specifying config when creating package
-        Package::create_package(&target_path, None)
+    /// Move a package to a new folder and change its identifier.
+    pub(crate) fn rename_package(&self, pkg: &Package, new_identifier: &str) -> Result<Package> {
+        if let Some(existing) = self.package(new_identifier)? {
+            return Err(PackageAlreadyExists(existing.path().into()).into());
+        }
+        let target_path = self.path().join(new_identifier);
+        if target_path.exists() {
+            return Err(PackageAlreadyExists(target_path).into());
+        }
+
+        fs::rename(pkg.path(), &target_path)?;
+        if pkg.config.identifier.is_some() {
+            update_toml_file(&target_path.join(Package::CONFIG_FILENAME), |doc| {
+                doc["identifier"] = toml_edit::value(new_identifier);
+            })?;
+        }
+
+        // pinned commits refer to the old folder, so they are no longer valid
+        if self.state.commits.contains_key(pkg.identifier().as_ref()) {
+            let mut state = self.state.clone();
+            state.remove_package(&pkg.identifier());
+            state.write(self.path())?;
+        }
+
+        Package::read(&target_path)
+    }
+
+    /// Create a new package with the same configuration as the given package, without any versions.
+    pub(crate) fn copy_package(&self, pkg: &Package, new_identifier: &str) -> Result<Package> {
+        let target_path = self.new_package_path(new_identifier)?;
+        fs::create_dir(&target_path)?;
+        let config_path = target_path.join(Package::CONFIG_FILENAME);
+        fs::copy(pkg.config_path(), &config_path)?;
+        update_toml_file(&config_path, |doc| {
+            // the folder may be numbered if a folder with the identifier's name already exists
+            if target_path.file_name() == Some(new_identifier.as_ref()) {
+                doc.remove("identifier");
+            } else {
+                doc["identifier"] = toml_edit::value(new_identifier);
+            }
+        })?;
+        Package::read(&target_path)
     }
 
     pub(crate) fn generate_index(&self) -> Result<String> {
         let mut xml = XMLBuilder::new()
-            .version(XMLVersion::XML1_1)
+            .version(match self.config.xml_version.unwrap_or_default() {
+                XmlVersion::V1_0 => XMLVersion::XML1_0,
+                XmlVersion::V1_1 => XMLVersion::XML1_1,
+            })
             .encoding("UTF-8".into())
+            .indent(self.config.xml_indent.unwrap_or(true))
+            .sort_attributes(self.config.xml_sort_attributes.unwrap_or(false))
             .build();
 
         let root_element = self.element()?;
@@ -298,20 +1286,37 @@ impl Repository {
         index.add_attribute("version", "1");
         index.add_attribute("name", &self.identifier());
 
-        // add description
-        if let Some(desc) = &self.readme()? {
-            let mut metadata = XMLElement::new("metadata");
-            let mut description = XMLElement::new("description");
-            description.add_text(cdata(desc)).unwrap();
-            metadata.add_child(description).unwrap();
+        // add description and links
+        let links = [
+            ("website", &self.config.website),
+            ("donation", &self.config.donation),
+            ("screenshot", &self.config.screenshot),
+        ];
+        let links: Vec<(&str, &str)> = links
+            .iter()
+            .filter_map(|(rel, url)| Some((*rel, url.as_deref()?)))
+            .collect();
+        let desc = if self.slim { None } else { self.readme()? };
+        if let Some(metadata) = metadata_element(desc.as_deref(), &links) {
             index.add_child(metadata).unwrap();
         }
 
         // group packages into categories
         let packages = self.packages()?;
+        let identifiers: Vec<Cow<str>> = packages.iter().map(|pkg| pkg.identifier()).collect();
+        if let Some((dup, original)) = find_case_duplicates(&identifiers).first() {
+            return Err(DuplicatePackageIdentifier(
+                identifiers[*original].to_string(),
+                identifiers[*dup].to_string(),
+            )
+            .into());
+        }
         let pkg_map = {
             let mut pkg_map = HashMap::new();
-            for pkg in packages.into_iter() {
+            for pkg in packages
+                .into_iter()
+                .filter(|pkg| self.includes_package(pkg))
+            {
                 if !pkg_map.contains_key(pkg.category()) {
                     pkg_map.insert(pkg.category().to_relative_path_buf(), vec![]);
                 }
@@ -322,10 +1327,15 @@ impl Repository {
         };
 
         // insert categories into index
-        for (category_name, packages) in pkg_map.iter() {
+        let mut category_names: Vec<&RelativePath> =
+            pkg_map.keys().map(|x| x.as_relative_path()).collect();
+        self.sort_categories(&mut category_names);
+        for category_name in category_names {
             let mut category = XMLElement::new("category");
             category.add_attribute("name", category_name.as_ref());
 
+            let mut packages: Vec<&Package> = pkg_map[category_name].iter().collect();
+            packages.sort_by_key(|pkg| pkg.identifier());
             for pkg in packages {
                 let reapack = pkg.element(self)?;
                 category.add_child(reapack).unwrap();
@@ -343,30 +1353,35 @@ pub(crate) struct Package {
     path: PathBuf,
     config: PackageConfig,
     entrypoints: OnceCell<Option<Entrypoints>>,
+    url_template: OnceCell<UrlTemplate>,
 }
 
 impl Package {
-    const CONFIG_FILENAME: &'static str = "package.toml";
+    pub(crate) const CONFIG_FILENAME: &'static str = "package.toml";
 
     pub(crate) fn read(dir: &Path) -> Result<Self> {
         debug_assert!(
-            dir == path::absolute(&dir).unwrap(),
+            dir == path::absolute(dir).unwrap(),
             "dir = {} ; absolute(dir) = {}",
             dir.display(),
-            path::absolute(&dir).unwrap().display()
+            path::absolute(dir).unwrap().display()
         );
 
         let config_path = dir.join(Self::CONFIG_FILENAME);
-        let config: PackageConfig = toml::from_str(&fs::read_to_string(config_path)?)?;
+        let config: PackageConfig = toml::from_str(&fs::read_to_string(&config_path)?)?;
+        if let Some(url_pattern) = &config.url_pattern {
+            check_url_pattern(&config_path, url_pattern)?;
+        }
 
         Ok(Self {
             path: dir.into(),
             config,
             entrypoints: OnceCell::new(),
+            url_template: OnceCell::new(),
         })
     }
 
-    pub(crate) fn identifier(&self) -> Cow<str> {
+    pub(crate) fn identifier(&self) -> Cow<'_, str> {
         if let Some(identifier) = &self.config.identifier {
             identifier.into()
         } else {
@@ -374,7 +1389,7 @@ impl Package {
         }
     }
 
-    pub(crate) fn name(&self) -> Cow<str> {
+    pub(crate) fn name(&self) -> Cow<'_, str> {
         if let Some(name) = &self.config.name {
             name.into()
         } else {
@@ -382,10 +1397,22 @@ impl Package {
         }
     }
 
+    /// The `desc` attribute of the package in the index, shown in ReaPack's package list.
+    pub(crate) fn description(&self) -> Cow<'_, str> {
+        match &self.config.description {
+            Some(description) => description.into(),
+            None => self.name(),
+        }
+    }
+
     pub(crate) fn path(&self) -> &Path {
         &self.path
     }
 
+    pub(crate) fn config_path(&self) -> PathBuf {
+        self.path.join(Self::CONFIG_FILENAME)
+    }
+
     pub(crate) fn category(&self) -> &RelativePath {
         &self.config.category
     }
@@ -394,12 +1421,145 @@ impl Package {
         self.config.pkg_type.clone()
     }
 
+    fn url_template(&self) -> Result<Option<&UrlTemplate>, leon::ParseError> {
+        match &self.config.url_pattern {
+            Some(pattern) => self
+                .url_template
+                .get_or_try_init(|| UrlTemplate::parse(pattern))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
     pub(crate) fn author(&self) -> Option<&str> {
         self.config.author.as_deref()
     }
 
-    pub(crate) fn readme(&self) -> Result<Option<String>> {
-        read_rtf_or_md_file(&self.path.join("README.rtf"))
+    /// The notice shown to users if this package is deprecated.
+    pub(crate) fn deprecation_notice(&self) -> Option<String> {
+        match self.config.deprecated.as_ref()? {
+            Deprecation::Flag(false) => None,
+            Deprecation::Flag(true) => Some("This package is deprecated.".into()),
+            Deprecation::Message(message) => {
+                Some(format!("This package is deprecated: {}", message.trim()))
+            }
+        }
+    }
+
+    pub(crate) fn set_deprecated(&mut self, deprecated: Option<Deprecation>) -> Result<()> {
+        update_toml_file(&self.config_path(), |doc| match &deprecated {
+            Some(Deprecation::Flag(flag)) => doc["deprecated"] = toml_edit::value(*flag),
+            Some(Deprecation::Message(message)) => {
+                doc["deprecated"] = toml_edit::value(message.as_str())
+            }
+            None => {
+                doc.remove("deprecated");
+            }
+        })?;
+        self.config.deprecated = deprecated;
+        Ok(())
+    }
+
+    /// The path of the package's README, either `README.rtf` or `README.md`, if it exists.
+    pub(crate) fn readme_path(&self) -> Option<PathBuf> {
+        ["README.rtf", "README.md"]
+            .iter()
+            .map(|x| self.readme_dir().join(x))
+            .find(|x| x.exists())
+    }
+
+    /// The folder containing the package's README. A README in the folder of the latest version takes priority over
+    /// the package's own README, so that the description can follow the newest release.
+    fn readme_dir(&self) -> PathBuf {
+        let has_readme =
+            |dir: &Path| dir.join("README.rtf").exists() || dir.join("README.md").exists();
+        self.latest_released_version()
+            .ok()
+            .flatten()
+            .map(|ver| ver.path().to_path_buf())
+            .filter(|dir| has_readme(dir))
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// The values of the placeholders that can be used in the package's README.
+    fn readme_placeholders(&self, repo: &Repository) -> Result<Vec<(&'static str, String)>> {
+        let latest_version = self.latest_released_version()?;
+        Ok(vec![
+            ("identifier", self.identifier().to_string()),
+            (
+                "latest_version",
+                latest_version
+                    .map(|ver| ver.name().to_string())
+                    .unwrap_or_default(),
+            ),
+            ("author", self.author().unwrap_or(repo.author()).to_string()),
+        ])
+    }
+
+    /// Whether the package has a description, either a README or the `@about` header of its main entrypoint.
+    /// Unlike [Self::readme], this doesn't convert the description.
+    pub(crate) fn has_description(&self) -> Result<bool> {
+        Ok(self.readme_path().is_some() || self.about_header()?.is_some())
+    }
+
+    /// The `@about` header of the main entrypoint of the latest version, used as the description of script packages
+    /// without a README. Returns the path of the entrypoint and the header's text.
+    fn about_header(&self) -> Result<Option<(PathBuf, String)>> {
+        if self.pkg_type() != PackageType::Script {
+            return Ok(None);
+        }
+        let Some(ver) = self.latest_released_version()? else {
+            return Ok(None);
+        };
+        for src in ver.sources(self)? {
+            // remote and external sources aren't available locally
+            if !src.path().is_file() || src.sections(self, &ver)?.is_empty() {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(src.path()) else {
+                continue;
+            };
+            if let Some(about) = Headers::parse(&text).get("about") {
+                return Ok(Some((src.path().to_path_buf(), about.to_string())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The package's README converted to RTF. Script packages without a README use the `@about` header of their main
+    /// entrypoint instead, like reapack-index.
+    pub(crate) fn readme(&self, repo: &Repository) -> Result<Option<String>> {
+        let placeholders = self.readme_placeholders(repo)?;
+        let placeholders: Vec<(&str, &str)> =
+            placeholders.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        if let Some(readme) =
+            read_rtf_or_md_file(&self.readme_dir().join("README.rtf"), &placeholders, repo)?
+        {
+            return Ok(Some(readme));
+        }
+        match self.about_header()? {
+            Some((path, about)) => markdown_to_rtf_or_plain_text(about, &path, repo).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The package's `README.md` with its placeholders filled in, without converting it to RTF. Like
+    /// [Self::readme], script packages without a README use the `@about` header of their main entrypoint.
+    pub(crate) fn readme_markdown(&self, repo: &Repository) -> Result<Option<String>> {
+        if self.readme_path().is_none() {
+            return Ok(self.about_header()?.map(|(_, about)| about));
+        }
+        let path = self.readme_dir().join("README.md");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let placeholders = self.readme_placeholders(repo)?;
+        let placeholders: Vec<(&str, &str)> =
+            placeholders.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        Ok(Some(render_placeholders(
+            &fs::read_to_string(path)?,
+            &placeholders,
+        )))
     }
 
     pub(crate) fn entrypoints(&self) -> Result<Option<&Entrypoints>, globset::Error> {
@@ -415,12 +1575,66 @@ impl Package {
         Version::discover_versions(self.path())
     }
 
-    pub(crate) fn latest_version(&self) -> Result<Option<Version>> {
-        Ok(self
+    /// The versions to be included in the index.
+    /// Yanked versions are skipped, and only the newest `max_versions_in_index` versions are kept. The limit of the
+    /// package takes priority over the limit of the repository.
+    pub(crate) fn indexed_versions(&self, repo: &Repository) -> Result<Vec<Version>> {
+        let versions = self
             .versions()?
-            .iter()
-            .max_by(|a, b| Version::compare_version_names(&a.name(), &b.name()))
-            .cloned())
+            .into_iter()
+            .filter(|ver| !ver.yanked())
+            .collect();
+        let max_versions = self
+            .config
+            .max_versions_in_index
+            .or(repo.config.max_versions_in_index);
+        Ok(match max_versions {
+            Some(max) => Version::retain_newest(versions, max),
+            None => versions,
+        })
+    }
+
+    /// Find the version with the given name.
+    pub(crate) fn version(&self, name: &str) -> Result<Option<Version>> {
+        Ok(self.versions()?.into_iter().find(|ver| ver.name() == name))
+    }
+
+    pub(crate) fn latest_version(&self) -> Result<Option<Version>> {
+        Ok(Version::latest(self.versions()?))
+    }
+
+    /// The latest version that isn't yanked.
+    pub(crate) fn latest_released_version(&self) -> Result<Option<Version>> {
+        Ok(Version::latest(
+            self.versions()?.into_iter().filter(|ver| !ver.yanked()),
+        ))
+    }
+
+    /// The latest version that is included in the index.
+    pub(crate) fn latest_indexed_version(&self, repo: &Repository) -> Result<Option<Version>> {
+        Ok(Version::latest(self.indexed_versions(repo)?))
+    }
+
+    /// The screenshot links of the package.
+    pub(crate) fn screenshots(&self) -> &[String] {
+        self.config
+            .links
+            .as_ref()
+            .and_then(|x| x.screenshot.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The links of the package as `(rel, url)` pairs, in the order they're listed in the index.
+    pub(crate) fn links(&self) -> Vec<(&'static str, &str)> {
+        let mut links: Vec<(&'static str, &str)> = vec![];
+        if let Some(pkg_links) = &self.config.links {
+            links.extend(pkg_links.website.as_deref().map(|x| ("website", x)));
+            links.extend(pkg_links.donation.as_deref().map(|x| ("donation", x)));
+        }
+        for screenshot in self.screenshots() {
+            links.push(("screenshot", screenshot.as_str()));
+        }
+        links
     }
 
     fn create_package(path: &Path, config: Option<PackageTemplateParams>) -> Result<Package> {
@@ -430,59 +1644,40 @@ impl Package {
         if path.exists() {
             let metadata = path.metadata()?;
             if !metadata.is_dir() {
-                return Err(PathIsAFile(path.into()).into());
+                return Err(PathIsAFile(path).into());
             }
         } else {
             fs::create_dir(&path)?;
         }
         let config_path = path.join(Self::CONFIG_FILENAME);
         if config_path.exists() {
-            return Err(PackageAlreadyExists(path.into()).into());
+            return Err(PackageAlreadyExists(path).into());
         }
 
         // create package config
-        let config_text =
-            templates::generate_package_config(&config.unwrap_or(PackageTemplateParams::default()));
+        let config_text = templates::generate_package_config(&config.unwrap_or_default());
         fs::write(&config_path, config_text)?;
 
         // read the package
         Self::read(&path)
     }
 
+    /// Find all package folders in the given repository folder, i.e. subfolders with a package config.
+    pub(crate) fn discover_package_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+        discover_config_dirs(dir, Self::CONFIG_FILENAME)
+    }
+
     fn discover_packages(dir: &Path) -> Result<Vec<Package>> {
         let mut result = vec![];
-        for entry in fs::read_dir(dir)? {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(err) => {
-                    warn!("failed to read entry {}", err);
-                    continue;
-                }
-            };
-            let path = entry.path();
-
-            let is_dir = match path.metadata() {
-                Ok(metadata) => metadata.is_dir(),
-                Err(err) => {
-                    warn!(
-                        "failed to get metadata for entry {} due to {}",
-                        path.display(),
-                        err
-                    );
-                    continue;
-                }
-            };
-            if !is_dir {
-                continue;
-            }
-            if !path.join(Self::CONFIG_FILENAME).exists() {
-                continue;
-            }
-
+        for path in Self::discover_package_paths(dir)? {
             let pkg = match Package::read(&path) {
                 Ok(pkg) => pkg,
                 Err(err) => {
-                    warn!("failed to read package {} due to {}", path.display(), err);
+                    warnings::warn(
+                        "unreadable-package",
+                        &path,
+                        format!("failed to read package {} due to {}", path.display(), err),
+                    );
                     continue;
                 }
             };
@@ -492,22 +1687,40 @@ impl Package {
     }
 
     fn element(&self, repo: &Repository) -> Result<XMLElement> {
+        let names: Vec<String> = self
+            .versions()?
+            .iter()
+            .map(|ver| ver.name().to_string())
+            .collect();
+        if let Some((dup, original)) = find_case_duplicates(&names).first() {
+            return Err(DuplicateVersionName(
+                self.path.clone(),
+                names[*original].clone(),
+                names[*dup].clone(),
+            )
+            .into());
+        }
+
         let mut reapack = XMLElement::new("reapack");
-        reapack.add_attribute("desc", &self.name());
+        reapack.add_attribute("desc", &self.description());
         reapack.add_attribute("type", (&self.pkg_type()).into());
         reapack.add_attribute("name", &self.identifier());
 
-        // add description
-        if let Some(desc) = &self.readme()? {
-            let mut metadata = XMLElement::new("metadata");
-            let mut description = XMLElement::new("description");
-            description.add_text(cdata(desc)).unwrap();
-            metadata.add_child(description).unwrap();
+        // add description, with the deprecation notice at the end, and links
+        let readme = if repo.slim { None } else { self.readme(repo)? };
+        let desc = match (readme, self.deprecation_notice()) {
+            (desc, None) => desc,
+            (Some(desc), Some(notice)) => Some(rtf_append_paragraph(&desc, &notice)),
+            (None, Some(notice)) => Some(rtf_append_paragraph("{\\rtf1\\ansi\n}", &notice)),
+        };
+        if let Some(metadata) = metadata_element(desc.as_deref(), &self.links()) {
             reapack.add_child(metadata).unwrap();
         }
 
-        // add versions
-        for version in self.versions()?.iter() {
+        // add versions, oldest first
+        let mut versions = self.indexed_versions(repo)?;
+        versions.sort_by(|a, b| Version::compare_version_names(&a.name(), &b.name()));
+        for version in versions.iter() {
             reapack.add_child(version.element(repo, self)?).unwrap();
         }
 
@@ -520,24 +1733,44 @@ pub(crate) struct Version {
     path: PathBuf,
     config: VersionConfig,
     entrypoints: OnceCell<Option<Entrypoints>>,
+    url_template: OnceCell<UrlTemplate>,
 }
 
 impl Version {
-    const CONFIG_FILENAME: &'static str = "version.toml";
+    pub(crate) const CONFIG_FILENAME: &'static str = "version.toml";
+
+    /// Compares version segments by their leading number first, then by the text after it. Segments without a
+    /// leading number come after the ones with one.
+    fn compare_segments(part_a: &str, part_b: &str) -> std::cmp::Ordering {
+        fn key(part: &str) -> (bool, usize, &str, &str) {
+            let end = part
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(part.len());
+            let (digits, suffix) = part.split_at(end);
+            // compare numbers by length then digits, so that they can't overflow
+            let number = digits.trim_start_matches('0');
+            (digits.is_empty(), number.len(), number, suffix)
+        }
+        key(part_a)
+            .cmp(&key(part_b))
+            .then_with(|| part_a.cmp(part_b))
+    }
 
-    /// Splits version names by dots '.', then compares each segment.
+    /// Splits version names by dots '.', then compares each segment. Numbers are compared as numbers, so `0.0.10`
+    /// is newer than `0.0.9`; comparing them as text would put it first. Text after a number is compared as text,
+    /// so `1.10b` is newer than `1.10`. This decides which versions are the newest everywhere, e.g. for
+    /// `max_versions_in_index` and the order of versions in the index.
     pub(crate) fn compare_version_names(version_a: &str, version_b: &str) -> std::cmp::Ordering {
         for entry in version_a.split('.').zip_longest(version_b.split('.')) {
             match entry {
-                itertools::EitherOrBoth::Both(part_a, part_b) => match part_a
-                    .partial_cmp(part_b)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-                {
-                    // comparison is equal, don't return, keep iterating
-                    std::cmp::Ordering::Equal => (),
-                    // otherwise, return that order (greater/less)
-                    order => return order,
-                },
+                itertools::EitherOrBoth::Both(part_a, part_b) => {
+                    match Self::compare_segments(part_a, part_b) {
+                        // comparison is equal, don't return, keep iterating
+                        std::cmp::Ordering::Equal => (),
+                        // otherwise, return that order (greater/less)
+                        order => return order,
+                    }
+                }
                 // if one version is longer, return that one
                 itertools::EitherOrBoth::Left(_part_a) => return std::cmp::Ordering::Greater,
                 itertools::EitherOrBoth::Right(_part_b) => return std::cmp::Ordering::Less,
@@ -546,54 +1779,64 @@ impl Version {
         std::cmp::Ordering::Equal
     }
 
-    pub(crate) fn increment_version(text: &str) -> Result<String, UnknownVersionFormat> {
-        let text = text.to_string();
-
-        let suffix = {
-            let mut suffix = String::new();
-            for c in text.chars().rev() {
-                // if found non-digit char, stop the loop
-                if c.is_ascii_digit() {
-                    suffix.push(c);
-                } else {
-                    break;
-                }
-            }
-            if suffix.is_empty() {
-                return Err(UnknownVersionFormat(text));
-            }
-            suffix = suffix.chars().rev().collect();
-            Ok(suffix)
-        }?;
+    /// The newest of the given versions, by [Self::compare_version_names].
+    pub(crate) fn latest<T: Borrow<Version>>(versions: impl IntoIterator<Item = T>) -> Option<T> {
+        versions
+            .into_iter()
+            .max_by(|a, b| Self::compare_version_names(&a.borrow().name(), &b.borrow().name()))
+    }
 
-        // Parse the suffix to an integer
-        let incremented_suffix = suffix.parse::<u32>().unwrap() + 1;
+    pub(crate) fn increment_version(text: &str) -> Result<String, UnknownVersionFormat> {
+        version::increment_version(text)
+    }
 
-        // Create the new version string
-        let prefix_len = text.len() - suffix.len();
+    pub(crate) fn increment_version_part(
+        text: &str,
+        part: version::VersionPart,
+    ) -> Result<String, UnknownVersionFormat> {
+        version::increment_version_part(text, part)
+    }
 
-        Ok(format!("{}{}", &text[..prefix_len], incremented_suffix))
+    /// Keep only the `max` newest versions, preserving the original order of the remaining versions.
+    pub(crate) fn retain_newest(versions: Vec<Version>, max: usize) -> Vec<Version> {
+        let mut names: Vec<String> = versions.iter().map(|ver| ver.name().into()).collect();
+        names.sort_by(|a, b| Self::compare_version_names(b, a));
+        names.truncate(max);
+        versions
+            .into_iter()
+            .filter(|ver| names.iter().any(|x| *x == ver.name()))
+            .collect()
     }
 
     pub(crate) fn read(dir: &Path) -> Result<Self> {
         debug_assert!(
-            dir == path::absolute(&dir).unwrap(),
+            dir == path::absolute(dir).unwrap(),
             "dir = {} ; absolute(dir) = {}",
             dir.display(),
-            path::absolute(&dir).unwrap().display()
+            path::absolute(dir).unwrap().display()
         );
 
         let config_path = dir.join(Self::CONFIG_FILENAME);
-        let config: VersionConfig = toml::from_str(&fs::read_to_string(config_path)?)?;
+        let config: VersionConfig = toml::from_str(&fs::read_to_string(&config_path)?)?;
+        if let Some(url_pattern) = &config.url_pattern {
+            check_url_pattern(&config_path, url_pattern)?;
+        }
+        for target in config.targets.iter().flat_map(|x| x.values()) {
+            let normalized = target.normalize();
+            if normalized.as_str().is_empty() || normalized.starts_with("..") {
+                return Err(InvalidTarget(dir.into(), target.clone()).into());
+            }
+        }
 
         Ok(Self {
             path: dir.into(),
             config,
             entrypoints: OnceCell::new(),
+            url_template: OnceCell::new(),
         })
     }
 
-    pub(crate) fn name(&self) -> Cow<str> {
+    pub(crate) fn name(&self) -> Cow<'_, str> {
         self.path.file_name().unwrap().to_string_lossy()
     }
 
@@ -601,8 +1844,79 @@ impl Version {
         self.config.time
     }
 
-    pub(crate) fn path(&self) -> &Path {
-        &self.path
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn config_path(&self) -> PathBuf {
+        self.path.join(Self::CONFIG_FILENAME)
+    }
+
+    fn url_template(&self) -> Result<Option<&UrlTemplate>, leon::ParseError> {
+        match &self.config.url_pattern {
+            Some(pattern) => self
+                .url_template
+                .get_or_try_init(|| UrlTemplate::parse(pattern))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn author(&self) -> Option<&str> {
+        self.config.author.as_deref()
+    }
+
+    /// The author of this version, falling back to the package and repository authors.
+    pub(crate) fn resolved_author<'a>(&'a self, repo: &'a Repository, pkg: &'a Package) -> &'a str {
+        self.author().or(pkg.author()).unwrap_or(repo.author())
+    }
+
+    /// Yanked versions are kept on disk, but are excluded from the index.
+    pub(crate) fn yanked(&self) -> bool {
+        self.config.yanked.unwrap_or(false)
+    }
+
+    pub(crate) fn set_yanked(&mut self, yanked: bool) -> Result<()> {
+        update_toml_file(&self.config_path(), |doc| {
+            if yanked {
+                doc["yanked"] = toml_edit::value(true);
+            } else {
+                doc.remove("yanked");
+            }
+        })?;
+        self.config.yanked = yanked.then_some(true);
+        Ok(())
+    }
+
+    pub(crate) fn set_time(&mut self, time: DateTime<chrono::Utc>) -> Result<()> {
+        update_toml_file(&self.config_path(), |doc| {
+            doc["time"] = toml_edit::value(time.to_rfc3339());
+        })?;
+        self.config.time = time;
+        Ok(())
+    }
+
+    /// Remote versions have their source files hosted outside of the repository, and are downloaded from an
+    /// absolute URL instead.
+    pub(crate) fn remote(&self) -> Option<&RemoteConfig> {
+        self.config.remote.as_ref()
+    }
+
+    pub(crate) fn set_remote(&mut self, remote: RemoteConfig) -> Result<()> {
+        update_toml_file(&self.config_path(), |doc| {
+            let mut table = toml_edit::Table::new();
+            table["url"] = toml_edit::value(&remote.url);
+            table["files"] = toml_edit::value(
+                remote
+                    .files
+                    .iter()
+                    .map(|x| x.as_str())
+                    .collect::<toml_edit::Array>(),
+            );
+            doc["remote"] = toml_edit::Item::Table(table);
+        })?;
+        self.config.remote = Some(remote);
+        Ok(())
     }
 
     pub(crate) fn changelog(&self) -> Result<Option<String>> {
@@ -626,44 +1940,161 @@ impl Version {
         pkg.entrypoints()
     }
 
-    pub(crate) fn sources(&self) -> Result<Vec<Source>, NoSourcesFound> {
-        Source::discover_sources(&self.path)
+    /// The files that are remapped in `targets`, relative to the version folder.
+    pub(crate) fn target_sources(&self) -> Vec<&RelativePath> {
+        self.config
+            .targets
+            .iter()
+            .flat_map(|x| x.keys())
+            .map(|x| x.as_relative_path())
+            .collect()
     }
 
-    fn discover_versions(dir: &Path) -> Result<Vec<Version>> {
-        let mut result = vec![];
-        for entry in fs::read_dir(dir)? {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(err) => {
-                    warn!("failed to read entry {}", err);
-                    continue;
-                }
-            };
-            let path = entry.path();
+    /// The path a file is installed to, relative to the package folder, if it is remapped in `targets`.
+    pub(crate) fn target(&self, relpath: &RelativePath) -> Option<RelativePathBuf> {
+        self.config
+            .targets
+            .as_ref()?
+            .get(relpath)
+            .map(|x| x.normalize())
+    }
 
-            let is_dir = match path.metadata() {
-                Ok(metadata) => metadata.is_dir(),
-                Err(err) => {
-                    warn!(
-                        "failed to get metadata for entry {} due to {}",
-                        path.display(),
-                        err
-                    );
-                    continue;
-                }
+    /// The sections of individual files of this version, or of the package if the version doesn't override them.
+    pub(crate) fn entrypoint_files<'a>(
+        &'a self,
+        pkg: &'a Package,
+    ) -> Option<&'a BTreeMap<RelativePathBuf, Vec<ActionListSection>>> {
+        self.config
+            .entrypoint_files
+            .as_ref()
+            .or(pkg.config.entrypoint_files.as_ref())
+    }
+
+    /// The `types` patterns of this version, or of the package if the version doesn't override them.
+    pub(crate) fn type_patterns<'a>(
+        &'a self,
+        pkg: &'a Package,
+    ) -> Option<&'a BTreeMap<String, PackageType>> {
+        self.config.types.as_ref().or(pkg.config.types.as_ref())
+    }
+
+    /// The `platform` patterns of this version, or of the package if the version doesn't override them.
+    pub(crate) fn platform_patterns<'a>(
+        &'a self,
+        pkg: &'a Package,
+    ) -> Option<&'a BTreeMap<String, Platform>> {
+        self.config
+            .platform
+            .as_ref()
+            .or(pkg.config.platform.as_ref())
+    }
+
+    /// Glob patterns of files in the version folder that aren't sources, from the version, the package, or the
+    /// defaults. The version's README is always excluded.
+    fn exclude_patterns<'a>(&'a self, pkg: &'a Package) -> Vec<&'a str> {
+        let mut result: Vec<&str> =
+            match self.config.exclude.as_ref().or(pkg.config.exclude.as_ref()) {
+                Some(patterns) => patterns.iter().map(|x| x.as_str()).collect(),
+                None => DEFAULT_EXCLUDE.to_vec(),
             };
-            if !is_dir {
-                continue;
-            }
-            if !path.join(Self::CONFIG_FILENAME).exists() {
-                continue;
+        result.extend(ALWAYS_EXCLUDE);
+        result
+    }
+
+    /// Glob patterns of files in the version folder that are sources, from the version or the package.
+    /// None if every file is a source.
+    fn include_patterns<'a>(&'a self, pkg: &'a Package) -> Option<Vec<&'a str>> {
+        self.config
+            .include
+            .as_ref()
+            .or(pkg.config.include.as_ref())
+            .map(|patterns| patterns.iter().map(|x| x.as_str()).collect())
+    }
+
+    /// The sources that are included in the index: like [Self::sources], but files that would be installed to the
+    /// same path as an earlier file are skipped with a warning.
+    pub(crate) fn indexed_sources(&self, pkg: &Package) -> Result<Vec<Source>> {
+        let sources = self.sources(pkg)?;
+        let collisions = self.file_collisions(pkg, &sources);
+        for (dup, original) in collisions.iter() {
+            warnings::warn(
+                "file-collision",
+                sources[*dup].path(),
+                format!(
+                    "skipping {}, it would be installed to the same path as {}",
+                    sources[*dup].path().display(),
+                    sources[*original].path().display()
+                ),
+            );
+        }
+        Ok(sources
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !collisions.iter().any(|(dup, _)| dup == i))
+            .map(|(_, src)| src)
+            .collect())
+    }
+
+    /// The sources of this version: the files in the version folder (or the remote files, if the version is
+    /// hosted elsewhere), followed by the external sources. Files matching the `exclude` patterns, or not matching
+    /// the `include` patterns, are skipped.
+    pub(crate) fn sources(&self, pkg: &Package) -> Result<Vec<Source>> {
+        let mut sources: Vec<Source> = match &self.config.remote {
+            Some(remote) => remote
+                .files
+                .iter()
+                .map(|file| Source::read_remote(&file.to_logical_path(&self.path), &remote.url))
+                .collect(),
+            None => {
+                let exclude = build_globset(&self.exclude_patterns(pkg))?;
+                let include = match self.include_patterns(pkg) {
+                    Some(patterns) => Some(build_globset(&patterns)?),
+                    None => None,
+                };
+                let mut sources = Source::discover_sources(&self.path).unwrap_or_default();
+                // walkdir doesn't guarantee any order
+                sources.sort_by(|a, b| a.path.cmp(&b.path));
+                sources
+                    .into_iter()
+                    .filter(|src| {
+                        let relpath = src.relpath_from_version(self);
+                        !exclude.is_match(relpath.as_str())
+                            && include
+                                .as_ref()
+                                .is_none_or(|x| x.is_match(relpath.as_str()))
+                    })
+                    .collect()
             }
+        };
+        sources.extend(
+            self.config
+                .external
+                .iter()
+                .flatten()
+                .map(|external| Source::read_external(&self.path, external)),
+        );
+        if sources.is_empty() {
+            return Err(NoSourcesFound(self.path.clone()).into());
+        }
+        Ok(sources)
+    }
+
+    /// Find all version folders in the given package folder, i.e. subfolders with a version config.
+    pub(crate) fn discover_version_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+        discover_config_dirs(dir, Self::CONFIG_FILENAME)
+    }
 
+    fn discover_versions(dir: &Path) -> Result<Vec<Version>> {
+        let mut result = vec![];
+        for path in Self::discover_version_paths(dir)? {
             let pkg = match Version::read(&path) {
                 Ok(pkg) => pkg,
                 Err(err) => {
-                    warn!("failed to read version {} due to {}", path.display(), err);
+                    warnings::warn(
+                        "unreadable-version",
+                        &path,
+                        format!("failed to read version {} due to {}", path.display(), err),
+                    );
                     continue;
                 }
             };
@@ -675,44 +2106,184 @@ impl Version {
     fn element(&self, repo: &Repository, pkg: &Package) -> Result<XMLElement> {
         let mut version = XMLElement::new("version");
         version.add_attribute("name", &self.name());
-        version.add_attribute("author", pkg.author().unwrap_or(repo.author()));
+        version.add_attribute("author", self.resolved_author(repo, pkg));
         version.add_attribute("time", &self.time().to_rfc3339());
 
         // add changelog
-        if let Some(text) = &self.changelog()? {
+        let changelog = if repo.slim {
+            None
+        } else {
+            self.changelog_text(repo, pkg)?
+        };
+        if let Some(text) = &changelog {
+            if text.contains(templates::CHANGELOG_PLACEHOLDER.trim()) {
+                warnings::warn(
+                    "placeholder-changelog",
+                    &self.path.join("CHANGELOG.txt"),
+                    format!(
+                        "the changelog of version {} of {} still contains the placeholder text",
+                        self.name(),
+                        pkg.identifier()
+                    ),
+                );
+            }
             let mut changelog = XMLElement::new("changelog");
             changelog.add_text(cdata(text)).unwrap();
             version.add_child(changelog).unwrap();
         }
 
-        // add sources
-        let sources = self.sources()?;
-        for source in sources.iter() {
-            version.add_child(source.element(repo, pkg, self)?).unwrap();
+        // add sources, skipping files that would be installed to the same path as an earlier file
+        for source in self.indexed_sources(pkg)?.iter() {
+            for element in source.elements(repo, pkg, self)? {
+                version.add_child(element).unwrap();
+            }
         }
 
+        self.check_entrypoints(pkg, &self.sources(pkg)?)?;
+
+        Ok(version)
+    }
+
+    /// The changelog shown in the index, with the changelog URL appended if configured.
+    pub(crate) fn changelog_text(
+        &self,
+        repo: &Repository,
+        pkg: &Package,
+    ) -> Result<Option<String>> {
+        Ok(match (self.changelog()?, repo.changelog_url(pkg, self)?) {
+            (Some(text), Some(url)) => Some(format!("{}\n\n{}", text.trim_end(), url)),
+            (text, url) => text.or(url),
+        })
+    }
+
+    /// Find sources that would be installed to the same path as an earlier source.
+    /// Paths are compared case-insensitively, since REAPER may be installed on a case-insensitive filesystem.
+    /// Returns pairs of `(duplicate index, original index)`.
+    pub(crate) fn file_collisions(&self, pkg: &Package, sources: &[Source]) -> Vec<(usize, usize)> {
+        let paths: Vec<RelativePathBuf> = sources
+            .iter()
+            .map(|src| src.output_relpath_from_category(pkg, self))
+            .collect();
+        find_collisions(&paths)
+    }
+
+    /// Check that the entrypoints are valid for the given sources.
+    /// For script packages, there must be at least one entrypoint.
+    pub(crate) fn check_entrypoints(&self, pkg: &Package, sources: &[Source]) -> Result<()> {
+        for relpath in self.entrypoint_files(pkg).iter().flat_map(|x| x.keys()) {
+            let path = relpath.to_logical_path(&self.path);
+            if !sources.iter().any(|src| src.path() == path) {
+                return Err(EntrypointFileNotFound(self.path.clone(), relpath.clone()).into());
+            }
+        }
         // for script packages, check there is at least one entrypoint
-        {
-            let pkg_type = pkg.pkg_type();
-            if pkg_type == PackageType::Script {
-                let mut package_has_no_entrypoints = true;
-                for src in sources {
-                    let sections = src.sections(pkg, self)?;
-                    if !sections.is_empty() {
-                        package_has_no_entrypoints = false;
-                        break;
-                    }
-                }
-                if package_has_no_entrypoints {
-                    return Err(NoEntrypointsFoundForScriptPackage(pkg.path().into()).into());
+        if pkg.pkg_type() == PackageType::Script {
+            let mut package_has_no_entrypoints = true;
+            for src in sources {
+                let sections = src.sections(pkg, self)?;
+                if !sections.is_empty() {
+                    package_has_no_entrypoints = false;
+                    break;
                 }
             }
+            if package_has_no_entrypoints {
+                return Err(NoEntrypointsFoundForScriptPackage(pkg.path().into()).into());
+            }
         }
+        Ok(())
+    }
+}
 
-        Ok(version)
+/// The variables supported by URL patterns.
+const URL_VARIABLES: &[&str] = &[
+    "git_commit",
+    "git_commit_short",
+    "file_commit",
+    "git_tag",
+    "git_branch",
+    "relpath",
+    "package",
+    "version",
+    "filename",
+    "category",
+    "repo",
+];
+
+#[derive(Error, Debug)]
+pub(crate) enum InvalidUrlPattern {
+    #[error("invalid url_pattern in {0}: {1}")]
+    Syntax(PathBuf, String),
+    #[error("unknown variable `{{{1}}}` in url_pattern in {0}, the supported variables are: {2}")]
+    UnknownVariable(PathBuf, String, String),
+}
+
+/// Check that a URL pattern can be parsed, and only uses supported variables.
+pub(crate) fn check_url_pattern(
+    config_path: &Path,
+    pattern: &str,
+) -> Result<(), InvalidUrlPattern> {
+    let template = Template::parse(pattern)
+        .map_err(|err| InvalidUrlPattern::Syntax(config_path.into(), err.to_string()))?;
+    if let Some(key) = template.keys().find(|x| !URL_VARIABLES.contains(x)) {
+        return Err(InvalidUrlPattern::UnknownVariable(
+            config_path.into(),
+            key.to_string(),
+            URL_VARIABLES.join(", "),
+        ));
+    }
+    Ok(())
+}
+
+/// A parsed URL pattern, so that it can be rendered for every source without parsing it again.
+#[derive(Debug, Clone)]
+pub(crate) struct UrlTemplate(Vec<UrlTemplateItem>);
+
+#[derive(Debug, Clone)]
+enum UrlTemplateItem {
+    Text(String),
+    Key(String),
+}
+
+impl UrlTemplate {
+    fn parse(pattern: &str) -> Result<Self, leon::ParseError> {
+        let template = Template::parse(pattern)?;
+        Ok(Self(
+            template
+                .items
+                .iter()
+                .map(|item| match item {
+                    leon::Item::Text(text) => UrlTemplateItem::Text(text.to_string()),
+                    leon::Item::Key(key) => UrlTemplateItem::Key(key.to_string()),
+                })
+                .collect(),
+        ))
+    }
+
+    fn render(&self, values: &dyn Values) -> Result<String, leon::RenderError> {
+        let items: Vec<_> = self
+            .0
+            .iter()
+            .map(|item| match item {
+                UrlTemplateItem::Text(text) => leon::Item::Text(text),
+                UrlTemplateItem::Key(key) => leon::Item::Key(key),
+            })
+            .collect();
+        Template::new(&items, None).render(values)
     }
 }
 
+#[derive(Error, Debug)]
+pub(crate) enum UrlCommandError {
+    #[error("url_command is empty")]
+    Empty,
+    #[error("failed to launch url_command `{0}`: {1}")]
+    FailedToLaunch(String, String),
+    #[error("url_command failed for {0}: {1}")]
+    Failed(PathBuf, String),
+    #[error("url_command printed no URL for {0}")]
+    NoOutput(PathBuf),
+}
+
 struct UrlTemplateValueProvider<'a> {
     repo: &'a Repository,
     pkg: &'a Package,
@@ -723,19 +2294,59 @@ struct UrlTemplateValueProvider<'a> {
 impl Values for UrlTemplateValueProvider<'_> {
     fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
         match key {
-            "git_commit" => match self.repo.git_hash() {
+            "git_commit" => match self.repo.version_git_hash(self.pkg, self.ver) {
                 Ok(hash) => Some(hash.into()),
                 Err(err) => {
                     error!("failed to obtain URL variable `git_commit` due to {err}");
                     None
                 }
             },
+            "git_commit_short" => match self.repo.version_git_hash(self.pkg, self.ver) {
+                Ok(hash) => {
+                    let length = self.repo.git_commit_short_length().min(hash.len());
+                    Some(hash[..length].into())
+                }
+                Err(err) => {
+                    error!("failed to obtain URL variable `git_commit_short` due to {err}");
+                    None
+                }
+            },
+            "file_commit" => match self.repo.file_git_hash(self.src.path()) {
+                Ok(hash) => Some(hash.into()),
+                Err(err) => {
+                    error!("failed to obtain URL variable `file_commit` due to {err}");
+                    None
+                }
+            },
+            "git_tag" => match self.repo.git_tag() {
+                Ok(tag) => Some(url_encode_path(RelativePath::new(tag)).into()),
+                Err(err) => {
+                    error!("failed to obtain URL variable `git_tag` due to {err}");
+                    None
+                }
+            },
+            "git_branch" => match self.repo.git_branch() {
+                Ok(branch) => Some(url_encode_path(RelativePath::new(branch)).into()),
+                Err(err) => {
+                    error!("failed to obtain URL variable `git_branch` due to {err}");
+                    None
+                }
+            },
             "relpath" => {
                 // path of source, relative to root of repository
                 let source_relpath = self.src.path().relative_to(self.repo.path()).unwrap();
                 let encoded_path = url_encode_path(&source_relpath);
                 Some(encoded_path.into())
             }
+            "package" => Some(url_encode_path(RelativePath::new(&self.pkg.identifier())).into()),
+            "version" => Some(url_encode_path(RelativePath::new(&self.ver.name())).into()),
+            "filename" => {
+                // name of the source file, without its folders
+                let filename = self.src.path().file_name().unwrap().to_string_lossy();
+                Some(url_encode_path(RelativePath::new(&filename)).into())
+            }
+            "category" => Some(url_encode_path(self.pkg.category()).into()),
+            "repo" => Some(url_encode_path(RelativePath::new(&self.repo.identifier())).into()),
             _ => None,
         }
     }
@@ -745,31 +2356,82 @@ impl Values for UrlTemplateValueProvider<'_> {
 pub(crate) struct Source {
     path: PathBuf,
     sections: OnceCell<HashSet<ActionListSection>>,
+    /// Base URL of the version folder, if the version's files are hosted outside of the repository
+    remote_url: Option<String>,
+    /// The external source declared in the version config, if this source isn't a file in the version folder
+    external: Option<ExternalSource>,
 }
 
 impl Source {
     fn read(path: &Path) -> Self {
         debug_assert!(
-            path == path::absolute(&path).unwrap(),
+            path == path::absolute(path).unwrap(),
             "path = {} ; absolute(path) = {}",
             path.display(),
-            path::absolute(&path).unwrap().display()
+            path::absolute(path).unwrap().display()
         );
 
         Self {
             path: path.into(),
             sections: OnceCell::new(),
+            remote_url: None,
+            external: None,
+        }
+    }
+
+    /// A source file of a remote version, which doesn't exist on disk.
+    fn read_remote(path: &Path, url: &str) -> Self {
+        Self {
+            path: path.into(),
+            sections: OnceCell::new(),
+            remote_url: Some(url.into()),
+            external: None,
+        }
+    }
+
+    /// An external source of a version, which is downloaded from its own URL.
+    fn read_external(ver_path: &Path, external: &ExternalSource) -> Self {
+        Self {
+            path: external.file.to_logical_path(ver_path),
+            sections: OnceCell::new(),
+            remote_url: None,
+            external: Some(external.clone()),
         }
     }
 
+    /// Whether this source is declared in the `external` list of its version, instead of being a file.
+    pub(crate) fn is_external(&self) -> bool {
+        self.external.is_some()
+    }
+
+    /// Whether the index lists the hash of this source.
+    pub(crate) fn has_hash(&self, repo: &Repository) -> bool {
+        // remote and external files aren't on disk, so they can't be hashed
+        repo.source_hashes() && self.remote_url.is_none() && self.external.is_none()
+    }
+
     pub(crate) fn path(&self) -> &Path {
         &self.path
     }
 
-    fn url(&self, repo: &Repository, pkg: &Package, ver: &Version) -> Result<String> {
-        let url_pattern = repo.url_pattern();
-        // TODO: Find a way to not parse a new template from scratch for every source
-        let template = Template::parse(url_pattern)?;
+    pub(crate) fn url(&self, repo: &Repository, pkg: &Package, ver: &Version) -> Result<String> {
+        if let Some(external) = &self.external {
+            return Ok(external.url.clone());
+        }
+        if let Some(base) = &self.remote_url {
+            return Ok(format!(
+                "{}/{}",
+                base.trim_end_matches('/'),
+                url_encode_path(&self.relpath_from_version(ver))
+            ));
+        }
+        if let Some(command) = repo.url_command() {
+            // URL patterns of packages and versions take priority over the command of the repository
+            if pkg.config.url_pattern.is_none() && ver.config.url_pattern.is_none() {
+                return Ok(self.url_from_command(command, repo, pkg, ver)?);
+            }
+        }
+        let template = repo.url_template(pkg, ver)?;
         let values = UrlTemplateValueProvider {
             repo,
             pkg,
@@ -780,6 +2442,58 @@ impl Source {
         Ok(template.render(&values)?)
     }
 
+    /// Run the `url_command` of the repository, passing the details of this source as JSON on stdin.
+    fn url_from_command(
+        &self,
+        command: &[String],
+        repo: &Repository,
+        pkg: &Package,
+        ver: &Version,
+    ) -> Result<String, UrlCommandError> {
+        use std::{
+            io::Write,
+            process::{Command, Stdio},
+        };
+        let (program, args) = command.split_first().ok_or(UrlCommandError::Empty)?;
+        let relpath = self.path.relative_to(repo.path()).unwrap();
+        let input = serde_json::json!({
+            "path": self.path,
+            "relpath": relpath.as_str(),
+            "package": pkg.identifier(),
+            "version": ver.name(),
+            "filename": self.path.file_name().unwrap().to_string_lossy(),
+            "category": pkg.category().as_str(),
+            "repo": repo.identifier(),
+        });
+        let launch_error = |err: std::io::Error| {
+            UrlCommandError::FailedToLaunch(command.join(" "), err.to_string())
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(repo.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(launch_error)?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(input.to_string().as_bytes())
+            .map_err(launch_error)?;
+        let output = child.wait_with_output().map_err(launch_error)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(UrlCommandError::Failed(self.path.clone(), stderr));
+        }
+        let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if url.is_empty() {
+            return Err(UrlCommandError::NoOutput(self.path.clone()));
+        }
+        Ok(url)
+    }
+
     /// The relative path of this source file from its version folder.
     ///
     /// E.g. An absolute path like `"C:/index/my-package/0.0.1/foo/index.lua"` will return `"foo/index.lua"`
@@ -790,10 +2504,12 @@ impl Source {
     /// The desired output path of this source file, relative to the root of a folder. E.g. `"my-package/foo/index.lua"`
     ///
     /// Note: This does NOT consider the subfolders created by the package category. Use [Source::output_relpath_from_category] instead.
-    fn output_relpath(&self, pkg: &Package, ver: &Version) -> RelativePathBuf {
+    pub(crate) fn output_relpath(&self, pkg: &Package, ver: &Version) -> RelativePathBuf {
+        let relpath = self.relpath_from_version(ver);
+        let relpath = ver.target(&relpath).unwrap_or(relpath);
         let result = RelativePathBuf::from_path(pkg.identifier().as_ref())
             .expect("package identifier cannot be an absolute path")
-            .join(self.relpath_from_version(ver));
+            .join(relpath);
         debug_assert!(result == result.normalize());
         result
     }
@@ -808,10 +2524,14 @@ impl Source {
                     // skip directories
                     match entry.metadata() {
                         Err(e) => {
-                            warn!(
-                                "failed to get metadata for source {} due to {}",
-                                path.display(),
-                                e
+                            warnings::warn(
+                                "unreadable-source",
+                                path,
+                                format!(
+                                    "failed to get metadata for source {} due to {}",
+                                    path.display(),
+                                    e
+                                ),
                             );
                             return None;
                         }
@@ -825,7 +2545,11 @@ impl Source {
                     Some(Source::read(path))
                 }
                 Err(e) => {
-                    warn!("failed to read source {}", e);
+                    warnings::warn(
+                        "unreadable-source",
+                        e.path().unwrap_or(dir),
+                        format!("failed to read source {}", e),
+                    );
                     None
                 }
             })
@@ -839,7 +2563,11 @@ impl Source {
     }
 
     /// The 'file' attribute of the Element. A relative path from the Category folder to the source's target location. E.g. `"../my-package/foo/index.lua"`
-    fn output_relpath_from_category(&self, pkg: &Package, ver: &Version) -> RelativePathBuf {
+    pub(crate) fn output_relpath_from_category(
+        &self,
+        pkg: &Package,
+        ver: &Version,
+    ) -> RelativePathBuf {
         let mut result = RelativePathBuf::new();
         // prepend '..' for each segment in category
         for component in pkg.category().components() {
@@ -853,45 +2581,162 @@ impl Source {
             }
         }
         // push the normal expected output path
-        result.push(&self.output_relpath(pkg, ver));
+        result.push(self.output_relpath(pkg, ver));
         result
     }
 
-    fn element(&self, repo: &Repository, pkg: &Package, ver: &Version) -> Result<XMLElement> {
+    /// The `<source>` elements of this source: one for each of its `arch` platforms, or a single element.
+    fn elements(&self, repo: &Repository, pkg: &Package, ver: &Version) -> Result<Vec<XMLElement>> {
+        self.indexed_platforms(pkg, ver)?
+            .into_iter()
+            .map(|platform| self.element(repo, pkg, ver, platform))
+            .collect()
+    }
+
+    /// The platform of each entry of this source in the index: one for each of its `arch` platforms, or its single
+    /// `platform`.
+    pub(crate) fn indexed_platforms(
+        &self,
+        pkg: &Package,
+        ver: &Version,
+    ) -> Result<Vec<Option<Platform>>> {
+        let platforms = self.arch_platforms(ver)?;
+        if platforms.is_empty() {
+            return Ok(vec![self.platform(pkg, ver)?]);
+        }
+        Ok(platforms.into_iter().map(Some).collect())
+    }
+
+    fn element(
+        &self,
+        repo: &Repository,
+        pkg: &Package,
+        ver: &Version,
+        platform: Option<Platform>,
+    ) -> Result<XMLElement> {
         let mut source = XMLElement::new("source");
-        source.add_text(self.url(repo, pkg, ver)?).unwrap();
+        // URLs from `url_command` or external sources may contain query strings
+        source
+            .add_text(xml_escape(&self.url(repo, pkg, ver)?))
+            .unwrap();
         source.add_attribute("file", self.output_relpath_from_category(pkg, ver).as_ref());
 
-        // TODO: Implement setting "type" attribute
-        // https://github.com/cfillion/reapack/wiki/Index-Format#source-element
+        if let Some(typ) = self.source_type(pkg, ver)? {
+            source.add_attribute("type", (&typ).into());
+        }
+
+        if let Some(platform) = platform {
+            source.add_attribute("platform", (&platform).into());
+        }
+
+        if self.has_hash(repo) {
+            let (hash, _) = hash_file(&self.path)?;
+            source.add_attribute("hash", &sha256_multihash(&hash));
+        }
 
         let sections = self.sections(pkg, ver)?;
 
         if !sections.is_empty() {
-            let sections = sections.iter().map(Into::<&str>::into).join(" ");
-            source.add_attribute("main", &sections);
+            if repo.legacy_main_attribute() && !sections.contains(&ActionListSection::Main) {
+                warnings::warn(
+                    "legacy-main-section",
+                    self.path(),
+                    "source is not registered in the main section, older ReaPack clients will not register it"
+                        .into(),
+                );
+            }
+            source.add_attribute(
+                "main",
+                &main_attribute(sections, repo.legacy_main_attribute()),
+            );
         }
 
         Ok(source)
     }
 
-    fn sections(&self, pkg: &Package, ver: &Version) -> Result<&HashSet<ActionListSection>> {
+    /// The type of this source if it differs from the package type, from the `types` patterns of its version or
+    /// package.
+    pub(crate) fn source_type(&self, pkg: &Package, ver: &Version) -> Result<Option<PackageType>> {
+        if let Some(typ) = self.external.as_ref().and_then(|x| x.src_type.as_ref()) {
+            return Ok(Some(typ.clone()).filter(|x| *x != pkg.pkg_type()));
+        }
+        let Some(patterns) = ver.type_patterns(pkg) else {
+            return Ok(None);
+        };
+        let types = match_patterns(patterns, self.relpath_from_version(ver).as_str())?;
+        match types.as_slice() {
+            [] => Ok(None),
+            [typ] if *typ == pkg.pkg_type() => Ok(None),
+            [typ] => Ok(Some(typ.clone())),
+            [a, b, ..] => Err(ConflictingSourceTypes(
+                self.path.clone(),
+                <&str>::from(a).into(),
+                <&str>::from(b).into(),
+            )
+            .into()),
+        }
+    }
+
+    /// The platforms of this source from the `arch` patterns of its version, which need a `<source>` element each.
+    /// Empty if no pattern matches.
+    pub(crate) fn arch_platforms(&self, ver: &Version) -> Result<Vec<Platform>> {
+        let Some(patterns) = &ver.config.arch else {
+            return Ok(vec![]);
+        };
+        let mut result: Vec<Platform> = vec![];
+        for platforms in match_patterns(patterns, self.relpath_from_version(ver).as_str())? {
+            for platform in platforms {
+                if !result.contains(&platform) {
+                    result.push(platform);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// The platform of this source, from the `platform` patterns of its version or package.
+    pub(crate) fn platform(&self, pkg: &Package, ver: &Version) -> Result<Option<Platform>> {
+        if let Some(platform) = self.external.as_ref().and_then(|x| x.platform) {
+            return Ok(Some(platform));
+        }
+        let Some(patterns) = ver.platform_patterns(pkg) else {
+            return Ok(None);
+        };
+        let platforms = match_patterns(patterns, self.relpath_from_version(ver).as_str())?;
+        match platforms.as_slice() {
+            [] => Ok(None),
+            [platform] => Ok(Some(*platform)),
+            [a, b, ..] => Err(ConflictingPlatforms(
+                self.path.clone(),
+                <&str>::from(a).into(),
+                <&str>::from(b).into(),
+            )
+            .into()),
+        }
+    }
+
+    pub(crate) fn sections(
+        &self,
+        pkg: &Package,
+        ver: &Version,
+    ) -> Result<&HashSet<ActionListSection>> {
         self.sections.get_or_try_init(|| {
             let entrypoints = ver.entrypoints(pkg)?;
+            let files = ver.entrypoint_files(pkg);
+            let has_entrypoints = entrypoints
+                .is_some_and(|x| x.iter().any(|(_, pattern)| !pattern.is_empty()))
+                || files.is_some_and(|x| x.values().any(|sections| !sections.is_empty()));
             let pkg_type = pkg.pkg_type();
-            if pkg_type == PackageType::Script {
-                let Some(entrypoints) = entrypoints else {
-                    return Err(NoEntrypointsDefinedForScriptPackage(pkg.path().into()).into());
-                };
-                if entrypoints.iter().all(|(_, pattern)| pattern.is_empty()) {
-                    return Err(NoEntrypointsDefinedForScriptPackage(pkg.path().into()).into());
-                }
-            } else if let Some(entrypoints) = entrypoints {
-                if entrypoints.iter().any(|(_, pattern)| !pattern.is_empty()) {
-                    return Err(EntrypointsOnlyAllowedInScriptPackages(pkg.path().into()).into());
-                }
+            if pkg_type == PackageType::Script && !has_entrypoints {
+                return Err(NoEntrypointsDefinedForScriptPackage(pkg.path().into()).into());
+            } else if pkg_type != PackageType::Script && has_entrypoints {
+                return Err(EntrypointsOnlyAllowedInScriptPackages(pkg.path().into()).into());
             }
             let relpath_to_ver = self.relpath_from_version(ver);
+            // explicitly listed files ignore the glob patterns
+            if let Some(sections) = files.and_then(|x| x.get(&relpath_to_ver)) {
+                return Ok(sections.iter().copied().collect());
+            }
             let sections = match entrypoints {
                 Some(entrypoints) => entrypoints
                     .iter()
@@ -932,10 +2777,308 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn xml_escape_01() {
+        assert_eq!(
+            xml_escape("https://example.com/a.lua?x=1&y=<2>"),
+            "https://example.com/a.lua?x=1&amp;y=&lt;2&gt;"
+        );
+    }
+
     #[test]
     fn cdata_03() {
         let result = cdata("app]]>le");
         let expected = "<![CDATA[app]]]]><![CDATA[>le]]>";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn plain_text_rtf_01() {
+        let result = plain_text_rtf("# Title\n\nUse {x}\n");
+        assert_eq!(
+            result,
+            "{\\rtf1\\ansi\n# Title\\line \\line Use \\{x\\}\n}\n"
+        );
+    }
+
+    #[test]
+    fn rtf_append_paragraph_01() {
+        let result = rtf_append_paragraph("{\\rtf1\\ansi\nHello\\par\n}\n", "Use {x} — not y");
+        let expected =
+            "{\\rtf1\\ansi\nHello\\par\n{\\pard \\b Use \\{x\\} \\u8212? not y\\b0\\par}\n}";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn render_placeholders_01() {
+        let result = render_placeholders(
+            "# {identifier} v{latest_version}\n\n```lua\nlocal t = {x = 1}\n```\n{unknown}",
+            &[("identifier", "my-package"), ("latest_version", "1.2.0")],
+        );
+        let expected = "# my-package v1.2.0\n\n```lua\nlocal t = {x = 1}\n```\n{unknown}";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn render_placeholders_02() {
+        // placeholders in values are kept as-is
+        let result = render_placeholders(
+            "{description} by {author}{",
+            &[("description", "Uses {author}"), ("author", "Me")],
+        );
+        assert_eq!(result, "Uses {author} by Me{");
+    }
+
+    #[test]
+    fn find_config_named_01() {
+        let names = vec![
+            ".reapack-indexer.toml".to_string(),
+            "repository.toml".into(),
+        ];
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("git/.git")).unwrap();
+        fs::create_dir_all(root.join("git/own")).unwrap();
+        fs::create_dir_all(root.join("git/nested/repo")).unwrap();
+        fs::create_dir_all(root.join("plain/repo")).unwrap();
+        fs::write(root.join("git/.reapack-indexer.toml"), "").unwrap();
+        fs::write(root.join("git/own/repository.toml"), "").unwrap();
+        fs::write(root.join("plain/.reapack-indexer.toml"), "").unwrap();
+
+        // the folder's own config wins over a dotfile in a parent folder
+        assert_eq!(
+            Repository::find_config_named(&root.join("git/own"), &names),
+            Some(root.join("git/own/repository.toml"))
+        );
+        assert_eq!(
+            Repository::find_config_named(&root.join("git/nested/repo"), &names),
+            Some(root.join("git/.reapack-indexer.toml"))
+        );
+        // parent folders are only searched in a git repository
+        assert_eq!(
+            Repository::find_config_named(&root.join("plain/repo"), &names),
+            None
+        );
+    }
+
+    #[test]
+    fn sort_categories_01() {
+        let repo = Repository {
+            path: PathBuf::new(),
+            config: RepositoryConfig {
+                identifier: None,
+                author: "Author".into(),
+                url_pattern: "{relpath}".into(),
+                url_command: None,
+                pandoc_path: None,
+                category_order: Some(vec!["Featured".into(), "Utility".into()]),
+                pin_git_commits: None,
+                git_commit_short_length: None,
+                changelog_url_pattern: None,
+                authors: None,
+                output_path: None,
+                lint_changelogs: None,
+                exclude_deprecated: None,
+                legacy_main_attribute: None,
+                source_hashes: None,
+                min_reapack_version: None,
+                max_versions_in_index: None,
+                xml_version: None,
+                xml_indent: None,
+                xml_sort_attributes: None,
+                website: None,
+                donation: None,
+                screenshot: None,
+                storage: None,
+            },
+            state: ExportState::default(),
+            git_hash: OnceCell::new(),
+            git_tag: OnceCell::new(),
+            git_branch: OnceCell::new(),
+            git_file_commits: OnceCell::new(),
+            slim: false,
+            url_pattern_override: None,
+            url_template: OnceCell::new(),
+        };
+        let mut categories: Vec<&RelativePath> = vec![
+            "MIDI".as_ref(),
+            "Utility".as_ref(),
+            "Audio".as_ref(),
+            "Featured".as_ref(),
+        ];
+        repo.sort_categories(&mut categories);
+        let expected: Vec<&RelativePath> = vec![
+            "Featured".as_ref(),
+            "Utility".as_ref(),
+            "Audio".as_ref(),
+            "MIDI".as_ref(),
+        ];
+        assert_eq!(categories, expected);
+    }
+
+    #[test]
+    fn compare_version_names_01() {
+        use std::cmp::Ordering::*;
+        assert_eq!(Version::compare_version_names("0.0.10", "0.0.9"), Greater);
+        assert_eq!(Version::compare_version_names("1.2", "1.10"), Less);
+        assert_eq!(Version::compare_version_names("2.0", "10.0"), Less);
+        assert_eq!(Version::compare_version_names("1.0.0", "1.0"), Greater);
+        assert_eq!(Version::compare_version_names("1.0", "1.0"), Equal);
+        // segments that aren't numbers are compared as text
+        assert_eq!(
+            Version::compare_version_names("1.0beta", "1.0alpha"),
+            Greater
+        );
+        assert_eq!(Version::compare_version_names("1.a", "1.1"), Greater);
+    }
+
+    #[test]
+    fn compare_version_names_02() {
+        use std::cmp::Ordering::*;
+        // a number followed by text must still be ordered consistently with plain numbers
+        assert_eq!(Version::compare_version_names("1.9", "1.10"), Less);
+        assert_eq!(Version::compare_version_names("1.10", "1.10b"), Less);
+        assert_eq!(Version::compare_version_names("1.9", "1.10b"), Less);
+        assert_eq!(Version::compare_version_names("1.010", "1.10"), Less);
+
+        let expected = vec!["1.9", "1.10", "1.10b"];
+        for mut names in [
+            vec!["1.10b", "1.9", "1.10"],
+            vec!["1.10", "1.10b", "1.9"],
+            vec!["1.9", "1.10b", "1.10"],
+        ] {
+            names.sort_by(|a, b| Version::compare_version_names(a, b));
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    fn retain_newest_01() {
+        let version = |name: &str| Version {
+            path: Path::new("/repo/my-package").join(name),
+            config: VersionConfig {
+                time: chrono::Utc::now(),
+                entrypoints: None,
+                entrypoint_files: None,
+                author: None,
+                url_pattern: None,
+                yanked: None,
+                remote: None,
+                external: None,
+                targets: None,
+                exclude: None,
+                include: None,
+                arch: None,
+                platform: None,
+                types: None,
+            },
+            entrypoints: OnceCell::new(),
+            url_template: OnceCell::new(),
+        };
+        let versions = vec![
+            version("0.0.9"),
+            version("0.0.10"),
+            version("0.0.1"),
+            version("0.1.0"),
+        ];
+        let result = Version::retain_newest(versions, 2);
+        let names: Vec<_> = result.iter().map(|ver| ver.name()).collect();
+        assert_eq!(names, vec!["0.0.10", "0.1.0"]);
+    }
+
+    #[test]
+    fn find_collisions_01() {
+        let paths: Vec<RelativePathBuf> = vec![
+            "../pkg/a.lua".into(),
+            "../pkg/b.lua".into(),
+            "../pkg/A.lua".into(),
+            "../pkg/sub/../b.lua".into(),
+        ];
+        assert_eq!(find_collisions(&paths), vec![(2, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn main_attribute_01() {
+        let main = HashSet::from([ActionListSection::Main]);
+        let midi = HashSet::from([ActionListSection::MIDIEditor]);
+        assert_eq!(main_attribute(&main, false), "main");
+        assert_eq!(main_attribute(&main, true), "true");
+        assert_eq!(main_attribute(&midi, true), "midi_editor");
+        let both = HashSet::from([
+            ActionListSection::MIDIEditor,
+            ActionListSection::Main,
+            ActionListSection::MediaExplorer,
+        ]);
+        assert_eq!(
+            main_attribute(&both, true),
+            "main mediaexplorer midi_editor"
+        );
+    }
+
+    #[test]
+    fn match_patterns_01() {
+        let patterns = BTreeMap::from([
+            ("*.dll".to_string(), Platform::Windows),
+            ("*-x64.dll".to_string(), Platform::Win64),
+            ("*.dylib".to_string(), Platform::Darwin),
+        ]);
+        assert_eq!(
+            match_patterns(&patterns, "reaper_ext.dll").unwrap(),
+            vec![Platform::Windows]
+        );
+        assert_eq!(
+            match_patterns(&patterns, "reaper_ext-x64.dll").unwrap(),
+            vec![Platform::Win64, Platform::Windows]
+        );
+        assert!(match_patterns(&patterns, "lib/reaper_ext.dylib")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn sha256_multihash_01() {
+        let digest = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(
+            sha256_multihash(digest),
+            "1220e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn check_url_pattern_01() {
+        let path = Path::new("repository.toml");
+        assert!(check_url_pattern(path, "https://example.com/{git_commit}/{relpath}").is_ok());
+        assert!(matches!(
+            check_url_pattern(path, "https://example.com/{git_comit}/{relpath}"),
+            Err(InvalidUrlPattern::UnknownVariable(_, key, _)) if key == "git_comit"
+        ));
+        assert!(matches!(
+            check_url_pattern(path, "https://example.com/{relpath"),
+            Err(InvalidUrlPattern::Syntax(..))
+        ));
+    }
+
+    #[test]
+    fn local_url_pattern_01() {
+        assert_eq!(
+            local_url_pattern(Path::new("/home/me/my repo")),
+            "file:///home/me/my%20repo/{relpath}"
+        );
+    }
+
+    #[test]
+    fn parse_file_commits_01() {
+        let log =
+            "commit:bbb\n\npkg/1.0/a.lua\n\ncommit:aaa\n\npkg/1.0/a.lua\npkg/1.0/My file.lua\n";
+        let commits = parse_file_commits(log);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[RelativePath::new("pkg/1.0/a.lua")], "bbb");
+        assert_eq!(commits[RelativePath::new("pkg/1.0/My file.lua")], "aaa");
+    }
+
+    #[test]
+    fn find_case_duplicates_01() {
+        let names = ["1.0.0b", "1.0.0", "1.0.0B", "1.0.1"];
+        assert_eq!(find_case_duplicates(&names), vec![(2, 0)]);
+    }
 }
@@ -0,0 +1,221 @@
+//! A summarized "report card" of a repository, listing what should be improved first.
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+
+use crate::{
+    repo::Repository,
+    stats::format_table,
+    validate::{self, Severity},
+};
+
+/// Packages whose latest version is older than this are not considered recently released.
+const RECENT_RELEASE_DAYS: i64 = 365;
+
+/// How many packages to name for each issue.
+const LISTED_PACKAGES_COUNT: usize = 5;
+
+/// A property that every package should have.
+struct Check {
+    name: &'static str,
+    /// Describes the packages that fail this check, e.g. "packages without a README"
+    issue: &'static str,
+    /// Identifiers of the packages that fail this check
+    failing: Vec<String>,
+}
+
+pub(crate) struct Report {
+    packages: usize,
+    checks: Vec<Check>,
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    pub(crate) fn generate(repo: &Repository) -> Result<Self> {
+        let mut checks = vec![
+            Check {
+                name: "Description",
+                issue: "packages without a README or `@about` header",
+                failing: vec![],
+            },
+            Check {
+                name: "Changelog",
+                issue: "packages whose latest version has no changelog",
+                failing: vec![],
+            },
+            Check {
+                name: "Recent release",
+                issue: "packages without a release in the last year",
+                failing: vec![],
+            },
+            Check {
+                name: "Screenshots",
+                issue: "packages without a screenshot",
+                failing: vec![],
+            },
+            Check {
+                name: "Hashes",
+                issue: "packages whose latest version has sources without a hash",
+                failing: vec![],
+            },
+        ];
+
+        let recent = Utc::now() - Duration::days(RECENT_RELEASE_DAYS);
+        let mut packages = repo.packages()?;
+        packages.sort_by_key(|pkg| pkg.identifier().to_string());
+        for pkg in packages.iter() {
+            let latest = pkg.latest_released_version()?;
+            let results = [
+                pkg.has_description()?,
+                match &latest {
+                    Some(ver) => ver.changelog()?.is_some_and(|x| !x.trim().is_empty()),
+                    None => false,
+                },
+                latest.as_ref().is_some_and(|ver| ver.time() > recent),
+                !pkg.screenshots().is_empty(),
+                match &latest {
+                    Some(ver) => ver.sources(pkg)?.iter().all(|src| src.has_hash(repo)),
+                    None => false,
+                },
+            ];
+            for (check, passed) in checks.iter_mut().zip(results) {
+                if !passed {
+                    check.failing.push(pkg.identifier().to_string());
+                }
+            }
+        }
+
+        let problems = validate::validate(repo)?;
+        Ok(Self {
+            packages: packages.len(),
+            checks,
+            errors: problems.count(Severity::Error),
+            warnings: problems.count(Severity::Warning),
+        })
+    }
+
+    /// Percentage of packages that pass a check.
+    fn percentage(&self, check: &Check) -> f64 {
+        if self.packages == 0 {
+            return 100.0;
+        }
+        100.0 * (self.packages - check.failing.len()) as f64 / self.packages as f64
+    }
+
+    /// The overall score out of 100, the average of all checks. Validation errors halve the score.
+    pub(crate) fn score(&self) -> f64 {
+        let total: f64 = self.checks.iter().map(|x| self.percentage(x)).sum();
+        let score = total / self.checks.len() as f64;
+        if self.errors > 0 {
+            score / 2.0
+        } else {
+            score
+        }
+    }
+
+    pub(crate) fn print(&self) {
+        let rows: Vec<Vec<String>> = self
+            .checks
+            .iter()
+            .map(|check| {
+                vec![
+                    check.name.to_string(),
+                    format!("{:.0}%", self.percentage(check)),
+                    format!("{}/{}", self.packages - check.failing.len(), self.packages),
+                ]
+            })
+            .collect();
+        println!("{}", format_table(&["Check", "Score", "Packages"], &rows));
+        println!();
+        println!(
+            "Validation: {} error(s), {} warning(s)",
+            self.errors, self.warnings
+        );
+        println!("Overall score: {:.0}/100", self.score());
+
+        let issues = self.issues();
+        if issues.is_empty() {
+            return;
+        }
+        println!();
+        println!("Top issues to fix:");
+        for (i, issue) in issues.iter().enumerate() {
+            println!("  {}. {}", i + 1, issue);
+        }
+    }
+
+    /// Issues ordered by priority: validation errors first, then the checks failed by the most packages.
+    fn issues(&self) -> Vec<String> {
+        let mut result = vec![];
+        if self.errors > 0 {
+            result.push(format!(
+                "{} validation error(s), run `check` for details",
+                self.errors
+            ));
+        }
+        let mut checks: Vec<&Check> = self
+            .checks
+            .iter()
+            .filter(|x| !x.failing.is_empty())
+            .collect();
+        checks.sort_by_key(|x| std::cmp::Reverse(x.failing.len()));
+        for check in checks {
+            let mut listed =
+                check.failing[..check.failing.len().min(LISTED_PACKAGES_COUNT)].join(", ");
+            if check.failing.len() > LISTED_PACKAGES_COUNT {
+                listed.push_str(&format!(
+                    ", and {} more",
+                    check.failing.len() - LISTED_PACKAGES_COUNT
+                ));
+            }
+            result.push(format!(
+                "{} {}: {}",
+                check.failing.len(),
+                check.issue,
+                listed
+            ));
+        }
+        if self.warnings > 0 {
+            result.push(format!(
+                "{} validation warning(s), run `check` for details",
+                self.warnings
+            ));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_01() {
+        let report = Report {
+            packages: 4,
+            checks: vec![
+                Check {
+                    name: "a",
+                    issue: "a",
+                    failing: vec![],
+                },
+                Check {
+                    name: "b",
+                    issue: "b",
+                    failing: vec!["x".into(), "y".into()],
+                },
+            ],
+            errors: 0,
+            warnings: 1,
+        };
+        assert_eq!(report.score(), 75.0);
+        assert_eq!(
+            report.issues(),
+            vec![
+                "2 b: x, y".to_string(),
+                "1 validation warning(s), run `check` for details".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Search packages by their identifier, name, category and README.
+
+use std::fs;
+
+use anyhow::Result;
+
+use crate::repo::{Package, Repository};
+
+/// The text of a package that is searched, in lowercase.
+struct SearchFields {
+    identifier: String,
+    name: String,
+    category: String,
+    readme: String,
+}
+
+impl SearchFields {
+    fn read(pkg: &Package) -> Result<Self> {
+        // RTF readmes are searched as-is, converting them would need pandoc
+        let readme = match pkg.readme_path() {
+            Some(path) => fs::read_to_string(path)?,
+            None => String::new(),
+        };
+        Ok(Self {
+            identifier: pkg.identifier().to_lowercase(),
+            name: pkg.name().to_lowercase(),
+            category: pkg.category().as_str().to_lowercase(),
+            readme: readme.to_lowercase(),
+        })
+    }
+}
+
+/// Score a package against the search terms, or None if any term doesn't match.
+/// Matches in the identifier and name rank higher than matches in the category, which rank higher than the README.
+/// Returns the score and the names of the matched fields.
+fn score(fields: &SearchFields, terms: &[String]) -> Option<(u32, Vec<&'static str>)> {
+    let mut total = 0;
+    let mut matched = vec![];
+    for term in terms {
+        let mut term_score = 0;
+        let mut add = |score: u32, field: &'static str| {
+            term_score += score;
+            if !matched.contains(&field) {
+                matched.push(field);
+            }
+        };
+        if fields.identifier == *term {
+            add(100, "identifier");
+        } else if fields.identifier.contains(term.as_str()) {
+            add(40, "identifier");
+        }
+        if fields.name == *term {
+            add(80, "name");
+        } else if fields.name.contains(term.as_str()) {
+            add(30, "name");
+        }
+        if fields.category.contains(term.as_str()) {
+            add(15, "category");
+        }
+        // repeated mentions in the README count a little, up to a limit
+        let readme_count = fields.readme.matches(term.as_str()).count().min(5) as u32;
+        if readme_count > 0 {
+            add(2 * readme_count, "readme");
+        }
+        if term_score == 0 {
+            return None;
+        }
+        total += term_score;
+    }
+    Some((total, matched))
+}
+
+pub(crate) struct SearchResult {
+    pub(crate) pkg: Package,
+    pub(crate) score: u32,
+    /// Names of the fields that matched the query
+    pub(crate) matched: Vec<&'static str>,
+}
+
+/// Find the packages matching every word of the query, best matches first.
+pub(crate) fn search(repo: &Repository, query: &str) -> Result<Vec<SearchResult>> {
+    let terms: Vec<String> = query.split_whitespace().map(|x| x.to_lowercase()).collect();
+    let mut results = vec![];
+    for pkg in repo.packages()? {
+        let fields = SearchFields::read(&pkg)?;
+        if let Some((score, matched)) = score(&fields, &terms) {
+            results.push(SearchResult {
+                pkg,
+                score,
+                matched,
+            });
+        }
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.pkg.identifier().cmp(&b.pkg.identifier()))
+    });
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_01() {
+        let fields = SearchFields {
+            identifier: "midi-tools".into(),
+            name: "midi tools".into(),
+            category: "midi editor".into(),
+            readme: "quantize notes. quantize everything.".into(),
+        };
+        let terms = |x: &str| x.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let (midi, matched) = score(&fields, &terms("midi")).unwrap();
+        assert_eq!(matched, vec!["identifier", "name", "category"]);
+        let (quantize, matched) = score(&fields, &terms("quantize")).unwrap();
+        assert_eq!(matched, vec!["readme"]);
+        assert!(midi > quantize);
+        assert!(score(&fields, &terms("midi humanize")).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{repo::Repository, warnings};
+
+#[derive(Error, Debug)]
+#[error("failed to start server on {0}: {1}")]
+pub(crate) struct FailedToStartServer(String, String);
+
+/// Serve the repository over HTTP, generating the index on every request.
+/// Source URLs in the index point back at this server, so the repository can be added to ReaPack for testing.
+pub(crate) fn serve(repo_path: &Path, port: u16) -> Result<()> {
+    let address = format!("127.0.0.1:{port}");
+    let server = Server::http(&address)
+        .map_err(|err| FailedToStartServer(address.clone(), err.to_string()))?;
+
+    // read the repository once to fail early if it is invalid
+    let repo_path = Repository::read(repo_path)?.path().to_path_buf();
+    println!("Serving {} at http://{}/", repo_path.display(), address);
+    println!("Add this URL to ReaPack: http://{}/index.xml", address);
+
+    for request in server.incoming_requests() {
+        let url = request.url().to_string();
+        let result = if url == "/" || url == "/index.xml" {
+            respond_with_index(request, &repo_path, &address)
+        } else {
+            respond_with_file(request, &repo_path, &url)
+        };
+        if let Err(err) = result {
+            log::error!("failed to respond to request for {url}: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+fn respond_with_index(request: Request, repo_path: &Path, address: &str) -> Result<()> {
+    let index = Repository::read(repo_path).and_then(|mut repo| {
+        repo.set_url_pattern(format!("http://{address}/{{relpath}}"));
+        repo.generate_index()
+    });
+    // show warnings again on the next request
+    warnings::take();
+
+    match index {
+        Ok(index) => {
+            let header = Header::from_bytes("Content-Type", "application/xml; charset=utf-8")
+                .expect("header is valid");
+            request.respond(Response::from_string(index).with_header(header))?;
+        }
+        Err(err) => {
+            log::error!("failed to generate index: {err}");
+            request.respond(Response::from_string(err.to_string()).with_status_code(500))?;
+        }
+    }
+    Ok(())
+}
+
+fn respond_with_file(request: Request, repo_path: &Path, url: &str) -> Result<()> {
+    match resolve_path(repo_path, url) {
+        Some(path) => {
+            let file = fs::File::open(path)?;
+            request.respond(Response::from_file(file))?;
+        }
+        None => request.respond(Response::from_string("not found").with_status_code(404))?,
+    }
+    Ok(())
+}
+
+/// Whether any component of the path starts with a dot, e.g. `.git/config` or `.env`.
+fn is_hidden(relpath: &Path) -> bool {
+    relpath
+        .components()
+        .any(|x| x.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Convert a request URL to a file within the repository.
+/// Returns None if the file does not exist, is outside of the repository, or is hidden (like `.git`).
+fn resolve_path(repo_path: &Path, url: &str) -> Option<PathBuf> {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let relpath = percent_decode_str(url.trim_start_matches('/'))
+        .decode_utf8()
+        .ok()?;
+    if is_hidden(Path::new(relpath.as_ref())) {
+        return None;
+    }
+    let path = fs::canonicalize(repo_path.join(relpath.as_ref())).ok()?;
+    let repo_path = fs::canonicalize(repo_path).ok()?;
+    // symlinks may point into a hidden folder
+    if is_hidden(path.strip_prefix(&repo_path).ok()?) || !path.is_file() {
+        return None;
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_hidden_01() {
+        assert!(is_hidden(Path::new(".git/config")));
+        assert!(is_hidden(Path::new("pkg/.env")));
+        assert!(is_hidden(Path::new("../outside.txt")));
+        assert!(!is_hidden(Path::new("pkg/0.0.1/My script.lua")));
+        assert!(!is_hidden(Path::new("index.xml")));
+    }
+}
@@ -0,0 +1,182 @@
+//! Set fields of a configuration file from the command line, keeping its comments and formatting.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::repo::check_url_pattern;
+
+#[derive(Error, Debug)]
+#[error("invalid assignment `{0}`, expected `key=value`")]
+pub(crate) struct InvalidAssignment(String);
+
+#[derive(Error, Debug)]
+#[error("unknown field `{0}`")]
+pub(crate) struct UnknownField(String);
+
+#[derive(Error, Debug)]
+#[error("`{0}` is not a table")]
+pub(crate) struct NotATable(String);
+
+/// Parse `key=value`. Keys can be dotted to set a field in a table, e.g. `entrypoints.main=["a.lua"]`.
+/// Values are parsed as TOML, and anything that isn't valid TOML is used as a string, so `author=Jane` works
+/// without quotes. Values that parse as something else are retried as strings by [set_fields] if the field is a
+/// string, so `name=1.2` stays a string.
+fn parse_assignment(text: &str) -> Result<(Vec<String>, Value), InvalidAssignment> {
+    let Some((key, value)) = text.split_once('=') else {
+        return Err(InvalidAssignment(text.into()));
+    };
+    let key: Vec<String> = key
+        .trim()
+        .split('.')
+        .map(|x| x.trim().to_string())
+        .collect();
+    if key.iter().any(|x| x.is_empty()) {
+        return Err(InvalidAssignment(text.into()));
+    }
+    let value = value.trim();
+    let value = value
+        .parse::<Value>()
+        .unwrap_or_else(|_| Value::from(value));
+    Ok((key, value.decorated(" ", "")))
+}
+
+/// Set the value at the given key path, creating tables as needed.
+fn set_value(doc: &mut DocumentMut, key: &[String], value: Value) -> Result<()> {
+    let (last, parents) = key.split_last().expect("keys are never empty");
+    let mut table: &mut Table = doc.as_table_mut();
+    for (i, part) in parents.iter().enumerate() {
+        let item = table
+            .entry(part)
+            .or_insert_with(|| Item::Table(Table::new()));
+        table = item
+            .as_table_mut()
+            .ok_or_else(|| NotATable(key[..=i].join(".")))?;
+    }
+    let mut value = value;
+    // keep comments after the old value
+    if let Some(old) = table.get(last).and_then(|x| x.as_value()) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table[last.as_str()] = Item::Value(value);
+    Ok(())
+}
+
+/// Whether the key path exists in the value.
+fn has_key(value: &toml::Value, key: &[String]) -> bool {
+    let mut value = value;
+    for part in key {
+        match value.get(part.as_str()) {
+            Some(x) => value = x,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The value as a string, using the text that was typed for it, or None if it already is a string.
+fn as_string_value(value: &Value) -> Option<Value> {
+    if value.is_str() {
+        return None;
+    }
+    let text = value.to_string();
+    Some(Value::from(text.trim()).decorated(" ", ""))
+}
+
+/// Apply the assignments to a config file. The result is checked against the config type `T` before anything is
+/// written, so invalid values and unknown fields are rejected. The URL pattern is checked too, if there is one.
+pub(crate) fn set_fields<T: DeserializeOwned + Serialize>(
+    path: &Path,
+    assignments: &[String],
+) -> Result<()> {
+    let mut doc: DocumentMut = fs::read_to_string(path)?.parse()?;
+    let assignments = assignments
+        .iter()
+        .map(|x| parse_assignment(x))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (key, value) in assignments.iter() {
+        set_value(&mut doc, key, value.clone())?;
+        // use the value as a string instead if that's what the field expects, e.g. `name=1.2`
+        if toml::from_str::<T>(&doc.to_string()).is_err() {
+            if let Some(string) = as_string_value(value) {
+                let mut retry = doc.clone();
+                set_value(&mut retry, key, string)?;
+                if toml::from_str::<T>(&retry.to_string()).is_ok() {
+                    doc = retry;
+                }
+            }
+        }
+    }
+
+    let text = doc.to_string();
+    let config: T = toml::from_str(&text)?;
+    // unknown fields are ignored when reading, so they are missing when the config is written back
+    let written = toml::Value::try_from(&config)?;
+    for (key, _) in assignments.iter() {
+        if !has_key(&written, key) {
+            return Err(UnknownField(key.join(".")).into());
+        }
+    }
+    if let Some(url_pattern) = written.get("url_pattern").and_then(|x| x.as_str()) {
+        check_url_pattern(path, url_pattern)?;
+    }
+
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_assignment_01() {
+        let (key, value) = parse_assignment("author=Jane Doe").unwrap();
+        assert_eq!(key, vec!["author"]);
+        assert_eq!(value.as_str(), Some("Jane Doe"));
+
+        let (key, value) = parse_assignment("entrypoints.main = [\"a.lua\"]").unwrap();
+        assert_eq!(key, vec!["entrypoints", "main"]);
+        assert!(value.is_array());
+
+        let (_, value) = parse_assignment("max_versions_in_index=10").unwrap();
+        assert_eq!(value.as_integer(), Some(10));
+
+        assert!(parse_assignment("author").is_err());
+        assert!(parse_assignment("a..b=1").is_err());
+    }
+
+    #[test]
+    fn as_string_value_01() {
+        let (_, value) = parse_assignment("name=1.2").unwrap();
+        assert_eq!(as_string_value(&value).unwrap().as_str(), Some("1.2"));
+        let (_, value) = parse_assignment("name=true").unwrap();
+        assert_eq!(as_string_value(&value).unwrap().as_str(), Some("true"));
+        let (_, value) = parse_assignment("name=Jane").unwrap();
+        assert!(as_string_value(&value).is_none());
+    }
+
+    #[test]
+    fn set_value_01() {
+        let mut doc: DocumentMut = "# comment\nname = \"x\"\n".parse().unwrap();
+        set_value(
+            &mut doc,
+            &["name".into()],
+            Value::from("y").decorated(" ", ""),
+        )
+        .unwrap();
+        set_value(
+            &mut doc,
+            &["entrypoints".into(), "main".into()],
+            parse_assignment("m=[\"a.lua\"]").unwrap().1,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "# comment\nname = \"y\"\n\n[entrypoints]\nmain = [\"a.lua\"]\n"
+        );
+    }
+}
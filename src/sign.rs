@@ -0,0 +1,143 @@
+//! Detached signatures for the exported index, created with minisign or GPG.
+
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("failed to launch {0}, please ensure it is accessible through the command line")]
+pub(crate) struct SigningToolNotFound(&'static str);
+
+#[derive(Error, Debug)]
+#[error("{0} failed: {1}")]
+pub(crate) struct SigningToolFailed(&'static str, String);
+
+#[derive(Error, Debug)]
+#[error("minisign needs a public key to verify signatures, please specify it with --key")]
+pub(crate) struct PublicKeyRequired;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SignTool {
+    Minisign,
+    Gpg,
+}
+
+impl SignTool {
+    fn program(&self) -> &'static str {
+        match self {
+            SignTool::Minisign => "minisign",
+            SignTool::Gpg => "gpg",
+        }
+    }
+
+    /// The path of the detached signature for the given file.
+    pub(crate) fn signature_path(&self, path: &Path) -> PathBuf {
+        let extension = match self {
+            SignTool::Minisign => "minisig",
+            SignTool::Gpg => "asc",
+        };
+        let mut result = OsString::from(path.as_os_str());
+        result.push(".");
+        result.push(extension);
+        result.into()
+    }
+
+    fn run(&self, args: &[&OsStr]) -> Result<()> {
+        // minisign and gpg may prompt for a password, so keep them attached to the terminal. Their errors are
+        // printed to the terminal as well
+        let status = Command::new(self.program())
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => anyhow::Error::from(SigningToolNotFound(self.program())),
+                _ => err.into(),
+            })?;
+        if !status.success() {
+            return Err(SigningToolFailed(self.program(), status.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Create a detached signature for the file, returning the path of the signature.
+    ///
+    /// The key is the secret key file for minisign, or the key ID for GPG. If not given, the tool's default key is used.
+    pub(crate) fn sign(&self, path: &Path, key: Option<&str>) -> Result<PathBuf> {
+        let signature_path = self.signature_path(path);
+        let mut args: Vec<&OsStr> = vec![];
+        match self {
+            SignTool::Minisign => {
+                args.extend([OsStr::new("-S"), OsStr::new("-m"), path.as_os_str()]);
+                args.extend([OsStr::new("-x"), signature_path.as_os_str()]);
+                if let Some(key) = key {
+                    args.extend([OsStr::new("-s"), OsStr::new(key)]);
+                }
+            }
+            SignTool::Gpg => {
+                args.extend([
+                    OsStr::new("--yes"),
+                    OsStr::new("--armor"),
+                    OsStr::new("--detach-sign"),
+                ]);
+                args.extend([OsStr::new("--output"), signature_path.as_os_str()]);
+                if let Some(key) = key {
+                    args.extend([OsStr::new("--local-user"), OsStr::new(key)]);
+                }
+                args.push(path.as_os_str());
+            }
+        }
+        self.run(&args)?;
+        Ok(signature_path)
+    }
+
+    /// Verify the detached signature of the file.
+    ///
+    /// The key is the public key file for minisign, which is required. It is ignored for GPG, which verifies
+    /// against the keys in the user's keyring.
+    pub(crate) fn verify(&self, path: &Path, key: Option<&str>) -> Result<()> {
+        let signature_path = self.signature_path(path);
+        let mut args: Vec<&OsStr> = vec![];
+        match self {
+            SignTool::Minisign => {
+                let Some(key) = key else {
+                    return Err(PublicKeyRequired.into());
+                };
+                args.extend([OsStr::new("-V"), OsStr::new("-m"), path.as_os_str()]);
+                args.extend([OsStr::new("-x"), signature_path.as_os_str()]);
+                args.extend([OsStr::new("-p"), OsStr::new(key)]);
+            }
+            SignTool::Gpg => {
+                args.push(OsStr::new("--verify"));
+                args.extend([signature_path.as_os_str(), path.as_os_str()]);
+            }
+        }
+        self.run(&args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_path_01() {
+        let path = Path::new("/repo/index.xml");
+        assert_eq!(
+            SignTool::Minisign.signature_path(path),
+            PathBuf::from("/repo/index.xml.minisig")
+        );
+        assert_eq!(
+            SignTool::Gpg.signature_path(path),
+            PathBuf::from("/repo/index.xml.asc")
+        );
+    }
+}
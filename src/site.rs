@@ -0,0 +1,316 @@
+//! A small static website for browsing the repository, with a page per category and per package.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use relative_path::RelativePath;
+
+use crate::repo::{
+    convert_markdown, convert_markdown_or_plain_text, url_encode_path, Package, Repository, Version,
+};
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:50em;margin:2em auto;padding:0 1em;line-height:1.5}\
+pre{background:#f4f4f4;padding:.5em;white-space:pre-wrap}\
+table{border-collapse:collapse}td,th{padding:.2em .8em;text-align:left;border-bottom:1px solid #ddd}";
+
+/// Escape text to be inserted into HTML.
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// The file name of a category's page, e.g. `Tools/MIDI` becomes `tools-midi.html`.
+fn category_filename(category: &str) -> String {
+    let mut slug = String::new();
+    for c in category.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "uncategorized.html".into()
+    } else {
+        format!("{slug}.html")
+    }
+}
+
+/// The file names of the categories' pages, see [category_filename]. Categories that would get the same file
+/// name are numbered in order, e.g. `Tools MIDI` and `Tools/MIDI` become `tools-midi.html` and `tools-midi-2.html`.
+fn category_filenames<'c>(
+    categories: impl IntoIterator<Item = &'c String>,
+) -> BTreeMap<&'c str, String> {
+    let mut used = HashSet::new();
+    let mut result = BTreeMap::new();
+    for category in categories {
+        let mut filename = category_filename(category);
+        let stem = filename.trim_end_matches(".html").to_string();
+        let mut number = 2;
+        while used.contains(&filename) {
+            filename = format!("{stem}-{number}.html");
+            number += 1;
+        }
+        used.insert(filename.clone());
+        result.insert(category.as_str(), filename);
+    }
+    result
+}
+
+/// The file name of a package's page. With `encoded`, the name is URL-encoded to be used in links.
+fn package_filename(pkg: &Package, encoded: bool) -> String {
+    let filename = format!("{}.html", pkg.identifier());
+    if encoded {
+        url_encode_path(RelativePath::new(&filename))
+    } else {
+        filename
+    }
+}
+
+/// Wrap the body in a complete HTML document.
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        STYLE,
+        body
+    )
+}
+
+/// The site generator. Pages link to each other with relative paths, so the site can be hosted under any URL.
+pub(crate) struct Site<'a> {
+    repo: &'a Repository,
+    /// The path of the exported index relative to the site's root, if the index is inside the site
+    index_link: Option<String>,
+}
+
+impl<'a> Site<'a> {
+    pub(crate) fn new(repo: &'a Repository, output: &Path, index_path: &Path) -> Self {
+        let index_link = path::absolute(index_path)
+            .ok()
+            .zip(path::absolute(output).ok())
+            .and_then(|(index, output)| {
+                index
+                    .strip_prefix(output)
+                    .ok()
+                    .map(|x| x.to_string_lossy().replace('\\', "/"))
+            });
+        Self { repo, index_link }
+    }
+
+    /// Write the site into the output folder. Returns the paths of the written pages.
+    pub(crate) fn write(&self, output: &Path) -> Result<Vec<PathBuf>> {
+        let mut categories: BTreeMap<String, Vec<Package>> = BTreeMap::new();
+        for pkg in self.repo.indexed_packages()? {
+            if pkg.indexed_versions(self.repo)?.is_empty() {
+                continue;
+            }
+            categories
+                .entry(pkg.category().to_string())
+                .or_default()
+                .push(pkg);
+        }
+        for packages in categories.values_mut() {
+            packages.sort_by_key(|pkg| pkg.name().to_lowercase());
+        }
+
+        let filenames = category_filenames(categories.keys());
+
+        let mut written = vec![];
+        let mut write_page = |relpath: String, content: String| -> Result<()> {
+            let path = output.join(relpath);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            written.push(path);
+            Ok(())
+        };
+
+        write_page(
+            "index.html".into(),
+            self.index_page(&categories, &filenames),
+        )?;
+        for (category, packages) in categories.iter() {
+            let category_filename = &filenames[category.as_str()];
+            write_page(
+                format!("categories/{category_filename}"),
+                self.category_page(category, packages),
+            )?;
+            for pkg in packages {
+                write_page(
+                    format!("packages/{}", package_filename(pkg, false)),
+                    self.package_page(pkg, category_filename)?,
+                )?;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Instructions for adding the repository to ReaPack. `prefix` is the relative path to the site's root.
+    fn install_instructions(&self, prefix: &str) -> String {
+        let url = match &self.index_link {
+            Some(link) => format!(
+                "<a href=\"{}{}\">the repository index</a>",
+                prefix,
+                escape_html(link)
+            ),
+            None => "the URL of the repository index".into(),
+        };
+        format!(
+            "<h2>Installation</h2>\n<ol>\n<li>In REAPER, open <em>Extensions &gt; ReaPack &gt; Import repositories...</em></li>\n\
+<li>Paste {url} and press OK</li>\n\
+<li>Open <em>Extensions &gt; ReaPack &gt; Browse packages</em>, then search for the package and install it</li>\n</ol>\n"
+        )
+    }
+
+    fn index_page(
+        &self,
+        categories: &BTreeMap<String, Vec<Package>>,
+        filenames: &BTreeMap<&str, String>,
+    ) -> String {
+        let name = self.repo.identifier();
+        let mut body = format!("<h1>{}</h1>\n", escape_html(&name));
+        body.push_str("<h2>Categories</h2>\n<ul>\n");
+        for (category, packages) in categories.iter() {
+            body.push_str(&format!(
+                "<li><a href=\"categories/{}\">{}</a> ({})</li>\n",
+                filenames[category.as_str()],
+                escape_html(category),
+                packages.len()
+            ));
+        }
+        body.push_str("</ul>\n");
+        body.push_str(&self.install_instructions(""));
+        page(&name, &body)
+    }
+
+    fn category_page(&self, category: &str, packages: &[Package]) -> String {
+        let mut body = format!(
+            "<p><a href=\"../index.html\">{}</a></p>\n<h1>{}</h1>\n<table>\n<tr><th>Package</th><th>Type</th><th>Author</th></tr>\n",
+            escape_html(&self.repo.identifier()),
+            escape_html(category)
+        );
+        for pkg in packages {
+            body.push_str(&format!(
+                "<tr><td><a href=\"../packages/{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                package_filename(pkg, true),
+                escape_html(&pkg.name()),
+                <&str>::from(&pkg.pkg_type()),
+                escape_html(pkg.author().unwrap_or(self.repo.author()))
+            ));
+        }
+        body.push_str("</table>\n");
+        page(category, &body)
+    }
+
+    fn package_page(&self, pkg: &Package, category_filename: &str) -> Result<String> {
+        let category = pkg.category().to_string();
+        let mut body = format!(
+            "<p><a href=\"../index.html\">{}</a> / <a href=\"../categories/{}\">{}</a></p>\n<h1>{}</h1>\n",
+            escape_html(&self.repo.identifier()),
+            category_filename,
+            escape_html(&category),
+            escape_html(&pkg.name())
+        );
+        if let Some(notice) = pkg.deprecation_notice() {
+            body.push_str(&format!(
+                "<p><strong>{}</strong></p>\n",
+                escape_html(&notice)
+            ));
+        }
+        if let Some(readme) = pkg.readme_markdown(self.repo)? {
+            let readme_path = pkg.readme_path().unwrap_or_else(|| pkg.path().into());
+            body.push_str(&convert_markdown_or_plain_text(
+                readme,
+                &readme_path,
+                |text| {
+                    convert_markdown(
+                        text,
+                        pandoc::OutputFormat::Html5,
+                        false,
+                        self.repo.pandoc_path().as_deref(),
+                    )
+                },
+                |text| format!("<pre>{}</pre>\n", escape_html(text.trim())),
+            )?);
+        }
+        body.push_str(&self.install_instructions("../"));
+
+        body.push_str("<h2>Versions</h2>\n");
+        let mut versions = pkg.indexed_versions(self.repo)?;
+        versions.sort_by(|a, b| Version::compare_version_names(&b.name(), &a.name()));
+        for ver in versions.iter() {
+            body.push_str(&format!(
+                "<h3>{}</h3>\n<p>Released {} by {}</p>\n",
+                escape_html(&ver.name()),
+                ver.time().format("%Y-%m-%d"),
+                escape_html(ver.resolved_author(self.repo, pkg))
+            ));
+            if let Some(changelog) = ver.changelog_text(self.repo, pkg)? {
+                body.push_str(&format!("<pre>{}</pre>\n", escape_html(changelog.trim())));
+            }
+            body.push_str("<ul>\n");
+            for src in ver.sources(pkg)? {
+                body.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    escape_html(&src.url(self.repo, pkg, ver)?),
+                    escape_html(src.output_relpath(pkg, ver).as_str())
+                ));
+            }
+            body.push_str("</ul>\n");
+        }
+        Ok(page(&pkg.name(), &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_filename_01() {
+        assert_eq!(category_filename("Tools/MIDI"), "tools-midi.html");
+        assert_eq!(category_filename("My Category!"), "my-category.html");
+        assert_eq!(category_filename("/"), "uncategorized.html");
+    }
+
+    #[test]
+    fn category_filenames_01() {
+        let categories: Vec<String> = vec![
+            "Tools MIDI".into(),
+            "Tools MIDI 2".into(),
+            "Tools/MIDI".into(),
+            "Utility".into(),
+        ];
+        let filenames = category_filenames(&categories);
+        assert_eq!(filenames["Tools MIDI"], "tools-midi.html");
+        assert_eq!(filenames["Tools MIDI 2"], "tools-midi-2.html");
+        assert_eq!(filenames["Tools/MIDI"], "tools-midi-3.html");
+        assert_eq!(filenames["Utility"], "utility.html");
+    }
+
+    #[test]
+    fn escape_html_01() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
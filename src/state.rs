@@ -0,0 +1,96 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Persistent data remembered between exports, stored in the root of the repository.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct ExportState {
+    /// Files and folders written by previous exports, relative to the repository, e.g. the index and the HTML pages.
+    /// Paths outside the repository aren't recorded.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) outputs: BTreeSet<String>,
+    /// The git commit used for each version when it was first exported.
+    /// Maps package identifier -> version name -> commit hash.
+    #[serde(default)]
+    pub(crate) commits: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ExportState {
+    pub(crate) const FILENAME: &'static str = ".reapack-indexer-state.toml";
+
+    /// Read the state file in the given repository folder.
+    /// If the file does not exist, return an empty state.
+    pub(crate) fn read(repo_dir: &Path) -> Result<Self> {
+        let path = repo_dir.join(Self::FILENAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub(crate) fn write(&self, repo_dir: &Path) -> Result<()> {
+        let path = repo_dir.join(Self::FILENAME);
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn commit(&self, package: &str, version: &str) -> Option<&str> {
+        self.commits
+            .get(package)
+            .and_then(|versions| versions.get(version))
+            .map(|x| x.as_str())
+    }
+
+    pub(crate) fn remove_package(&mut self, package: &str) {
+        self.commits.remove(package);
+    }
+
+    /// Add to the recorded outputs. Returns whether any of them are new.
+    pub(crate) fn add_outputs(&mut self, outputs: impl IntoIterator<Item = String>) -> bool {
+        let len = self.outputs.len();
+        self.outputs.extend(outputs);
+        self.outputs.len() != len
+    }
+
+    pub(crate) fn set_commit(&mut self, package: &str, version: &str, hash: &str) {
+        self.commits
+            .entry(package.into())
+            .or_default()
+            .insert(version.into(), hash.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_01() {
+        let mut state = ExportState::default();
+        state.set_commit("my-package", "0.0.1", "abc");
+        state.set_commit("my-package", "0.0.2", "def");
+        let text = toml::to_string(&state).unwrap();
+        let state: ExportState = toml::from_str(&text).unwrap();
+        assert_eq!(state.commit("my-package", "0.0.1"), Some("abc"));
+        assert_eq!(state.commit("my-package", "0.0.2"), Some("def"));
+        assert_eq!(state.commit("my-package", "0.0.3"), None);
+        assert_eq!(state.commit("other-package", "0.0.1"), None);
+    }
+
+    #[test]
+    fn roundtrip_02() {
+        let mut state = ExportState::default();
+        state.set_commit("my-package", "0.0.1", "abc");
+        assert!(state.add_outputs(["index.xml".into(), "site".into()]));
+        assert!(!state.add_outputs(["site".into()]));
+        let text = toml::to_string(&state).unwrap();
+        let state: ExportState = toml::from_str(&text).unwrap();
+        assert_eq!(state.outputs.len(), 2);
+        assert_eq!(state.commit("my-package", "0.0.1"), Some("abc"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::repo::{Package, Repository};
+
+/// How many of the largest files to report.
+const LARGEST_FILES_COUNT: usize = 10;
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Stats {
+    pub(crate) packages: Vec<PackageStats>,
+    pub(crate) total: TotalStats,
+    /// The largest source files in the repository, largest first
+    pub(crate) largest_files: Vec<FileStats>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct PackageStats {
+    pub(crate) identifier: String,
+    pub(crate) versions: usize,
+    pub(crate) sources: usize,
+    pub(crate) bytes: u64,
+    pub(crate) first_published: Option<DateTime<Utc>>,
+    pub(crate) last_published: Option<DateTime<Utc>>,
+    /// Average number of days between consecutive versions, if there are at least 2 versions
+    pub(crate) days_between_versions: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct TotalStats {
+    pub(crate) packages: usize,
+    pub(crate) versions: usize,
+    pub(crate) sources: usize,
+    pub(crate) bytes: u64,
+    /// Number of versions published in the last 30 days
+    pub(crate) versions_last_30_days: usize,
+    pub(crate) days_between_versions: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct FileStats {
+    pub(crate) path: PathBuf,
+    pub(crate) bytes: u64,
+}
+
+/// The average number of days between the given times, or None if there are fewer than 2 times.
+fn average_interval_days(times: &mut [DateTime<Utc>]) -> Option<f64> {
+    times.sort();
+    let (first, last) = (times.first()?, times.last()?);
+    if times.len() < 2 {
+        return None;
+    }
+    let seconds = (*last - *first).num_seconds() as f64;
+    Some(seconds / 86400.0 / (times.len() - 1) as f64)
+}
+
+/// Format a size in bytes with a binary unit, e.g. `1.5 KiB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for x in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = x;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// Format rows as a table with left-aligned, padded columns.
+pub(crate) fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|x| x.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![format_row(headers.to_vec())];
+    for row in rows {
+        lines.push(format_row(row.iter().map(|x| x.as_str()).collect()));
+    }
+    lines.join("\n")
+}
+
+impl PackageStats {
+    fn generate(pkg: &Package, files: &mut Vec<FileStats>) -> Result<Self> {
+        let versions = pkg.versions()?;
+        let mut times: Vec<DateTime<Utc>> = versions.iter().map(|ver| ver.time()).collect();
+        let mut sources = 0;
+        let mut bytes = 0;
+        for ver in versions.iter() {
+            // versions without sources are reported by `check`, they just don't count here
+            for src in ver.sources(pkg).unwrap_or_default() {
+                sources += 1;
+                // remote sources aren't on disk, so they don't have a size
+                if let Ok(metadata) = src.path().metadata() {
+                    bytes += metadata.len();
+                    files.push(FileStats {
+                        path: src.path().to_path_buf(),
+                        bytes: metadata.len(),
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            identifier: pkg.identifier().to_string(),
+            versions: versions.len(),
+            sources,
+            bytes,
+            first_published: times.iter().min().copied(),
+            last_published: times.iter().max().copied(),
+            days_between_versions: average_interval_days(&mut times),
+        })
+    }
+}
+
+impl Stats {
+    pub(crate) fn generate(repo: &Repository) -> Result<Self> {
+        let mut packages = vec![];
+        let mut files = vec![];
+        let mut times = vec![];
+        for pkg in repo.packages()? {
+            packages.push(PackageStats::generate(&pkg, &mut files)?);
+            times.extend(pkg.versions()?.iter().map(|ver| ver.time()));
+        }
+        packages.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+
+        files.sort_by_key(|x| std::cmp::Reverse(x.bytes));
+        files.truncate(LARGEST_FILES_COUNT);
+        for file in files.iter_mut() {
+            if let Ok(relpath) = file.path.strip_prefix(repo.path()) {
+                file.path = relpath.to_path_buf();
+            }
+        }
+
+        let month_ago = Utc::now() - chrono::Duration::days(30);
+        let total = TotalStats {
+            packages: packages.len(),
+            versions: packages.iter().map(|x| x.versions).sum(),
+            sources: packages.iter().map(|x| x.sources).sum(),
+            bytes: packages.iter().map(|x| x.bytes).sum(),
+            versions_last_30_days: times.iter().filter(|x| **x > month_ago).count(),
+            days_between_versions: average_interval_days(&mut times),
+        };
+
+        Ok(Self {
+            packages,
+            total,
+            largest_files: files,
+        })
+    }
+
+    /// Print the statistics as human-readable tables.
+    pub(crate) fn print(&self) {
+        let format_days = |days: Option<f64>| match days {
+            Some(days) => format!("{days:.1}"),
+            None => "-".into(),
+        };
+        let format_date = |time: Option<DateTime<Utc>>| match time {
+            Some(time) => time.format("%Y-%m-%d").to_string(),
+            None => "-".into(),
+        };
+
+        let rows: Vec<Vec<String>> = self
+            .packages
+            .iter()
+            .map(|pkg| {
+                vec![
+                    pkg.identifier.clone(),
+                    pkg.versions.to_string(),
+                    pkg.sources.to_string(),
+                    format_size(pkg.bytes),
+                    format_date(pkg.first_published),
+                    format_date(pkg.last_published),
+                    format_days(pkg.days_between_versions),
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            format_table(
+                &[
+                    "Package",
+                    "Versions",
+                    "Files",
+                    "Size",
+                    "First",
+                    "Last",
+                    "Days between versions"
+                ],
+                &rows
+            )
+        );
+
+        println!();
+        println!("Packages: {}", self.total.packages);
+        println!("Versions: {}", self.total.versions);
+        println!("Files: {}", self.total.sources);
+        println!("Size: {}", format_size(self.total.bytes));
+        println!(
+            "Versions in the last 30 days: {}",
+            self.total.versions_last_30_days
+        );
+        println!(
+            "Days between versions: {}",
+            format_days(self.total.days_between_versions)
+        );
+
+        if !self.largest_files.is_empty() {
+            println!();
+            let rows: Vec<Vec<String>> = self
+                .largest_files
+                .iter()
+                .map(|file| vec![format_size(file.bytes), file.path.display().to_string()])
+                .collect();
+            println!("{}", format_table(&["Size", "Largest files"], &rows));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_size_01() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn average_interval_days_01() {
+        let day = |x: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, x)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        assert_eq!(average_interval_days(&mut []), None);
+        assert_eq!(average_interval_days(&mut [day(1)]), None);
+        assert_eq!(
+            average_interval_days(&mut [day(11), day(1), day(5)]),
+            Some(5.0)
+        );
+    }
+}
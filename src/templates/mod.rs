@@ -1,12 +1,15 @@
+use std::borrow::Cow;
+
 use leon::{Template, Values};
 use once_cell::sync::Lazy;
 
-use crate::config::PackageConfig;
-
 const REPOSITORY_STR: &str = include_str!("repository.toml");
 const PACKAGE_STR: &str = include_str!("package.toml");
 const VERSION_STR: &str = include_str!("version.toml");
 
+/// Initial contents of a new changelog, flagged by the changelog lint if it is never replaced.
+pub(crate) const CHANGELOG_PLACEHOLDER: &str = "Describe the changes in this version here.\n";
+
 static REPOSITORY_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::parse(REPOSITORY_STR).unwrap());
 static PACKAGE_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::parse(PACKAGE_STR).unwrap());
 static VERSION_TEMPLATE: Lazy<Template> = Lazy::new(|| Template::parse(VERSION_STR).unwrap());
@@ -27,6 +30,7 @@ macro_rules! template_params {
             }
         }
 
+        #[allow(dead_code)]
         impl<'a> $struct_name<'a> {
             $(
                 pub(crate) fn $field(mut self, val: &'a str) -> Self {
@@ -46,6 +50,21 @@ macro_rules! template_params {
     };
 }
 
+/// Template values where the given keys are inserted as TOML strings, quoted and escaped, so that values with quotes
+/// or backslashes still give a valid config.
+struct TomlStrings<'a, V>(&'a V, &'a [&'a str]);
+
+impl<V: Values> Values for TomlStrings<'_, V> {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        let value = self.0.get_value(key)?;
+        if self.1.contains(&key) {
+            Some(toml_edit::Value::from(value.as_ref()).to_string().into())
+        } else {
+            Some(value)
+        }
+    }
+}
+
 template_params!(
     RepositoryTemplateParams,
     author: &'a str = "Your Name",
@@ -53,21 +72,82 @@ template_params!(
     identifier: &'a str = "my-cool-repository"
 );
 
+/// Text in the default repository config that must be replaced by the user.
+const PLACEHOLDERS: &[&str] = &["YOUR_USERNAME", "YOUR_REPOSITORY"];
+
+/// Whether the text still contains placeholders from the default repository config.
+pub(crate) fn has_placeholder(text: &str) -> bool {
+    PLACEHOLDERS.iter().any(|x| text.contains(x))
+}
+
 pub(crate) fn generate_repository_config(params: &RepositoryTemplateParams) -> String {
-    REPOSITORY_TEMPLATE.render(&params).unwrap()
+    REPOSITORY_TEMPLATE
+        .render(&TomlStrings(
+            params,
+            &["author", "url_pattern", "identifier"],
+        ))
+        .unwrap()
 }
 
+/// The `[entrypoints]` section of a new script package, with the given entrypoints in the main section.
+pub(crate) fn script_entrypoints(entrypoints: &[&str]) -> String {
+    format!(
+        r#"
+# For scripts, define the files to be added to the action list here.
+# Glob patterns are supported as well.
+[entrypoints]
+main = [{}]
+# midi_editor = []
+# midi_inlineeditor = []
+# midi_eventlisteditor = []
+# mediaexplorer = []
+"#,
+        entrypoints
+            .iter()
+            .map(|x| toml_edit::Value::from(*x).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+static DEFAULT_SCRIPT_ENTRYPOINTS: Lazy<String> =
+    Lazy::new(|| script_entrypoints(&["My script.lua"]));
+
 template_params!(
     PackageTemplateParams,
     name: &'a str = "My cool package",
     category: &'a str = "Category",
     typ: &'a str = "script", // not 'type' because it is a reserved word
     identifier: &'a str = "my-cool-package",
-    author: &'a str = "Your Name"
+    author: &'a str = "Your Name",
+    entrypoints: &'a str = DEFAULT_SCRIPT_ENTRYPOINTS.as_str() // empty for other package types
 );
 
 pub(crate) fn generate_package_config(params: &PackageTemplateParams) -> String {
-    PACKAGE_TEMPLATE.render(&params).unwrap()
+    PACKAGE_TEMPLATE
+        .render(&TomlStrings(
+            params,
+            &["name", "category", "typ", "identifier", "author"],
+        ))
+        .unwrap()
+}
+
+/// Initial contents of a stub entrypoint file for a new package, or None if there is no stub for this type of file.
+pub(crate) fn stub_entrypoint(typ: &str, name: &str, file: &str) -> Option<String> {
+    let extension = file.rsplit_once('.').map(|(_, x)| x.to_lowercase());
+    match (typ, extension.as_deref()) {
+        ("script", Some("lua")) => Some(format!(
+            "-- {name}\n\nreaper.ShowConsoleMsg(\"Hello from {name}!\\n\")\n"
+        )),
+        ("script", Some("eel")) => Some(format!(
+            "// {name}\n\nShowConsoleMsg(\"Hello from {name}!\\n\");\n"
+        )),
+        ("script", Some("py")) => Some(format!(
+            "# {name}\n\nRPR_ShowConsoleMsg(\"Hello from {name}!\\n\")\n"
+        )),
+        ("effect", _) => Some(format!("desc:{name}\n\n@sample\n")),
+        _ => None,
+    }
 }
 
 template_params!(
@@ -79,6 +159,11 @@ pub(crate) fn generate_version_config(params: &VersionTemplateParams) -> String
     VERSION_TEMPLATE.render(&params).unwrap()
 }
 
+/// Initial contents of the changelog of a new version, with its name and date filled in, e.g. `2024-01-31`.
+pub(crate) fn changelog_template(version: &str, date: &str) -> String {
+    format!("{version} ({date})\n\n{CHANGELOG_PLACEHOLDER}")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config::{PackageConfig, RepositoryConfig, VersionConfig};
@@ -109,6 +194,44 @@ mod tests {
         let _: PackageConfig = toml::from_str(&text).unwrap();
     }
     #[test]
+    fn generated_package_config_is_valid_02() {
+        let entrypoints = script_entrypoints(&["My \"quoted\" script.lua"]);
+        let text =
+            generate_package_config(&PackageTemplateParams::default().entrypoints(&entrypoints));
+        let config: PackageConfig = toml::from_str(&text).unwrap();
+        assert_eq!(
+            config.entrypoints.unwrap()[&crate::config::ActionListSection::Main],
+            vec!["My \"quoted\" script.lua".to_string()]
+        );
+
+        let text = generate_package_config(
+            &PackageTemplateParams::default()
+                .typ("effect")
+                .entrypoints(""),
+        );
+        let config: PackageConfig = toml::from_str(&text).unwrap();
+        assert!(config.entrypoints.is_none());
+    }
+    #[test]
+    fn generated_package_config_is_valid_03() {
+        let text = generate_package_config(
+            &PackageTemplateParams::default()
+                .name("My \"quoted\" package")
+                .category("Back\\slash"),
+        );
+        let config: PackageConfig = toml::from_str(&text).unwrap();
+        assert_eq!(config.name.unwrap(), "My \"quoted\" package");
+        assert_eq!(config.category, "Back\\slash");
+    }
+    #[test]
+    fn generated_repository_config_is_valid_02() {
+        let text = generate_repository_config(
+            &RepositoryTemplateParams::default().author("Jane \"JD\" Doe"),
+        );
+        let config: RepositoryConfig = toml::from_str(&text).unwrap();
+        assert_eq!(config.author, "Jane \"JD\" Doe");
+    }
+    #[test]
     fn generated_version_config_is_valid() {
         let text = generate_version_config(&VersionTemplateParams::default());
         let _: VersionConfig = toml::from_str(&text).unwrap();
@@ -0,0 +1,406 @@
+//! A terminal UI for browsing the repository, with shortcuts to the most common commands.
+//!
+//! Commands that print output or open an editor (publish, edit, export) are run as a subprocess of this program
+//! while the UI is suspended, so they behave exactly like they do on the command line.
+
+use std::{
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Result;
+use ratatui::{
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind},
+        execute,
+        terminal::{enable_raw_mode, EnterAlternateScreen},
+    },
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use thiserror::Error;
+
+use crate::repo::{Package, Repository, Version};
+
+#[derive(Error, Debug)]
+#[error("the terminal UI can only be used in a terminal")]
+pub(crate) struct NotATerminal;
+
+const HELP: &str =
+    "↑↓ move  tab switch pane  p publish  y yank  e edit  x export  r reload  q quit";
+
+#[derive(PartialEq, Eq)]
+enum Focus {
+    Packages,
+    Versions,
+}
+
+/// What the main loop should do after handling a key.
+enum Action {
+    None,
+    Quit,
+    /// Run this program with the given arguments, with the UI suspended
+    Run(Vec<String>),
+}
+
+struct App {
+    repo_path: PathBuf,
+    repo: Repository,
+    packages: Vec<Package>,
+    /// Versions of the selected package, newest first
+    versions: Vec<Version>,
+    package_state: ListState,
+    version_state: ListState,
+    focus: Focus,
+    /// Text typed into the publish prompt, if it's open
+    input: Option<String>,
+    /// Shown in the status bar until the next key press
+    message: Option<String>,
+}
+
+impl App {
+    fn new(repo_path: &Path) -> Result<Self> {
+        let mut app = Self {
+            repo_path: repo_path.into(),
+            repo: Repository::read(repo_path)?,
+            packages: vec![],
+            versions: vec![],
+            package_state: ListState::default(),
+            version_state: ListState::default(),
+            focus: Focus::Packages,
+            input: None,
+            message: None,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Read the repository again, keeping the selection where possible.
+    fn reload(&mut self) -> Result<()> {
+        self.repo = Repository::read(&self.repo_path)?;
+        self.packages = self.repo.packages()?;
+        self.packages
+            .sort_by_key(|pkg| pkg.identifier().to_lowercase());
+        let selected = self
+            .package_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.packages.len().saturating_sub(1));
+        self.package_state
+            .select((!self.packages.is_empty()).then_some(selected));
+        self.load_versions()
+    }
+
+    fn load_versions(&mut self) -> Result<()> {
+        self.versions = match self.selected_package() {
+            Some(pkg) => pkg.versions()?,
+            None => vec![],
+        };
+        self.versions
+            .sort_by(|a, b| Version::compare_version_names(&b.name(), &a.name()));
+        let selected = self
+            .version_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.versions.len().saturating_sub(1));
+        self.version_state
+            .select((!self.versions.is_empty()).then_some(selected));
+        Ok(())
+    }
+
+    fn selected_package(&self) -> Option<&Package> {
+        self.package_state
+            .selected()
+            .and_then(|i| self.packages.get(i))
+    }
+
+    fn selected_version(&self) -> Option<&Version> {
+        self.version_state
+            .selected()
+            .and_then(|i| self.versions.get(i))
+    }
+
+    /// Move the selection in the focused list, staying within the list.
+    fn move_selection(&mut self, offset: isize) -> Result<()> {
+        let (state, len) = match self.focus {
+            Focus::Packages => (&mut self.package_state, self.packages.len()),
+            Focus::Versions => (&mut self.version_state, self.versions.len()),
+        };
+        if len == 0 {
+            return Ok(());
+        }
+        let selected = state
+            .selected()
+            .unwrap_or(0)
+            .saturating_add_signed(offset)
+            .min(len - 1);
+        state.select(Some(selected));
+        if self.focus == Focus::Packages {
+            self.version_state.select(Some(0));
+            self.load_versions()?;
+        }
+        Ok(())
+    }
+
+    fn repo_arg(&self) -> String {
+        self.repo_path.to_string_lossy().to_string()
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> Result<Action> {
+        self.message = None;
+
+        if let Some(input) = &mut self.input {
+            match key {
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Enter => {
+                    let source = self.input.take().unwrap_or_default();
+                    let Some(pkg) = self.selected_package() else {
+                        return Ok(Action::None);
+                    };
+                    if source.trim().is_empty() {
+                        return Ok(Action::None);
+                    }
+                    return Ok(Action::Run(vec![
+                        "publish".into(),
+                        "--repo".into(),
+                        self.repo_arg(),
+                        "--identifier".into(),
+                        pkg.identifier().to_string(),
+                        source.trim().into(),
+                    ]));
+                }
+                _ => {}
+            }
+            return Ok(Action::None);
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Action::Quit),
+            KeyCode::Tab | KeyCode::Left | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Focus::Packages => Focus::Versions,
+                    Focus::Versions => Focus::Packages,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1)?,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1)?,
+            KeyCode::Char('r') => {
+                self.reload()?;
+                self.message = Some("Reloaded the repository".into());
+            }
+            KeyCode::Char('p') if self.selected_package().is_some() => {
+                self.input = Some(String::new());
+            }
+            KeyCode::Char('y') => {
+                let Some(i) = self.version_state.selected() else {
+                    return Ok(Action::None);
+                };
+                if let Some(ver) = self.versions.get_mut(i) {
+                    let yanked = !ver.yanked();
+                    ver.set_yanked(yanked)?;
+                    self.message = Some(format!(
+                        "{} version {}",
+                        if yanked { "Yanked" } else { "Unyanked" },
+                        ver.name()
+                    ));
+                }
+            }
+            KeyCode::Char('e') => {
+                let Some(pkg) = self.selected_package() else {
+                    return Ok(Action::None);
+                };
+                let mut args = vec![
+                    "edit".into(),
+                    "--repo".into(),
+                    self.repo_arg(),
+                    pkg.identifier().to_string(),
+                ];
+                if self.focus == Focus::Versions {
+                    if let Some(ver) = self.selected_version() {
+                        args.push(ver.name().to_string());
+                    }
+                }
+                return Ok(Action::Run(args));
+            }
+            KeyCode::Char('x') => {
+                return Ok(Action::Run(vec![
+                    "export".into(),
+                    "--repo".into(),
+                    self.repo_arg(),
+                    self.repo.index_path().to_string_lossy().to_string(),
+                ]));
+            }
+            _ => {}
+        }
+        Ok(Action::None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+        let [versions_area, details_area] =
+            Layout::vertical([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(right);
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        let border = |title: &str, focused: bool| {
+            let block = Block::bordered().title(title.to_string());
+            if focused {
+                block.border_style(Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                block
+            }
+        };
+
+        let packages = List::new(self.packages.iter().map(|pkg| {
+            if pkg.deprecation_notice().is_some() {
+                format!("{} (deprecated)", pkg.identifier())
+            } else {
+                pkg.identifier().to_string()
+            }
+        }))
+        .block(border(
+            &format!("Packages ({})", self.repo.identifier()),
+            self.focus == Focus::Packages,
+        ))
+        .highlight_style(highlight);
+        frame.render_stateful_widget(packages, left, &mut self.package_state);
+
+        let versions = List::new(self.versions.iter().map(|ver| {
+            let mut line = format!("{}  {}", ver.name(), ver.time().format("%Y-%m-%d"));
+            if ver.yanked() {
+                line.push_str("  (yanked)");
+            }
+            line
+        }))
+        .block(border("Versions", self.focus == Focus::Versions))
+        .highlight_style(highlight);
+        frame.render_stateful_widget(versions, versions_area, &mut self.version_state);
+
+        let details = Paragraph::new(self.details())
+            .block(Block::bordered().title("Details"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, details_area);
+
+        let status_text = match (&self.input, &self.message) {
+            (Some(input), _) => format!("Publish from folder: {input}"),
+            (None, Some(message)) => message.clone(),
+            (None, None) => HELP.into(),
+        };
+        frame.render_widget(Paragraph::new(status_text), status);
+    }
+
+    /// The sources and changelog of the selected version.
+    fn details(&self) -> Vec<Line<'static>> {
+        let (Some(pkg), Some(ver)) = (self.selected_package(), self.selected_version()) else {
+            return vec![];
+        };
+        let mut lines = vec![
+            Line::from(format!("{} {}", pkg.name(), ver.name())),
+            Line::from(format!(
+                "Released {} by {}",
+                ver.time().format("%Y-%m-%d %H:%M"),
+                ver.resolved_author(&self.repo, pkg)
+            )),
+            Line::from(""),
+            Line::from("Sources:"),
+        ];
+        match ver.sources(pkg) {
+            Ok(sources) => {
+                for src in sources.iter() {
+                    let sections = match src.sections(pkg, ver) {
+                        Ok(sections) if sections.is_empty() => String::new(),
+                        Ok(sections) => {
+                            let mut names: Vec<&str> = sections.iter().map(Into::into).collect();
+                            names.sort();
+                            format!(" [{}]", names.join(", "))
+                        }
+                        Err(err) => format!(" [{err}]"),
+                    };
+                    lines.push(Line::from(format!(
+                        "  {}{}",
+                        src.output_relpath(pkg, ver),
+                        sections
+                    )));
+                }
+            }
+            Err(err) => lines.push(Line::from(format!("  {err}"))),
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("Changelog:"));
+        match ver.changelog_text(&self.repo, pkg) {
+            Ok(Some(changelog)) => {
+                lines.extend(changelog.lines().map(|x| Line::from(format!("  {x}"))))
+            }
+            Ok(None) => lines.push(Line::from("  (none)")),
+            Err(err) => lines.push(Line::from(format!("  {err}"))),
+        }
+        lines
+    }
+}
+
+/// Run this program with the given arguments, with the terminal restored to normal so that the command can print
+/// output and ask questions. Waits for Enter before returning to the UI.
+fn run_suspended(terminal: &mut DefaultTerminal, args: &[String]) -> Result<()> {
+    ratatui::restore();
+    let status = Command::new(std::env::current_exe()?).args(args).status();
+    match status {
+        Ok(status) if !status.success() => println!("Command failed with {status}"),
+        Ok(_) => {}
+        Err(err) => println!("Failed to run command: {err}"),
+    }
+    println!("Press Enter to return...");
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // show errors in the status bar instead of closing the UI
+        match app.handle_key(key.code) {
+            Ok(Action::None) => {}
+            Ok(Action::Quit) => return Ok(()),
+            Ok(Action::Run(args)) => {
+                run_suspended(terminal, &args)?;
+                if let Err(err) = app.reload() {
+                    app.message = Some(format!("Error: {err}"));
+                }
+            }
+            Err(err) => app.message = Some(format!("Error: {err}")),
+        }
+    }
+}
+
+/// Show the terminal UI for the given repository until the user quits.
+pub(crate) fn run(repo_path: &Path) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(NotATerminal.into());
+    }
+    let mut app = App::new(&std::path::absolute(repo_path)?)?;
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
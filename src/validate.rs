@@ -0,0 +1,293 @@
+use std::{fmt, path::PathBuf};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{
+    repo::{find_case_duplicates, Package, Repository, Version},
+    templates,
+};
+
+#[derive(Error, Debug)]
+#[error("{}", .0.join(", "))]
+pub(crate) struct ChangelogProblems(Vec<&'static str>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in the repository, at the given path.
+#[derive(Debug, Clone)]
+pub(crate) struct Problem {
+    pub(crate) severity: Severity,
+    pub(crate) path: PathBuf,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} ({})",
+            self.severity,
+            self.message,
+            self.path.display()
+        )
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Problems(Vec<Problem>);
+
+impl Problems {
+    pub(crate) fn warning(&mut self, path: impl Into<PathBuf>, message: impl Into<String>) {
+        self.push(Severity::Warning, path, message)
+    }
+
+    pub(crate) fn error(&mut self, path: impl Into<PathBuf>, message: impl Into<String>) {
+        self.push(Severity::Error, path, message)
+    }
+
+    fn push(&mut self, severity: Severity, path: impl Into<PathBuf>, message: impl Into<String>) {
+        self.0.push(Problem {
+            severity,
+            path: path.into(),
+            message: message.into(),
+        })
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Problem> {
+        self.0.iter()
+    }
+
+    pub(crate) fn count(&self, severity: Severity) -> usize {
+        self.0.iter().filter(|x| x.severity == severity).count()
+    }
+}
+
+/// Run all validation rules on the repository.
+pub(crate) fn validate(repo: &Repository) -> Result<Problems> {
+    let mut problems = Problems::default();
+
+    let mut packages: Vec<Package> = vec![];
+    for pkg_path in Package::discover_package_paths(repo.path())? {
+        let pkg = match Package::read(&pkg_path) {
+            Ok(pkg) => Some(pkg),
+            Err(err) => {
+                problems.error(&pkg_path, format!("failed to read package config: {err}"));
+                None
+            }
+        };
+
+        let mut versions = vec![];
+        for ver_path in Version::discover_version_paths(&pkg_path)? {
+            match Version::read(&ver_path) {
+                Ok(ver) => versions.push(ver),
+                Err(err) => {
+                    problems.error(&ver_path, format!("failed to read version config: {err}"))
+                }
+            }
+        }
+
+        let Some(pkg) = pkg else {
+            continue;
+        };
+        check_package(&pkg, &mut problems);
+        check_version_names(&versions, &mut problems);
+        for ver in versions.iter() {
+            check_sources(&pkg, ver, &mut problems);
+            check_authors(repo, &pkg, ver, &mut problems);
+            if repo.lint_changelogs() {
+                check_changelog(ver, &mut problems);
+            }
+        }
+        packages.push(pkg);
+    }
+    check_package_identifiers(&packages, &mut problems);
+
+    Ok(problems)
+}
+
+/// Check that no two package identifiers only differ by case.
+fn check_package_identifiers(packages: &[Package], problems: &mut Problems) {
+    let identifiers: Vec<_> = packages.iter().map(|pkg| pkg.identifier()).collect();
+    for (dup, original) in find_case_duplicates(&identifiers) {
+        problems.error(
+            packages[dup].path(),
+            format!(
+                "package identifier `{}` only differs by case from `{}` at {}",
+                identifiers[dup],
+                identifiers[original],
+                packages[original].path().display()
+            ),
+        );
+    }
+}
+
+/// Check that no two versions of a package only differ by case.
+fn check_version_names(versions: &[Version], problems: &mut Problems) {
+    let names: Vec<_> = versions.iter().map(|ver| ver.name()).collect();
+    for (dup, original) in find_case_duplicates(&names) {
+        problems.error(
+            versions[dup].path(),
+            format!(
+                "version `{}` only differs by case from version `{}`",
+                names[dup], names[original]
+            ),
+        );
+    }
+}
+
+/// Check the package-level configuration.
+fn check_package(pkg: &Package, problems: &mut Problems) {
+    if pkg
+        .category()
+        .components()
+        .any(|x| x == relative_path::Component::ParentDir)
+    {
+        problems.error(
+            pkg.path(),
+            format!(
+                "package category cannot refer to the parent directory: `{}`",
+                pkg.category()
+            ),
+        );
+    }
+
+    if let Err(err) = pkg.entrypoints() {
+        problems.error(pkg.path(), format!("invalid entrypoint pattern: {err}"));
+    }
+}
+
+/// Check that the version has sources, and that its entrypoints match the sources.
+fn check_sources(pkg: &Package, ver: &Version, problems: &mut Problems) {
+    let sources = match ver.sources(pkg) {
+        Ok(sources) => sources,
+        Err(err) => {
+            problems.error(ver.path(), err.to_string());
+            return;
+        }
+    };
+    if let Err(err) = ver.check_entrypoints(pkg, &sources) {
+        problems.error(ver.path(), err.to_string());
+    }
+    for relpath in ver.target_sources() {
+        if !sources
+            .iter()
+            .any(|src| src.path() == relpath.to_logical_path(ver.path()))
+        {
+            problems.warning(
+                ver.path(),
+                format!("`targets` refers to a file that isn't a source: {relpath}"),
+            );
+        }
+    }
+    for (dup, original) in ver.file_collisions(pkg, &sources) {
+        problems.error(
+            sources[dup].path(),
+            format!(
+                "file would be installed to the same path as {}",
+                sources[original].path().display()
+            ),
+        );
+    }
+}
+
+/// Check that the version has an author, and that the author is in the repository's allowlist.
+fn check_authors(repo: &Repository, pkg: &Package, ver: &Version, problems: &mut Problems) {
+    let author = ver.resolved_author(repo, pkg);
+    if author.trim().is_empty() {
+        problems.error(ver.path(), "version has no author");
+        return;
+    }
+
+    let Some(allowlist) = repo.authors() else {
+        return;
+    };
+    if ver.author().is_none() && pkg.author().is_none() {
+        problems.warning(
+            ver.path(),
+            format!("no author set for this version, defaulting to repository author `{author}`"),
+        );
+    }
+    if !allowlist.iter().any(|x| x == author) {
+        problems.error(
+            ver.path(),
+            format!("author `{author}` is not in the repository's list of authors"),
+        );
+    }
+}
+
+/// Find problems in a changelog that shouldn't be shown to users.
+fn changelog_problems(text: &str) -> Vec<&'static str> {
+    let mut result = vec![];
+    if text.trim().is_empty() {
+        result.push("changelog is empty");
+    } else if text.contains(templates::CHANGELOG_PLACEHOLDER.trim()) {
+        result.push("changelog still contains the placeholder text");
+    }
+    if text.contains("TODO") {
+        result.push("changelog contains `TODO`");
+    }
+    result
+}
+
+/// Check an edited changelog against the changelog lint rules, if they are enabled in the repository.
+pub(crate) fn check_changelog_text(repo: &Repository, text: &str) -> Result<(), ChangelogProblems> {
+    if !repo.lint_changelogs() {
+        return Ok(());
+    }
+    let problems = changelog_problems(text);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ChangelogProblems(problems))
+    }
+}
+
+/// Check that the version's changelog is ready to be published.
+fn check_changelog(ver: &Version, problems: &mut Problems) {
+    match ver.changelog() {
+        Ok(Some(text)) => {
+            for problem in changelog_problems(&text) {
+                problems.warning(ver.path().join("CHANGELOG.txt"), problem);
+            }
+        }
+        Ok(None) => (),
+        Err(err) => problems.error(ver.path(), format!("failed to read changelog: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changelog_problems_01() {
+        assert!(changelog_problems("Fixed a bug").is_empty());
+        assert_eq!(changelog_problems(" \n"), vec!["changelog is empty"]);
+        assert_eq!(
+            changelog_problems(templates::CHANGELOG_PLACEHOLDER),
+            vec!["changelog still contains the placeholder text"]
+        );
+        assert_eq!(
+            changelog_problems(&templates::changelog_template("1.0", "2024-01-31")),
+            vec!["changelog still contains the placeholder text"]
+        );
+        assert_eq!(
+            changelog_problems("Fixed a bug\nTODO: describe the other fix"),
+            vec!["changelog contains `TODO`"]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Check that the source URLs in the generated index can be downloaded.
+
+use std::{
+    fmt::Display,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+
+use crate::repo::Repository;
+
+/// A source URL to be checked.
+pub(crate) struct UrlCheck {
+    pub(crate) path: PathBuf,
+    pub(crate) url: String,
+    /// Size of the file on disk, if it exists locally
+    pub(crate) expected_length: Option<u64>,
+}
+
+/// The problem found with a URL.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum UrlProblem {
+    Status(u16),
+    LengthMismatch { expected: u64, actual: u64 },
+    Failed(String),
+}
+
+impl Display for UrlProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UrlProblem::Status(404) => write!(f, "not found (404)"),
+            UrlProblem::Status(status) => write!(f, "HTTP status {status}"),
+            UrlProblem::LengthMismatch { expected, actual } => write!(
+                f,
+                "content length is {actual} bytes, but the local file is {expected} bytes"
+            ),
+            UrlProblem::Failed(err) => write!(f, "request failed: {err}"),
+        }
+    }
+}
+
+/// Whether a failed request is worth retrying.
+fn is_transient(problem: &UrlProblem) -> bool {
+    match problem {
+        UrlProblem::Status(status) => *status == 429 || *status >= 500,
+        UrlProblem::LengthMismatch { .. } => false,
+        UrlProblem::Failed(_) => true,
+    }
+}
+
+/// Collect the URLs of every source that would be included in the index.
+pub(crate) fn collect_urls(repo: &Repository) -> Result<Vec<UrlCheck>> {
+    let mut result = vec![];
+    for pkg in repo.indexed_packages()? {
+        for ver in pkg.indexed_versions(repo)? {
+            for src in ver.indexed_sources(&pkg)?.iter() {
+                result.push(UrlCheck {
+                    path: src.path().to_path_buf(),
+                    url: src.url(repo, &pkg, &ver)?,
+                    expected_length: src.path().metadata().ok().map(|x| x.len()),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Request a URL once, using HEAD and falling back to GET for servers that don't support HEAD.
+fn request(agent: &ureq::Agent, check: &UrlCheck) -> Result<(), UrlProblem> {
+    let response = match agent.head(&check.url).call() {
+        Err(ureq::Error::Status(405 | 501, _)) => agent.get(&check.url).call(),
+        response => response,
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, _)) => return Err(UrlProblem::Status(status)),
+        Err(err) => return Err(UrlProblem::Failed(err.to_string())),
+    };
+    let actual = response
+        .header("Content-Length")
+        .and_then(|x| x.parse::<u64>().ok());
+    match (check.expected_length, actual) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            Err(UrlProblem::LengthMismatch { expected, actual })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check a URL, retrying transient failures up to `retries` times.
+fn check_url(agent: &ureq::Agent, check: &UrlCheck, retries: usize) -> Result<(), UrlProblem> {
+    let mut attempt = 0;
+    loop {
+        match request(agent, check) {
+            Err(problem) if attempt < retries && is_transient(&problem) => {
+                attempt += 1;
+                thread::sleep(Duration::from_millis(500 * attempt as u64));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Check all URLs using `concurrency` threads. Returns the checks that failed, in their original order.
+pub(crate) fn verify_urls(
+    checks: &[UrlCheck],
+    concurrency: usize,
+    retries: usize,
+) -> Vec<(&UrlCheck, UrlProblem)> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(30))
+        .build();
+    let next = AtomicUsize::new(0);
+    let problems = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..concurrency.max(1) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(check) = checks.get(i) else {
+                    break;
+                };
+                if let Err(problem) = check_url(&agent, check, retries) {
+                    problems.lock().unwrap().push((i, problem));
+                }
+            });
+        }
+    });
+
+    let mut problems = problems.into_inner().unwrap();
+    problems.sort_by_key(|(i, _)| *i);
+    problems
+        .into_iter()
+        .map(|(i, problem)| (&checks[i], problem))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_transient_01() {
+        assert!(is_transient(&UrlProblem::Status(503)));
+        assert!(is_transient(&UrlProblem::Status(429)));
+        assert!(!is_transient(&UrlProblem::Status(404)));
+        assert!(!is_transient(&UrlProblem::LengthMismatch {
+            expected: 1,
+            actual: 2
+        }));
+    }
+}
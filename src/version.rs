@@ -1,8 +1,5 @@
-use itertools::Itertools;
 use thiserror::Error;
 
-use crate::repo::Version;
-
 #[derive(Error, Debug)]
 #[error("unable to parse this version string, please specify the new version manually: {0}")]
 pub(crate) struct UnknownVersionFormat(String);
@@ -36,18 +33,50 @@ pub(crate) fn increment_version(text: &str) -> Result<String, UnknownVersionForm
     Ok(format!("{}{}", &text[..prefix_len], incremented_suffix))
 }
 
-pub(crate) fn find_latest_version<'a, I>(versions: I) -> Option<&'a str>
-where
-    I: Iterator<Item = &'a str>,
-{
-    versions.max_by(|version_a: &&'a str, version_b: &&'a str| {
-        Version::compare_version_names(*version_a, *version_b)
-    })
+/// A segment of a `major.minor.patch` version name.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum VersionPart {
+    Major = 0,
+    Minor = 1,
+    Patch = 2,
+}
+
+/// Increment the given part of a `major.minor.patch` version name, resetting the parts after it to 0.
+/// Missing parts are treated as 0, e.g. bumping the patch of `1.2` gives `1.2.1`.
+pub(crate) fn increment_version_part(
+    text: &str,
+    part: VersionPart,
+) -> Result<String, UnknownVersionFormat> {
+    let index = part as usize;
+    let mut segments: Vec<String> = text.split('.').map(String::from).collect();
+    while segments.len() <= index {
+        segments.push("0".into());
+    }
+    // the parts up to the incremented one must be plain numbers, later parts are reset anyway
+    let Ok(value) = segments[index].parse::<u32>() else {
+        return Err(UnknownVersionFormat(text.into()));
+    };
+    if segments[..index].iter().any(|x| x.parse::<u32>().is_err()) {
+        return Err(UnknownVersionFormat(text.into()));
+    }
+    segments[index] = (value + 1).to_string();
+    for segment in segments[index + 1..].iter_mut() {
+        *segment = "0".into();
+    }
+    Ok(segments.join("."))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repo::Version;
+
+    fn find_latest_version<'a, I>(versions: I) -> Option<&'a str>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        versions.max_by(|a, b| Version::compare_version_names(a, b))
+    }
 
     #[test]
     fn test_increment_01() {
@@ -69,6 +98,27 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_increment_part_01() {
+        assert_eq!(
+            increment_version_part("1.2.3", VersionPart::Major).unwrap(),
+            "2.0.0"
+        );
+        assert_eq!(
+            increment_version_part("1.2.3", VersionPart::Minor).unwrap(),
+            "1.3.0"
+        );
+        assert_eq!(
+            increment_version_part("1.2", VersionPart::Patch).unwrap(),
+            "1.2.1"
+        );
+        assert_eq!(
+            increment_version_part("1.2.3b", VersionPart::Minor).unwrap(),
+            "1.3.0"
+        );
+        assert!(increment_version_part("1.2.3b", VersionPart::Patch).is_err());
+    }
+
     #[test]
     fn test_latest_01() {
         let result = find_latest_version(vec!["0.1.0", "0.1.15"].into_iter()).unwrap();
@@ -103,4 +153,11 @@ mod tests {
         let expected = "0.1.15b";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_latest_06() {
+        let result = find_latest_version(vec!["0.1.10", "0.1.9"].into_iter()).unwrap();
+        let expected = "0.1.10";
+        assert_eq!(result, expected);
+    }
 }
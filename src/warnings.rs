@@ -0,0 +1,94 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A warning emitted while processing the repository.
+#[derive(Debug, Clone)]
+pub(crate) struct Warning {
+    /// A short name for the kind of warning, e.g. `unreadable-version`
+    pub(crate) rule: &'static str,
+    /// The file or folder that caused the warning
+    pub(crate) path: PathBuf,
+    pub(crate) message: String,
+}
+
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// Log a warning, and remember it so that a summary can be shown at the end of the command.
+/// The same warning is only logged once, even if the path is processed multiple times.
+pub(crate) fn warn(rule: &'static str, path: &Path, message: String) {
+    let mut warnings = WARNINGS.lock().unwrap();
+    if warnings
+        .iter()
+        .any(|x| x.rule == rule && x.path == path && x.message == message)
+    {
+        return;
+    }
+    log::warn!("{message}");
+    warnings.push(Warning {
+        rule,
+        path: path.into(),
+        message,
+    });
+}
+
+/// Return all warnings emitted so far, and clear the list.
+pub(crate) fn take() -> Vec<Warning> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap())
+}
+
+/// Group warnings by package, then by rule.
+/// The package is the first folder of the warning's path within the repository.
+pub(crate) fn group<'a>(
+    warnings: &'a [Warning],
+    repo_path: &Path,
+) -> BTreeMap<String, BTreeMap<&'static str, Vec<&'a Warning>>> {
+    let mut result: BTreeMap<String, BTreeMap<&'static str, Vec<&Warning>>> = BTreeMap::new();
+    for warning in warnings {
+        let package = warning
+            .path
+            .strip_prefix(repo_path)
+            .ok()
+            .and_then(|x| x.components().next())
+            .map(|x| x.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_else(|| "(repository)".into());
+        result
+            .entry(package)
+            .or_default()
+            .entry(warning.rule)
+            .or_default()
+            .push(warning);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_01() {
+        let warning = |rule, path: &str| Warning {
+            rule,
+            path: path.into(),
+            message: "message".into(),
+        };
+        let warnings = vec![
+            warning("unreadable-version", "/repo/pkg-a/0.0.1"),
+            warning("unreadable-version", "/repo/pkg-a/0.0.2"),
+            warning("unreadable-source", "/repo/pkg-a/0.0.2/foo.lua"),
+            warning("unreadable-package", "/repo/pkg-b"),
+            warning("unreadable-entry", "/repo"),
+        ];
+        let result = group(&warnings, Path::new("/repo"));
+        assert_eq!(
+            result.keys().collect::<Vec<_>>(),
+            vec!["(repository)", "pkg-a", "pkg-b"]
+        );
+        assert_eq!(result["pkg-a"]["unreadable-version"].len(), 2);
+        assert_eq!(result["pkg-a"]["unreadable-source"].len(), 1);
+        assert_eq!(result["pkg-b"]["unreadable-package"].len(), 1);
+    }
+}
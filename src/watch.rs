@@ -0,0 +1,112 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+
+use crate::repo::Repository;
+
+/// How long to wait for more changes before running the callback, so that saving many files at once
+/// only triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Check whether a changed path should trigger a new run.
+/// Changes inside `.git` and the cache folder, and to the given ignored files and folders (e.g. the output of the
+/// callback) are skipped.
+fn is_relevant(path: &Path, repo_path: &Path, ignored: &[PathBuf]) -> bool {
+    if ignored.iter().any(|x| path.starts_with(x)) {
+        return false;
+    }
+    let Ok(relpath) = path.strip_prefix(repo_path) else {
+        return true;
+    };
+    !relpath
+        .components()
+        .any(|x| x.as_os_str() == ".git" || x.as_os_str() == Repository::CACHE_DIRNAME)
+}
+
+/// Run the callback once, then again whenever a file in the repository changes. Never returns unless the
+/// watcher fails.
+pub(crate) fn watch(
+    repo_path: &Path,
+    ignored: &[PathBuf],
+    mut callback: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let repo_path = std::path::absolute(repo_path)?;
+    let ignored: Vec<PathBuf> = ignored
+        .iter()
+        .filter_map(|x| std::path::absolute(x).ok())
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&repo_path, RecursiveMode::Recursive)?;
+
+    let mut run = || {
+        if let Err(err) = callback() {
+            log::error!("{err}");
+        }
+        println!("Watching {} for changes...", repo_path.display());
+    };
+    run();
+
+    loop {
+        let event: notify::Event = rx.recv()??;
+        let mut changed = event
+            .paths
+            .iter()
+            .any(|x| is_relevant(x, &repo_path, &ignored));
+
+        // collect any other changes that happen shortly after
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            changed |= event?
+                .paths
+                .iter()
+                .any(|x| is_relevant(x, &repo_path, &ignored));
+        }
+
+        if changed {
+            run();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_relevant_01() {
+        let repo = Path::new("/repo");
+        let ignored = vec![
+            PathBuf::from("/repo/index.xml"),
+            PathBuf::from("/repo/site"),
+        ];
+        assert!(is_relevant(
+            Path::new("/repo/pkg/0.0.1/a.lua"),
+            repo,
+            &ignored
+        ));
+        assert!(is_relevant(
+            Path::new("/repo/pkg/README.md"),
+            repo,
+            &ignored
+        ));
+        assert!(!is_relevant(Path::new("/repo/index.xml"), repo, &ignored));
+        assert!(!is_relevant(
+            Path::new("/repo/site/pkg/index.html"),
+            repo,
+            &ignored
+        ));
+        assert!(is_relevant(Path::new("/repo/site.lua"), repo, &ignored));
+        assert!(!is_relevant(Path::new("/repo/.git/HEAD"), repo, &ignored));
+        assert!(!is_relevant(
+            Path::new("/repo/.reapack-indexer-cache/abc.rtf"),
+            repo,
+            &ignored
+        ));
+    }
+}
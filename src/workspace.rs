@@ -0,0 +1,111 @@
+//! Keep packages in sync with the folders they are developed in, publishing a new version whenever a folder changes.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    config::WorkspaceConfig,
+    hash::{hash_file, hex},
+    repo::{Package, Repository, Version},
+};
+
+/// Files that are added to version folders when publishing, so they are not compared against the source folder.
+const GENERATED_FILES: &[&str] = &[Version::CONFIG_FILENAME, "CHANGELOG.txt"];
+
+#[derive(Error, Debug)]
+#[error("package `{0}` in the workspace does not exist in the repository")]
+pub(crate) struct WorkspacePackageNotFound(String);
+
+#[derive(Error, Debug)]
+#[error("source folder of package `{0}` does not exist: {1}")]
+pub(crate) struct WorkspaceSourceNotFound(String, PathBuf);
+
+/// Hash the contents of a folder, including the relative path of every file.
+/// If a file is given, only its name and contents are hashed.
+fn hash_folder(path: &Path) -> Result<String> {
+    let mut files: Vec<(String, PathBuf)> = vec![];
+    if path.is_dir() {
+        for entry in walkdir::WalkDir::new(path) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relpath = entry.path().strip_prefix(path)?;
+                files.push((
+                    relpath.to_string_lossy().replace('\\', "/"),
+                    entry.into_path(),
+                ));
+            }
+        }
+    } else if let Some(name) = path.file_name() {
+        files.push((name.to_string_lossy().to_string(), path.into()));
+    }
+    files.retain(|(relpath, _)| !GENERATED_FILES.contains(&relpath.as_str()));
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for (relpath, path) in files.iter() {
+        let (hash, _) = hash_file(path)?;
+        hasher.update(format!("{relpath}\t{hash}\n"));
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// A package in the workspace.
+pub(crate) struct SyncTarget {
+    pub(crate) pkg: Package,
+    pub(crate) source: PathBuf,
+    /// The latest version of the package, which the source folder is compared against
+    pub(crate) latest: Option<Version>,
+    /// Whether the source folder differs from the latest version, or None if the latest version is hosted
+    /// elsewhere, so its files can't be compared
+    pub(crate) changed: Option<bool>,
+}
+
+impl SyncTarget {
+    /// Name of the version that would be published.
+    pub(crate) fn next_version(&self) -> Result<String> {
+        Ok(match &self.latest {
+            Some(latest) => Version::increment_version(&latest.name())?,
+            None => "0.0.1".into(),
+        })
+    }
+}
+
+/// Read the workspace file, then compare every source folder against the latest version of its package.
+pub(crate) fn plan(repo: &Repository, workspace_path: &Path) -> Result<Vec<SyncTarget>> {
+    let config: WorkspaceConfig = toml::from_str(&fs::read_to_string(workspace_path)?)?;
+    let base = workspace_path.parent().unwrap_or(Path::new("."));
+
+    let mut result = vec![];
+    for (identifier, source) in config.packages {
+        let Some(pkg) = repo.package(&identifier)? else {
+            return Err(WorkspacePackageNotFound(identifier).into());
+        };
+        let source = base.join(source);
+        if !source.exists() {
+            return Err(WorkspaceSourceNotFound(identifier, source).into());
+        }
+        // like `publish`, copying the repository into a version would duplicate every package
+        if crate::paths_overlap(&source.canonicalize()?, &repo.path().canonicalize()?) {
+            return Err(crate::SourceOverlapsRepository(source).into());
+        }
+        let latest = pkg.latest_version()?;
+        let changed = match &latest {
+            Some(latest) if latest.remote().is_some() => None,
+            Some(latest) => Some(hash_folder(&source)? != hash_folder(latest.path())?),
+            None => Some(true),
+        };
+        result.push(SyncTarget {
+            pkg,
+            source,
+            latest,
+            changed,
+        });
+    }
+    Ok(result)
+}